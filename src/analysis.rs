@@ -0,0 +1,419 @@
+// Game analysis
+// Replays a game, searches every position and judges each move by how far
+// it lowers the mover's evaluation below that of the engine's best move.
+// Inaccuracies, mistakes and blunders get the usual NAGs ($6, $2, $4) and
+// the engine's line as a variation. Scores are capped before they are
+// compared, so choosing a slower mate is not a blunder but giving away a
+// won position is.
+
+use crate::board::{Board, Color, Move, PieceType};
+use crate::evaluation::Evaluator;
+use crate::pgn::{format_score, move_to_san, score_comment, PgnGame};
+use crate::search::{search, SearchInfo, SearchLimits, SearchSignals, CHECKMATE_SCORE, MATE_BOUND};
+use crate::transposition::TranspositionTable;
+
+/// Depth used when neither depth nor movetime is given
+pub const DEFAULT_ANALYSIS_DEPTH: i32 = 8;
+
+/// Hash size used for each game, in MB
+const ANALYSIS_HASH_MB: usize = 16;
+
+/// Scores are capped to this many centipawns before moves are compared
+const SCORE_CAP: i32 = 500;
+
+/// Drops in the capped score (centipawns) that make a move an inaccuracy,
+/// a mistake or a blunder
+const INACCURACY_DROP: i32 = 50;
+const MISTAKE_DROP: i32 = 100;
+const BLUNDER_DROP: i32 = 300;
+
+/// Score from which a position counts as won
+pub const WINNING_SCORE: i32 = 400;
+
+// =============================================================================
+// Judgements
+// =============================================================================
+
+/// How bad a move was compared with the engine's choice
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Judgement {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    /// Classify a drop in the mover's capped score
+    pub fn from_drop(drop: i32) -> Option<Self> {
+        if drop >= BLUNDER_DROP {
+            Some(Judgement::Blunder)
+        } else if drop >= MISTAKE_DROP {
+            Some(Judgement::Mistake)
+        } else if drop >= INACCURACY_DROP {
+            Some(Judgement::Inaccuracy)
+        } else {
+            None
+        }
+    }
+
+    /// Numeric annotation glyph: $6 (?!), $2 (?) or $4 (??)
+    pub fn nag(self) -> u8 {
+        match self {
+            Judgement::Inaccuracy => 6,
+            Judgement::Mistake => 2,
+            Judgement::Blunder => 4,
+        }
+    }
+
+    /// Suffix used in text: "?!", "?" or "??"
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Judgement::Inaccuracy => "?!",
+            Judgement::Mistake => "?",
+            Judgement::Blunder => "??",
+        }
+    }
+}
+
+impl std::fmt::Display for Judgement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Judgement::Inaccuracy => write!(f, "inaccuracy"),
+            Judgement::Mistake => write!(f, "mistake"),
+            Judgement::Blunder => write!(f, "blunder"),
+        }
+    }
+}
+
+/// Score limited to ±SCORE_CAP, so all clearly won positions compare equal
+fn capped(score: i32) -> i32 {
+    score.clamp(-SCORE_CAP, SCORE_CAP)
+}
+
+/// A child position's score seen from its parent: negated, and one ply
+/// further from mate
+fn parent_score(score: i32) -> i32 {
+    let score = -score;
+    if score >= MATE_BOUND {
+        score - 1
+    } else if score <= -MATE_BOUND {
+        score + 1
+    } else {
+        score
+    }
+}
+
+// =============================================================================
+// Analysis
+// =============================================================================
+
+/// Engine verdict on one move of a game
+pub struct MoveAnalysis {
+    /// Index of the move in the game
+    pub ply: usize,
+    /// Side that played the move
+    pub side: Color,
+    /// Fullmove number of the move
+    pub number: u32,
+    pub played: Move,
+    /// SAN of the played move, with check and mate markers
+    pub san: String,
+    /// Search of the position before the move; its PV is the best line
+    pub best: SearchInfo,
+    /// Score after the move, from the mover's point of view
+    pub played_score: i32,
+    /// Search of the position after the move; None if it has no legal moves
+    pub reply: Option<SearchInfo>,
+    pub judgement: Option<Judgement>,
+}
+
+impl MoveAnalysis {
+    /// Whether the engine would have played the same move
+    pub fn is_best(&self) -> bool {
+        self.best.best_move().same_as(&self.played)
+    }
+
+    /// How far the move lowered the mover's capped score
+    pub fn drop(&self) -> i32 {
+        if self.is_best() {
+            0
+        } else {
+            (capped(self.best.score) - capped(self.played_score)).max(0)
+        }
+    }
+
+    /// Whether the move turned a won position into one that is not won
+    pub fn lets_win_slip(&self) -> bool {
+        !self.is_best() && self.best.score >= WINNING_SCORE && self.played_score < WINNING_SCORE
+    }
+
+    /// Move as written in text: "12. Kd2??" or "12... Rd5+?"
+    pub fn label(&self) -> String {
+        let dots = match self.side {
+            Color::White => ".",
+            Color::Black => "...",
+        };
+        let symbol = self.judgement.map_or("", Judgement::symbol);
+        format!("{}{} {}{}", self.number, dots, self.san, symbol)
+    }
+}
+
+/// Search one position; None if it has no legal moves
+fn search_position<E: Evaluator + ?Sized>(
+    board: &mut Board,
+    limits: &SearchLimits,
+    tt: &mut TranspositionTable,
+    evaluator: &E,
+) -> Option<SearchInfo> {
+    let signals = SearchSignals::default();
+    search(board, limits, tt, evaluator, None, &signals, &mut |_| {})
+}
+
+/// Search every position of `game` and judge each move
+/// `on_move` is called as each move is judged, for progress output
+pub fn analyze_game<E: Evaluator + ?Sized>(
+    game: &PgnGame,
+    limits: &SearchLimits,
+    evaluator: &E,
+    on_move: &mut dyn FnMut(&MoveAnalysis),
+) -> Vec<MoveAnalysis> {
+    let mut tt = TranspositionTable::new(ANALYSIS_HASH_MB);
+    let mut board = game.start.clone();
+    board.clear_history();
+    let mut before = search_position(&mut board, limits, &mut tt, evaluator);
+    let mut analysis = Vec::new();
+
+    for (ply, &mv) in game.moves.iter().enumerate() {
+        let best = match before.take() {
+            Some(info) => info,
+            None => break,
+        };
+        let side = board.side_to_move();
+        let number = board.fullmove_number();
+        let san = move_to_san(&mut board, &mv);
+        board.apply_move(mv);
+
+        // The reply search is also the next move's "before" search
+        let reply = search_position(&mut board, limits, &mut tt, evaluator);
+        let played_score = if board.is_checkmate(side.opposite()) {
+            CHECKMATE_SCORE - 1
+        } else if board.repetition_count() >= 2 || board.is_fifty_move_draw() {
+            0
+        } else {
+            reply.as_ref().map_or(0, |info| parent_score(info.score))
+        };
+
+        let mut entry = MoveAnalysis {
+            ply,
+            side,
+            number,
+            played: mv,
+            san,
+            best,
+            played_score,
+            reply: reply.clone(),
+            judgement: None,
+        };
+        entry.judgement = Judgement::from_drop(entry.drop());
+        on_move(&entry);
+        analysis.push(entry);
+        before = reply;
+    }
+    analysis
+}
+
+/// The game with the analysis written into it: every move gets its score,
+/// and judged moves a NAG, the best score and the engine's line
+/// Comments already in the game are kept, with the analysis appended after a ";"
+pub fn annotate(game: &PgnGame, analysis: &[MoveAnalysis], annotator: &str) -> PgnGame {
+    let mut annotated = game.clone();
+    annotated.set_tag("Annotator", annotator);
+    let plies = game.moves.len();
+    annotated.comments.resize(plies, None);
+    annotated.nags.resize(plies, None);
+    annotated.variations.resize(plies, None);
+
+    for (i, entry) in analysis.iter().enumerate().take(plies) {
+        let mut comment = match &entry.reply {
+            Some(info) => score_comment(entry.played_score, info.depth, info.time),
+            None => format_score(entry.played_score),
+        };
+        if let Some(judgement) = entry.judgement {
+            comment.push_str(&format!(", {}; best {}", judgement, format_score(entry.best.score)));
+            annotated.nags[i] = Some(judgement.nag());
+            annotated.variations[i] = Some(entry.best.pv.clone());
+        }
+        annotated.comments[i] = Some(match annotated.comments[i].take() {
+            Some(original) => format!("{}; {}", original, comment),
+            None => comment,
+        });
+    }
+    annotated
+}
+
+// =============================================================================
+// Summary
+// =============================================================================
+
+/// The side that has an Amazon in the starting position, if only one does
+pub fn amazon_side(board: &Board) -> Option<Color> {
+    let mut sides = Vec::new();
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                if piece.piece_type == PieceType::Amazon && !sides.contains(&piece.color) {
+                    sides.push(piece.color);
+                }
+            }
+        }
+    }
+    match sides[..] {
+        [side] => Some(side),
+        _ => None,
+    }
+}
+
+/// Inaccuracies, mistakes and blunders of one side
+pub fn judgement_counts(analysis: &[MoveAnalysis], side: Color) -> [usize; 3] {
+    let mut counts = [0; 3];
+    for entry in analysis.iter().filter(|entry| entry.side == side) {
+        match entry.judgement {
+            Some(Judgement::Inaccuracy) => counts[0] += 1,
+            Some(Judgement::Mistake) => counts[1] += 1,
+            Some(Judgement::Blunder) => counts[2] += 1,
+            None => {}
+        }
+    }
+    counts
+}
+
+/// Moves with which the Amazon side gave away a won position
+pub fn slipped_wins<'a>(game: &PgnGame, analysis: &'a [MoveAnalysis]) -> Vec<&'a MoveAnalysis> {
+    match amazon_side(&game.start) {
+        Some(side) => analysis.iter().filter(|entry| entry.side == side && entry.lets_win_slip()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Text summary of a game's analysis: judgements per side and the moves
+/// where the Amazon side let the win slip
+pub fn summarize(game: &PgnGame, analysis: &[MoveAnalysis]) -> String {
+    let white = game.tag("White").unwrap_or("?");
+    let black = game.tag("Black").unwrap_or("?");
+    let mut text = format!("{} - {} {}\n", white, black, game.result);
+
+    for (side, name) in [(Color::White, "White"), (Color::Black, "Black")] {
+        let [inaccuracies, mistakes, blunders] = judgement_counts(analysis, side);
+        text.push_str(&format!(
+            "  {}: {} inaccuracies, {} mistakes, {} blunders\n",
+            name, inaccuracies, mistakes, blunders
+        ));
+    }
+
+    for entry in slipped_wins(game, analysis) {
+        let mut board = game.start.clone();
+        for mv in &game.moves[..entry.ply] {
+            board.apply_move(*mv);
+        }
+        let best = move_to_san(&mut board, &entry.best.best_move());
+        text.push_str(&format!(
+            "  Win slipped: {} ({} -> {}), best was {}\n",
+            entry.label(),
+            format_score(entry.best.score),
+            format_score(entry.played_score),
+            best
+        ));
+    }
+    text
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval_params::EvalParams;
+    use crate::evaluation::HandCrafted;
+    use crate::pgn::parse_pgn;
+
+    /// A game from a FEN and SAN movetext
+    fn game(fen: &str, movetext: &str) -> PgnGame {
+        let text = format!("[White \"A\"]\n[Black \"B\"]\n[FEN \"{}\"]\n\n{}", fen, movetext);
+        parse_pgn(&text).unwrap().remove(0)
+    }
+
+    fn analyze(game: &PgnGame) -> Vec<MoveAnalysis> {
+        analyze_game(game, &SearchLimits::depth(3), &HandCrafted::new(EvalParams::default()), &mut |_| {})
+    }
+
+    #[test]
+    fn test_judgements() {
+        assert_eq!(Judgement::from_drop(10), None);
+        assert_eq!(Judgement::from_drop(INACCURACY_DROP), Some(Judgement::Inaccuracy));
+        assert_eq!(Judgement::from_drop(150), Some(Judgement::Mistake));
+        assert_eq!(Judgement::from_drop(2 * SCORE_CAP), Some(Judgement::Blunder));
+        assert_eq!(Judgement::Blunder.nag(), 4);
+        assert_eq!(parent_score(-CHECKMATE_SCORE), CHECKMATE_SCORE - 1);
+        assert_eq!(parent_score(CHECKMATE_SCORE - 2), -CHECKMATE_SCORE + 3);
+        assert_eq!(parent_score(120), -120);
+    }
+
+    #[test]
+    fn test_mate_is_best() {
+        let game = game("k7/8/1K6/8/8/8/8/3A4 w - - 0 1", "1. Ad8# 1-0");
+        let analysis = analyze(&game);
+        assert_eq!(analysis.len(), 1);
+        assert!(analysis[0].is_best());
+        assert_eq!(analysis[0].played_score, CHECKMATE_SCORE - 1);
+        assert_eq!(analysis[0].judgement, None);
+        assert!(analysis[0].reply.is_none());
+    }
+
+    #[test]
+    fn test_stalemate_is_a_blunder() {
+        // Ad7 stalemates the king in the corner instead of mating with Ad8
+        let game = game("k7/8/1K6/8/8/8/8/3A4 w - - 0 1", "1. Ad7 1/2-1/2");
+        let analysis = analyze(&game);
+        assert!(game.moves.len() == 1 && analysis.len() == 1);
+        assert_eq!(analysis[0].played_score, 0);
+        assert_eq!(analysis[0].judgement, Some(Judgement::Blunder));
+        assert!(analysis[0].lets_win_slip());
+        assert_eq!(analysis[0].label(), "1. Ad7??");
+
+        let slipped = slipped_wins(&game, &analysis);
+        assert_eq!(slipped.len(), 1);
+        let summary = summarize(&game, &analysis);
+        assert!(summary.contains("White: 0 inaccuracies, 0 mistakes, 1 blunders"));
+        assert!(summary.contains("Win slipped: 1. Ad7?? (+M1 -> +0.00), best was Ad8#"));
+    }
+
+    #[test]
+    fn test_annotated_pgn() {
+        let game = game("k7/8/1K6/8/8/8/8/3A4 w - - 0 1", "1. Ad7 1/2-1/2");
+        let analysis = analyze(&game);
+        let annotated = annotate(&game, &analysis, "test");
+        let text = annotated.to_pgn();
+        assert!(text.contains("[Annotator \"test\"]"));
+        assert!(text.contains("1. Ad7 $4 {+0.00, blunder; best +M1} (1. Ad8#) 1/2-1/2"));
+        // The annotated game reads back with its NAGs
+        let read = parse_pgn(&text).unwrap();
+        assert_eq!(read[0].nags, [Some(4)]);
+    }
+
+    #[test]
+    fn test_annotation_keeps_comments() {
+        let game = game("k7/8/1K6/8/8/8/8/3A4 w - - 0 1", "1. Ad7 {Played fast} {Stalemate} 1/2-1/2");
+        let analysis = analyze(&game);
+        let text = annotate(&game, &analysis, "test").to_pgn();
+        assert!(text.contains("1. Ad7 $4 {Played fast; +0.00, blunder; best +M1} (1. Ad8#) {Stalemate} 1/2-1/2"));
+    }
+
+    #[test]
+    fn test_amazon_side() {
+        assert_eq!(amazon_side(&Board::setup_amazon_vs_rook()), Some(Color::White));
+        assert_eq!(amazon_side(&Board::from_fen("3ak3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap()), Some(Color::Black));
+        assert_eq!(amazon_side(&Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap()), None);
+    }
+}
@@ -0,0 +1,1051 @@
+// Bitboard board representation
+// Each (color, piece type) pair is stored as a 64-bit set of squares, so attack
+// detection is a handful of table lookups and ANDs instead of generating moves.
+//
+// Square indices follow the mailbox orientation: index = row * 8 + col,
+// so bit 0 = a8, bit 7 = h8, bit 56 = a1, bit 63 = h1.
+
+use crate::board::{
+    back_rank, castling_squares, file_span, Board, CastleSide, CastlingRights, Color, FenError, Move, MoveKind,
+    Piece, PieceType, Square, STANDARD_PROMOTIONS,
+};
+use crate::pieces::pawn::PawnMoves;
+use crate::pieces::betza::{Ray, UNLIMITED};
+use crate::zobrist::ZOBRIST;
+use std::sync::OnceLock;
+
+/// A set of squares, one bit per square
+pub type Bitboard = u64;
+
+// =============================================================================
+// Square Helpers
+// =============================================================================
+
+/// Convert a (row, col) square to a bit index
+pub fn square_index(square: Square) -> usize {
+    (square.0 as usize) * 8 + square.1 as usize
+}
+
+/// Convert a bit index back to a (row, col) square
+pub fn index_square(index: usize) -> Square {
+    ((index / 8) as u8, (index % 8) as u8)
+}
+
+/// Bitboard with only the given square set
+pub fn square_bb(square: Square) -> Bitboard {
+    1u64 << square_index(square)
+}
+
+/// Iterator over the indices of the set bits, lowest first
+pub struct BitIter(Bitboard);
+
+impl Iterator for BitIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1; // Clear the lowest set bit
+        Some(index)
+    }
+}
+
+/// Iterate over the squares (as indices) in a bitboard
+pub fn bits(bb: Bitboard) -> BitIter {
+    BitIter(bb)
+}
+
+// =============================================================================
+// Precomputed Attack Tables
+// =============================================================================
+
+/// Ray directions as (row delta, col delta)
+/// The first four increase the square index, the last four decrease it
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),   // east
+    (1, -1),  // south-west
+    (1, 0),   // south
+    (1, 1),   // south-east
+    (0, -1),  // west
+    (-1, 1),  // north-east
+    (-1, 0),  // north
+    (-1, -1), // north-west
+];
+
+/// Orthogonal and diagonal direction indices into DIRECTIONS
+const ROOK_DIRECTIONS: [usize; 4] = [0, 2, 4, 6];
+const BISHOP_DIRECTIONS: [usize; 4] = [1, 3, 5, 7];
+
+const KING_OFFSETS: [(i8, i8); 8] = [
+    (-1, -1), (-1, 0), (-1, 1),
+    ( 0, -1),          ( 0, 1),
+    ( 1, -1), ( 1, 0), ( 1, 1),
+];
+
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (-2, -1), (-2, 1), (-1, -2), (-1, 2),
+    ( 1, -2), ( 1, 2), ( 2, -1), ( 2, 1),
+];
+
+/// Attack tables for leapers, and empty-board rays for sliders
+struct AttackTables {
+    king: [Bitboard; 64],
+    knight: [Bitboard; 64],
+    /// rays[direction][square]: all squares from `square` to the edge in that direction
+    rays: [[Bitboard; 64]; 8],
+}
+
+/// Built at compile time
+static ATTACKS: AttackTables = AttackTables::generate();
+
+/// Bitboard of all squares reached by one jump with each offset
+const fn leaper_table(offsets: &[(i8, i8); 8]) -> [Bitboard; 64] {
+    let mut table = [0u64; 64];
+    let mut square = 0;
+    while square < 64 {
+        let row = (square / 8) as i8;
+        let col = (square % 8) as i8;
+        let mut i = 0;
+        while i < offsets.len() {
+            let r = row + offsets[i].0;
+            let c = col + offsets[i].1;
+            if r >= 0 && r < 8 && c >= 0 && c < 8 {
+                table[square] |= 1u64 << (r as usize * 8 + c as usize);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+impl AttackTables {
+    const fn generate() -> Self {
+        let mut rays = [[0u64; 64]; 8];
+        let mut dir = 0;
+        while dir < 8 {
+            let (dr, dc) = DIRECTIONS[dir];
+            let mut square = 0;
+            while square < 64 {
+                let mut r = (square / 8) as i8 + dr;
+                let mut c = (square % 8) as i8 + dc;
+                while r >= 0 && r < 8 && c >= 0 && c < 8 {
+                    rays[dir][square] |= 1u64 << (r as usize * 8 + c as usize);
+                    r += dr;
+                    c += dc;
+                }
+                square += 1;
+            }
+            dir += 1;
+        }
+
+        AttackTables {
+            king: leaper_table(&KING_OFFSETS),
+            knight: leaper_table(&KNIGHT_OFFSETS),
+            rays,
+        }
+    }
+}
+
+/// Squares attacked by a King
+pub fn king_attacks(index: usize) -> Bitboard {
+    ATTACKS.king[index]
+}
+
+/// Squares attacked by a Knight (also the jumping half of the Amazon)
+pub fn knight_attacks(index: usize) -> Bitboard {
+    ATTACKS.knight[index]
+}
+
+/// Slider attacks along one ray, stopping at (and including) the first blocker
+fn ray_attacks(dir: usize, index: usize, occupancy: Bitboard) -> Bitboard {
+    let ray = ATTACKS.rays[dir][index];
+    let blockers = ray & occupancy;
+    if blockers == 0 {
+        return ray;
+    }
+    // The nearest blocker is the lowest bit for increasing directions, highest otherwise
+    let blocker = if dir < 4 {
+        blockers.trailing_zeros() as usize
+    } else {
+        63 - blockers.leading_zeros() as usize
+    };
+    ray ^ ATTACKS.rays[dir][blocker]
+}
+
+/// Squares attacked by a Rook given the board occupancy
+pub fn rook_attacks(index: usize, occupancy: Bitboard) -> Bitboard {
+    ROOK_DIRECTIONS
+        .iter()
+        .fold(0, |acc, &dir| acc | ray_attacks(dir, index, occupancy))
+}
+
+/// Squares attacked diagonally given the board occupancy
+pub fn bishop_attacks(index: usize, occupancy: Bitboard) -> Bitboard {
+    BISHOP_DIRECTIONS
+        .iter()
+        .fold(0, |acc, &dir| acc | ray_attacks(dir, index, occupancy))
+}
+
+/// Squares attacked by an Amazon (Queen + Knight)
+pub fn amazon_attacks(index: usize, occupancy: Bitboard) -> Bitboard {
+    rook_attacks(index, occupancy) | bishop_attacks(index, occupancy) | knight_attacks(index)
+}
+
+/// Movement pattern of one piece type and color, derived from its Betza movement
+struct PieceAttacks {
+    /// Squares reached by single leaps that can capture
+    capture_leaps: [Bitboard; 64],
+    /// Squares reached by single leaps that can move without capturing
+    quiet_leaps: [Bitboard; 64],
+    /// Slides like a Rook (unlimited W rider)
+    orthogonal: bool,
+    /// Slides like a Bishop (unlimited F rider)
+    diagonal: bool,
+    /// Any other rider (e.g. Nightrider, limited range, move or capture only),
+    /// walked square by square
+    riders: Vec<Ray>,
+}
+
+impl PieceAttacks {
+    fn new(piece_type: PieceType, color: Color) -> Self {
+        let mut attacks = PieceAttacks {
+            capture_leaps: [0; 64],
+            quiet_leaps: [0; 64],
+            orthogonal: false,
+            diagonal: false,
+            riders: Vec::new(),
+        };
+
+        for ray in piece_type.movement().rays() {
+            let ray = ray.oriented(color);
+            let unit = ray.dr.abs() <= 1 && ray.dc.abs() <= 1;
+            let full_slider = unit && ray.range == UNLIMITED && ray.can_move && ray.can_capture;
+            if ray.is_leap() {
+                for square in 0..64 {
+                    let target = ray_walk(&ray, square, !0);
+                    if ray.can_capture {
+                        attacks.capture_leaps[square] |= target;
+                    }
+                    if ray.can_move {
+                        attacks.quiet_leaps[square] |= target;
+                    }
+                }
+            } else if full_slider && (ray.dr == 0 || ray.dc == 0) {
+                attacks.orthogonal = true;
+            } else if full_slider {
+                attacks.diagonal = true;
+            } else {
+                attacks.riders.push(ray);
+            }
+        }
+        attacks
+    }
+
+    /// Squares reached along sliding rays (including the first blocker)
+    fn slides(&self, index: usize, occupancy: Bitboard, capture: bool) -> Bitboard {
+        let mut slides = 0;
+        if self.orthogonal {
+            slides |= rook_attacks(index, occupancy);
+        }
+        if self.diagonal {
+            slides |= bishop_attacks(index, occupancy);
+        }
+        for ray in &self.riders {
+            if (capture && ray.can_capture) || (!capture && ray.can_move) {
+                slides |= ray_walk(ray, index, occupancy);
+            }
+        }
+        slides
+    }
+}
+
+/// Squares along a ray from `index`, stopping at (and including) the first blocker
+fn ray_walk(ray: &Ray, index: usize, occupancy: Bitboard) -> Bitboard {
+    let mut attacks = 0;
+    let mut row = (index / 8) as i8;
+    let mut col = (index % 8) as i8;
+    for _ in 0..ray.range {
+        row += ray.dr;
+        col += ray.dc;
+        if !(0..8).contains(&row) || !(0..8).contains(&col) {
+            break;
+        }
+        let bit = 1u64 << (row as usize * 8 + col as usize);
+        attacks |= bit;
+        if occupancy & bit != 0 {
+            break;
+        }
+    }
+    attacks
+}
+
+/// Per piece type and color movement patterns, built once on first use
+fn piece_attack_table(piece_type: PieceType, color: Color) -> &'static PieceAttacks {
+    static TABLES: OnceLock<Vec<[PieceAttacks; 2]>> = OnceLock::new();
+    let tables = TABLES.get_or_init(|| {
+        PieceType::ALL
+            .into_iter()
+            .map(|pt| [PieceAttacks::new(pt, Color::White), PieceAttacks::new(pt, Color::Black)])
+            .collect()
+    });
+    &tables[piece_type.index()][color_index(color)]
+}
+
+/// Squares attacked (capturable) by a piece of the given type and color
+/// Rook and Bishop slides use the ray tables; other riders are walked
+pub fn piece_attacks(piece_type: PieceType, color: Color, index: usize, occupancy: Bitboard) -> Bitboard {
+    let table = piece_attack_table(piece_type, color);
+    table.capture_leaps[index] | table.slides(index, occupancy, true)
+}
+
+/// Squares a piece can reach without capturing (before removing occupied squares)
+/// Differs from `piece_attacks` only for pieces like the Pawn
+pub fn piece_quiet_moves(piece_type: PieceType, color: Color, index: usize, occupancy: Bitboard) -> Bitboard {
+    let table = piece_attack_table(piece_type, color);
+    table.quiet_leaps[index] | table.slides(index, occupancy, false)
+}
+
+// =============================================================================
+// Bitboard Position
+// =============================================================================
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// Chess position stored as bitboards
+///
+/// Offers the same public API as the mailbox `Board` (same method names,
+/// coordinates and `Move` type), so search and UCI code can switch between them.
+/// A 64-entry piece array is kept alongside the bitboards for O(1) `get_piece`.
+#[derive(Clone)]
+pub struct BitBoard {
+    /// pieces[color][piece type]: squares occupied by that kind of piece
+    pieces: [[Bitboard; PieceType::COUNT]; 2],
+    /// occupancy[color]: squares occupied by any piece of that color
+    occupancy: [Bitboard; 2],
+    /// Piece on each square, indexed like the bitboards
+    mailbox: [Option<Piece>; 64],
+    side_to_move: Color,
+    /// Zobrist key (same keys as `Board`, so hashes are comparable)
+    hash: u64,
+    position_history: Vec<u64>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    promotion_types: &'static [PieceType],
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    chess960: bool,
+}
+
+impl BitBoard {
+    /// Create an empty board
+    pub fn new() -> Self {
+        BitBoard {
+            pieces: [[0; PieceType::COUNT]; 2],
+            occupancy: [0; 2],
+            mailbox: [None; 64],
+            side_to_move: Color::White,
+            hash: 0,
+            position_history: Vec::new(),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            promotion_types: STANDARD_PROMOTIONS,
+            castling_rights: CastlingRights::default(),
+            en_passant: None,
+            chess960: false,
+        }
+    }
+
+    /// Copy the position of a mailbox board (position history is not copied)
+    pub fn from_board(board: &Board) -> Self {
+        let mut bitboard = BitBoard::new();
+        for row in 0..8 {
+            for col in 0..8 {
+                bitboard.set_piece((row, col), board.get_piece((row, col)));
+            }
+        }
+        bitboard.set_side_to_move(board.side_to_move());
+        bitboard.halfmove_clock = board.halfmove_clock();
+        bitboard.fullmove_number = board.fullmove_number();
+        bitboard.promotion_types = board.promotion_types();
+        bitboard.set_castling_rights(board.castling_rights());
+        bitboard.set_en_passant(board.en_passant());
+        bitboard.chess960 = board.is_chess960();
+        bitboard
+    }
+
+    /// Convert to a mailbox board (position history is not copied)
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new();
+        for (index, piece) in self.mailbox.iter().enumerate() {
+            if piece.is_some() {
+                board.set_piece(index_square(index), *piece);
+            }
+        }
+        board.set_side_to_move(self.side_to_move);
+        board.set_halfmove_clock(self.halfmove_clock);
+        board.set_fullmove_number(self.fullmove_number);
+        board.set_promotion_types(self.promotion_types);
+        board.set_castling_rights(self.castling_rights);
+        board.set_en_passant(self.en_passant);
+        board.set_chess960(self.chess960);
+        board
+    }
+
+    /// Zobrist hash of the current position
+    pub fn position_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Compute the Zobrist hash from scratch (used to verify the incremental key)
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (index, piece) in self.mailbox.iter().enumerate() {
+            if let Some(piece) = piece {
+                hash ^= ZOBRIST.piece(*piece, index_square(index));
+            }
+        }
+        if self.side_to_move == Color::Black {
+            hash ^= ZOBRIST.side_to_move();
+        }
+        hash ^= self.castling_rights.hash();
+        if let Some(square) = self.en_passant {
+            hash ^= ZOBRIST.en_passant(square.1);
+        }
+        hash
+    }
+
+    /// Check if the current position has occurred before (repetition)
+    pub fn is_repetition(&self) -> bool {
+        self.position_history.contains(&self.hash)
+    }
+
+    /// Count how many times the current position has occurred
+    pub fn repetition_count(&self) -> usize {
+        self.position_history.iter().filter(|&&h| h == self.hash).count()
+    }
+
+    /// Clear position history (e.g., when starting a new game)
+    pub fn clear_history(&mut self) {
+        self.position_history.clear();
+    }
+
+    /// Plies since the last capture
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Current move number (starts at 1)
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Castling rights still held by both sides
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// Replace the castling rights, keeping the hash in sync
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.hash ^= self.castling_rights.hash() ^ rights.hash();
+        self.castling_rights = rights;
+    }
+
+    /// Square a pawn can be captured on en passant
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// Set or clear the en passant square, keeping the hash in sync
+    pub fn set_en_passant(&mut self, square: Option<Square>) {
+        if let Some(old) = self.en_passant {
+            self.hash ^= ZOBRIST.en_passant(old.1);
+        }
+        if let Some(new) = square {
+            self.hash ^= ZOBRIST.en_passant(new.1);
+        }
+        self.en_passant = square;
+    }
+
+    /// Whether castling moves are encoded as king-takes-rook
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Choose the castling encoding (UCI_Chess960)
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
+    /// Whether the game is drawn by the fifty-move rule
+    pub fn is_fifty_move_draw(&mut self) -> bool {
+        self.halfmove_clock >= 100 && !self.is_checkmate(self.side_to_move)
+    }
+
+    /// Get the piece at a given square
+    pub fn get_piece(&self, square: Square) -> Option<Piece> {
+        if square.0 < 8 && square.1 < 8 {
+            self.mailbox[square_index(square)]
+        } else {
+            None
+        }
+    }
+
+    /// Set a piece at a given square, keeping bitboards and hash in sync
+    pub fn set_piece(&mut self, square: Square, piece: Option<Piece>) {
+        if square.0 >= 8 || square.1 >= 8 {
+            return;
+        }
+        let index = square_index(square);
+        let bit = 1u64 << index;
+
+        if let Some(old) = self.mailbox[index] {
+            let color = color_index(old.color);
+            self.pieces[color][old.piece_type.index()] &= !bit;
+            self.occupancy[color] &= !bit;
+            self.hash ^= ZOBRIST.piece(old, square);
+        }
+        if let Some(new) = piece {
+            let color = color_index(new.color);
+            self.pieces[color][new.piece_type.index()] |= bit;
+            self.occupancy[color] |= bit;
+            self.hash ^= ZOBRIST.piece(new, square);
+        }
+        self.mailbox[index] = piece;
+    }
+
+    /// Squares occupied by pieces of the given type and color
+    pub fn pieces(&self, piece_type: PieceType, color: Color) -> Bitboard {
+        self.pieces[color_index(color)][piece_type.index()]
+    }
+
+    /// Squares occupied by any piece of the given color
+    pub fn occupancy(&self, color: Color) -> Bitboard {
+        self.occupancy[color_index(color)]
+    }
+
+    /// Squares occupied by any piece
+    pub fn all_occupancy(&self) -> Bitboard {
+        self.occupancy[0] | self.occupancy[1]
+    }
+
+    /// Get the current side to move
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// Set the side to move
+    pub fn set_side_to_move(&mut self, color: Color) {
+        if self.side_to_move != color {
+            self.toggle_side_to_move();
+        }
+    }
+
+    fn toggle_side_to_move(&mut self) {
+        self.side_to_move = self.side_to_move.opposite();
+        self.hash ^= ZOBRIST.side_to_move();
+    }
+
+    /// Setup the Amazon + K vs R + K starting position
+    pub fn setup_amazon_vs_rook() -> Self {
+        BitBoard::from_board(&Board::setup_amazon_vs_rook())
+    }
+
+    /// Create a board from FEN notation (same format as `Board::from_fen`)
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Board::from_fen(fen).map(|board| BitBoard::from_board(&board))
+    }
+
+    /// Convert board to FEN notation
+    pub fn to_fen(&self) -> String {
+        self.to_board().to_fen()
+    }
+
+    /// Execute a move, returns the Move with captured piece info for unmake
+    pub fn make_move(&mut self, from: Square, to: Square) -> Move {
+        self.apply_move(Move::new(from, to))
+    }
+
+    /// Execute a generated move (including its promotion piece)
+    /// Castling and en passant are recognised from the position, as in `Board`
+    pub fn apply_move(&mut self, mv: Move) -> Move {
+        let (from, to, promotion) = (mv.from, mv.to, mv.promotion);
+        let color = self.side_to_move;
+        self.position_history.push(self.hash);
+
+        let kind = self.move_kind(from, to);
+        let piece = self.get_piece(from);
+        let halfmove_clock = self.halfmove_clock;
+        let castling_rights = self.castling_rights;
+        let en_passant = self.en_passant;
+
+        let captured = match kind {
+            MoveKind::Castling => {
+                let (king_to, rook_from, rook_to) = castling_squares(from, to, &castling_rights, color);
+                let rook = self.get_piece(rook_from);
+                self.set_piece(from, None);
+                self.set_piece(rook_from, None);
+                self.set_piece(king_to, piece);
+                self.set_piece(rook_to, rook);
+                None
+            }
+            MoveKind::EnPassant | MoveKind::Normal => {
+                let captured_square = if kind == MoveKind::EnPassant { (from.0, to.1) } else { to };
+                let captured = self.get_piece(captured_square);
+                let placed = match (piece, promotion) {
+                    (Some(pawn), Some(piece_type)) => Some(Piece::new(piece_type, pawn.color)),
+                    _ => piece,
+                };
+                self.set_piece(captured_square, None);
+                self.set_piece(to, placed);
+                self.set_piece(from, None);
+                captured
+            }
+        };
+
+        let pawn_move = piece.is_some_and(|p| p.piece_type == PieceType::Pawn);
+        if let Some(piece) = piece {
+            let mut rights = castling_rights;
+            rights.update(piece, from, to);
+            self.set_castling_rights(rights);
+        }
+        let double_step = pawn_move && from.0.abs_diff(to.0) == 2;
+        let skipped = ((from.0 + to.0) / 2, from.1);
+        let new_en_passant = if double_step { self.en_passant_target(skipped, to, color.opposite()) } else { None };
+        self.set_en_passant(new_en_passant);
+
+        self.halfmove_clock = if captured.is_some() || pawn_move { 0 } else { halfmove_clock + 1 };
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.toggle_side_to_move();
+
+        debug_assert_eq!(self.hash, self.compute_hash(), "Incremental hash out of sync");
+
+        Move {
+            from,
+            to,
+            captured,
+            halfmove_clock,
+            promotion,
+            kind,
+            castling_rights,
+            en_passant,
+        }
+    }
+
+    /// Classify a move by the special rule it follows, if any (see `Board`)
+    fn move_kind(&self, from: Square, to: Square) -> MoveKind {
+        let piece = match self.get_piece(from) {
+            Some(piece) => piece,
+            None => return MoveKind::Normal,
+        };
+        match piece.piece_type {
+            PieceType::King => {
+                let onto_own_rook = self.pieces(PieceType::Rook, piece.color) & square_bb(to) != 0;
+                if onto_own_rook || (from.0 == to.0 && from.1.abs_diff(to.1) == 2) {
+                    MoveKind::Castling
+                } else {
+                    MoveKind::Normal
+                }
+            }
+            PieceType::Pawn if self.en_passant == Some(to) && from.1 != to.1 => MoveKind::EnPassant,
+            _ => MoveKind::Normal,
+        }
+    }
+
+    /// En passant square after a pawn double-stepped over `skipped` to `pawn`,
+    /// kept only if a pawn of `capturer` stands next to it
+    fn en_passant_target(&self, skipped: Square, pawn: Square, capturer: Color) -> Option<Square> {
+        let index = square_index(pawn);
+        let beside = king_attacks(index) & (0xFFu64 << (pawn.0 as usize * 8));
+        (beside & self.pieces(PieceType::Pawn, capturer) != 0).then_some(skipped)
+    }
+
+    /// Undo a move, restoring the previous state
+    pub fn unmake_move(&mut self, mv: Move) {
+        self.position_history.pop();
+        self.toggle_side_to_move();
+
+        match mv.kind {
+            MoveKind::Castling => {
+                let color = self.side_to_move;
+                let (king_to, rook_from, rook_to) = castling_squares(mv.from, mv.to, &mv.castling_rights, color);
+                let king = self.get_piece(king_to);
+                let rook = self.get_piece(rook_to);
+                self.set_piece(king_to, None);
+                self.set_piece(rook_to, None);
+                self.set_piece(mv.from, king);
+                self.set_piece(rook_from, rook);
+            }
+            MoveKind::EnPassant | MoveKind::Normal => {
+                let mut piece = self.get_piece(mv.to);
+                if mv.promotion.is_some() {
+                    piece = piece.map(|p| Piece::new(PieceType::Pawn, p.color));
+                }
+                self.set_piece(mv.from, piece);
+                self.set_piece(mv.to, None);
+                let captured_square = if mv.kind == MoveKind::EnPassant { (mv.from.0, mv.to.1) } else { mv.to };
+                self.set_piece(captured_square, mv.captured);
+            }
+        }
+
+        self.set_castling_rights(mv.castling_rights);
+        self.set_en_passant(mv.en_passant);
+        self.halfmove_clock = mv.halfmove_clock;
+        if self.side_to_move == Color::Black {
+            self.fullmove_number -= 1;
+        }
+
+        debug_assert_eq!(self.hash, self.compute_hash(), "Incremental hash out of sync");
+    }
+
+    /// Find the position of a King of the given color
+    pub fn find_king(&self, color: Color) -> Option<Square> {
+        let kings = self.pieces(PieceType::King, color);
+        if kings == 0 {
+            None
+        } else {
+            Some(index_square(kings.trailing_zeros() as usize))
+        }
+    }
+
+    /// Check if a square is attacked by any piece of the given color
+    /// Attacks are reversible, so we look outward from the target square with
+    /// each piece's pattern and intersect with the attackers
+    /// Like `Board`, a square holding a piece of `by_color` counts as not attacked
+    /// (pieces cannot move onto their own side's pieces)
+    pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
+        let index = square_index(square);
+        let occupancy = self.all_occupancy();
+
+        if self.occupancy(by_color) & (1u64 << index) != 0 {
+            return false;
+        }
+
+        // Looking back from the target mirrors forward-only moves (the other color's pattern)
+        PieceType::ALL.into_iter().any(|piece_type| {
+            let attackers = self.pieces(piece_type, by_color);
+            attackers != 0 && piece_attacks(piece_type, by_color.opposite(), index, occupancy) & attackers != 0
+        })
+    }
+
+    /// Check if the King of the given color is in check
+    pub fn is_in_check(&self, color: Color) -> bool {
+        match self.find_king(color) {
+            Some(king_square) => self.is_square_attacked(king_square, color.opposite()),
+            None => false,
+        }
+    }
+
+    /// Generate all legal moves for the current side to move
+    pub fn generate_legal_moves(&mut self) -> Vec<Move> {
+        let color = self.side_to_move;
+        let own = self.occupancy(color);
+        let occupancy = self.all_occupancy();
+        let mut legal_moves = Vec::new();
+
+        for index in bits(own) {
+            let from = index_square(index);
+            let piece_type = match self.mailbox[index] {
+                Some(piece) => piece.piece_type,
+                None => continue,
+            };
+
+            let enemy = self.occupancy(color.opposite());
+            let mut targets = (piece_attacks(piece_type, color, index, occupancy) & enemy)
+                | (piece_quiet_moves(piece_type, color, index, occupancy) & !occupancy);
+
+            let is_pawn = piece_type == PieceType::Pawn;
+            if is_pawn {
+                if let Some(to) = PawnMoves::double_step(from, color) {
+                    let middle = ((from.0 as i8 + PawnMoves::forward(color)) as u8, from.1);
+                    if occupancy & (square_bb(middle) | square_bb(to)) == 0 {
+                        targets |= square_bb(to);
+                    }
+                }
+                if let Some(target) = self.en_passant {
+                    targets |= piece_attacks(piece_type, color, index, occupancy) & square_bb(target);
+                }
+            }
+
+            for target in bits(targets) {
+                let to = index_square(target);
+                let mv = self.make_move(from, to);
+                let legal = !self.is_in_check(color);
+                self.unmake_move(mv);
+                if !legal {
+                    continue;
+                }
+
+                if is_pawn && to.0 == PawnMoves::promotion_row(color) {
+                    for &promotion in self.promotion_types {
+                        legal_moves.push(Move {
+                            promotion: Some(promotion),
+                            ..mv
+                        });
+                    }
+                } else {
+                    legal_moves.push(mv);
+                }
+            }
+        }
+
+        for castle in self.castling_moves() {
+            let mv = self.apply_move(castle);
+            let legal = !self.is_in_check(color);
+            self.unmake_move(mv);
+            if legal {
+                legal_moves.push(mv);
+            }
+        }
+
+        legal_moves
+    }
+
+    /// Castling moves for the side to move, before the final king-safety check
+    /// (same rules as `Board`)
+    fn castling_moves(&self) -> Vec<Move> {
+        let color = self.side_to_move;
+        let row = back_rank(color);
+        let king_from = match self.find_king(color) {
+            Some(square) if square.0 == row => square,
+            _ => return Vec::new(),
+        };
+
+        let mut moves = Vec::new();
+        for side in CastleSide::BOTH {
+            let rook_file = match self.castling_rights.rook_file(color, side) {
+                Some(file) => file,
+                None => continue,
+            };
+            let rook_from = (row, rook_file);
+            if self.pieces(PieceType::Rook, color) & square_bb(rook_from) == 0 {
+                continue;
+            }
+
+            let rank_bb = |files: std::ops::RangeInclusive<u8>| files.fold(0, |bb, file| bb | square_bb((row, file)));
+            let king_path = file_span(king_from.1, side.king_to_file());
+            let crossed = rank_bb(king_path.clone()) | rank_bb(file_span(rook_file, side.rook_to_file()));
+            let blockers = self.all_occupancy() & !square_bb(king_from) & !square_bb(rook_from);
+            if crossed & blockers != 0
+                || king_path.into_iter().any(|file| self.is_square_attacked((row, file), color.opposite()))
+            {
+                continue;
+            }
+
+            let to = if self.chess960 { rook_from } else { (row, side.king_to_file()) };
+            moves.push(Move::new(king_from, to));
+        }
+        moves
+    }
+
+    /// Check if the given color is in checkmate
+    pub fn is_checkmate(&mut self, color: Color) -> bool {
+        if !self.is_in_check(color) {
+            return false;
+        }
+        let original_side = self.side_to_move;
+        self.set_side_to_move(color);
+        let has_no_moves = self.generate_legal_moves().is_empty();
+        self.set_side_to_move(original_side);
+        has_no_moves
+    }
+
+    /// Check if the given color is in stalemate
+    pub fn is_stalemate(&mut self, color: Color) -> bool {
+        if self.is_in_check(color) {
+            return false;
+        }
+        let original_side = self.side_to_move;
+        self.set_side_to_move(color);
+        let has_no_moves = self.generate_legal_moves().is_empty();
+        self.set_side_to_move(original_side);
+        has_no_moves
+    }
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        BitBoard::new()
+    }
+}
+
+impl std::fmt::Display for BitBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_board())
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perft::perft;
+
+    #[test]
+    fn test_square_index_round_trip() {
+        assert_eq!(square_index((0, 0)), 0); // a8
+        assert_eq!(square_index((7, 7)), 63); // h1
+        for index in 0..64 {
+            assert_eq!(square_index(index_square(index)), index);
+        }
+    }
+
+    #[test]
+    fn test_leaper_tables() {
+        // King in the corner a8 has 3 moves, knight on a8 has 2
+        assert_eq!(king_attacks(square_index((0, 0))).count_ones(), 3);
+        assert_eq!(knight_attacks(square_index((0, 0))).count_ones(), 2);
+        // In the center: 8 each
+        assert_eq!(king_attacks(square_index((4, 4))).count_ones(), 8);
+        assert_eq!(knight_attacks(square_index((4, 4))).count_ones(), 8);
+    }
+
+    #[test]
+    fn test_slider_attacks_stop_at_blocker() {
+        // Rook on d4 with a blocker on d6: attacks d5 and d6, not d7
+        let d4 = square_index((4, 3));
+        let blocker = square_bb((2, 3));
+        let attacks = rook_attacks(d4, blocker);
+        assert!(attacks & square_bb((3, 3)) != 0, "d5 attacked");
+        assert!(attacks & square_bb((2, 3)) != 0, "blocker d6 attacked");
+        assert!(attacks & square_bb((1, 3)) == 0, "d7 behind blocker");
+        assert_eq!(rook_attacks(d4, 0).count_ones(), 14);
+
+        // Bishop rays in both index directions
+        let blockers = square_bb((2, 1)) | square_bb((6, 5)); // b6 and f2
+        let diag = bishop_attacks(d4, blockers);
+        assert!(diag & square_bb((2, 1)) != 0 && diag & square_bb((1, 0)) == 0);
+        assert!(diag & square_bb((6, 5)) != 0 && diag & square_bb((7, 6)) == 0);
+    }
+
+    #[test]
+    fn test_attacks_match_mailbox_generators() {
+        // Every square of the starting position, for both colors
+        let mut board = Board::setup_amazon_vs_rook();
+        let bitboard = BitBoard::from_board(&board);
+        for row in 0..8 {
+            for col in 0..8 {
+                for by_color in [Color::White, Color::Black] {
+                    assert_eq!(
+                        board.is_square_attacked((row, col), by_color),
+                        bitboard.is_square_attacked((row, col), by_color),
+                        "Attack mismatch at ({}, {}) by {:?}",
+                        row,
+                        col,
+                        by_color
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            board.generate_legal_moves().len(),
+            BitBoard::from_board(&board).generate_legal_moves().len()
+        );
+    }
+
+    #[test]
+    fn test_from_board_round_trip() {
+        let board = Board::setup_amazon_vs_rook();
+        let bitboard = BitBoard::from_board(&board);
+        assert_eq!(bitboard.to_fen(), board.to_fen());
+        assert_eq!(bitboard.position_hash(), board.position_hash());
+        assert_eq!(bitboard.find_king(Color::Black), Some((0, 4)));
+        assert_eq!(bitboard.pieces(PieceType::Amazon, Color::White), square_bb((7, 3)));
+    }
+
+    #[test]
+    fn test_make_unmake_restores_bitboards() {
+        let mut bitboard = BitBoard::setup_amazon_vs_rook();
+        let before = bitboard.clone();
+        let moves = bitboard.generate_legal_moves();
+        for mv in moves {
+            let made = bitboard.make_move(mv.from, mv.to);
+            bitboard.unmake_move(made);
+            assert_eq!(bitboard.pieces, before.pieces);
+            assert_eq!(bitboard.occupancy, before.occupancy);
+            assert_eq!(bitboard.position_hash(), before.position_hash());
+        }
+    }
+
+    #[test]
+    fn test_checkmate_and_stalemate() {
+        // Same positions as the mailbox tests
+        let mut mate = BitBoard::new();
+        mate.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::Black)));
+        mate.set_piece((2, 0), Some(Piece::new(PieceType::King, Color::White)));
+        mate.set_piece((2, 1), Some(Piece::new(PieceType::Amazon, Color::White)));
+        assert!(mate.is_checkmate(Color::Black));
+
+        let mut stalemate = BitBoard::new();
+        stalemate.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::Black)));
+        stalemate.set_piece((2, 1), Some(Piece::new(PieceType::King, Color::White)));
+        stalemate.set_piece((1, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
+        assert!(stalemate.is_stalemate(Color::Black));
+        assert!(!stalemate.is_checkmate(Color::Black));
+    }
+
+    #[test]
+    fn test_perft_matches_mailbox() {
+        let positions = [
+            "r3k3/8/8/8/8/8/8/3AK3 w - - 0 1",
+            "r3k3/8/8/8/8/8/8/3AK3 b - - 0 1",
+            "8/8/3k4/8/2r5/8/4A3/4K3 w - - 0 1",
+            "4k3/8/8/3A4/8/8/1r6/4K3 b - - 0 1",
+        ];
+        for fen in positions {
+            let mut board = Board::from_fen(fen).unwrap();
+            let mut bitboard = BitBoard::from_fen(fen).unwrap();
+            for depth in 1..=3 {
+                assert_eq!(
+                    perft(&mut bitboard, depth),
+                    perft(&mut board, depth),
+                    "Perft mismatch for {} at depth {}",
+                    fen,
+                    depth
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_castling_and_en_passant_match_mailbox() {
+        let fen = "r3k2r/8/8/8/5p2/8/4P3/R3K2R w KQkq - 0 1";
+        let mut bitboard = BitBoard::from_fen(fen).unwrap();
+        let double_step = bitboard.make_move((6, 4), (4, 4));
+        let capture = bitboard.make_move((4, 5), (5, 4));
+        let castle = bitboard.make_move((7, 4), (7, 2));
+        assert_eq!(castle.kind, MoveKind::Castling);
+        assert_eq!(bitboard.to_fen(), "r3k2r/8/8/8/8/4p3/8/2KR3R b kq - 1 2");
+        assert_eq!(bitboard.position_hash(), bitboard.compute_hash());
+
+        bitboard.unmake_move(castle);
+        bitboard.unmake_move(capture);
+        bitboard.unmake_move(double_step);
+        assert_eq!(bitboard.to_fen(), fen);
+        assert_eq!(bitboard.position_hash(), Board::from_fen(fen).unwrap().position_hash());
+    }
+
+    #[test]
+    fn test_clocks_match_mailbox() {
+        let fen = "4k3/8/8/8/8/8/3r4/3AK3 b - - 12 30";
+        let mut bitboard = BitBoard::from_fen(fen).unwrap();
+        assert_eq!(bitboard.to_fen(), fen);
+
+        let mv = bitboard.make_move((6, 3), (7, 3));
+        assert_eq!((bitboard.halfmove_clock(), bitboard.fullmove_number()), (0, 31));
+        bitboard.unmake_move(mv);
+        assert_eq!(bitboard.to_fen(), fen);
+    }
+}
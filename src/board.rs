@@ -1,9 +1,12 @@
 // Board representation and piece logic
 // Using Mailbox (8x8 array) approach for clarity and extensibility
 
-use crate::pieces::amazon::AmazonMoves;
-use crate::pieces::king::KingMoves;
-use crate::pieces::rook::RookMoves;
+use crate::bitboard::{piece_attacks, square_index, Bitboard};
+use crate::nnue::{Network, NnueState};
+use crate::pieces::betza::Movement;
+use crate::pieces::pawn::PawnMoves;
+use crate::zobrist::ZOBRIST;
+use std::sync::{Arc, OnceLock};
 
 // =============================================================================
 // Type Definitions
@@ -30,6 +33,14 @@ impl Color {
             Color::Black => Color::White,
         }
     }
+
+    /// Index into per-color lookup tables
+    pub fn index(self) -> usize {
+        match self {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
 }
 
 /// Type of a chess piece
@@ -41,8 +52,105 @@ pub enum PieceType {
     Amazon,
     /// Rook - moves horizontally and vertically
     Rook,
+    /// Actress = Queen + Knight + Camel
+    /// Moves like an Amazon, plus the Camel's (3,1) leap
+    Actress,
+    /// Orthodox pieces, for mixed-material studies
+    Pawn,
+    Knight,
+    Bishop,
+    Queen,
+}
+
+impl PieceType {
+    /// Number of piece types (size of per-piece lookup tables)
+    pub const COUNT: usize = 8;
+
+    /// Every piece type, in index order
+    pub const ALL: [PieceType; PieceType::COUNT] = [
+        PieceType::King,
+        PieceType::Amazon,
+        PieceType::Rook,
+        PieceType::Actress,
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Queen,
+    ];
+
+    /// Index of this piece type into per-piece lookup tables
+    pub fn index(self) -> usize {
+        match self {
+            PieceType::King => 0,
+            PieceType::Amazon => 1,
+            PieceType::Rook => 2,
+            PieceType::Actress => 3,
+            PieceType::Pawn => 4,
+            PieceType::Knight => 5,
+            PieceType::Bishop => 6,
+            PieceType::Queen => 7,
+        }
+    }
+
+    /// Static description of this piece type (name, FEN letter, Betza movement)
+    pub fn info(self) -> &'static PieceInfo {
+        &PIECE_INFO[self.index()]
+    }
+
+    /// Find the piece type for a FEN letter (either case)
+    pub fn from_letter(letter: char) -> Option<PieceType> {
+        let letter = letter.to_ascii_uppercase();
+        PieceType::ALL.into_iter().find(|pt| pt.info().letter == letter)
+    }
+
+    /// Movement parsed from the Betza definition, built once on first use
+    pub fn movement(self) -> &'static Movement {
+        static MOVEMENTS: OnceLock<Vec<Movement>> = OnceLock::new();
+        let movements = MOVEMENTS.get_or_init(|| {
+            PieceType::ALL
+                .iter()
+                .map(|pt| Movement::parse(pt.info().betza).expect("Invalid built-in Betza definition"))
+                .collect()
+        });
+        &movements[self.index()]
+    }
 }
 
+/// Everything needed to declare a piece apart from its evaluation
+pub struct PieceInfo {
+    pub name: &'static str,
+    /// FEN letter for White (lowercase for Black)
+    pub letter: char,
+    /// Movement in Betza notation (see `pieces::betza`)
+    pub betza: &'static str,
+}
+
+/// Piece declarations, indexed by `PieceType::index`
+const PIECE_INFO: [PieceInfo; PieceType::COUNT] = [
+    PieceInfo { name: "King", letter: 'K', betza: "K" },
+    PieceInfo { name: "Amazon", letter: 'A', betza: "QN" },
+    PieceInfo { name: "Rook", letter: 'R', betza: "R" },
+    // No standard letter exists for the Actress
+    PieceInfo { name: "Actress", letter: 'X', betza: "QNC" },
+    // The double step is added by `PawnMoves`
+    PieceInfo { name: "Pawn", letter: 'P', betza: "fmWfcF" },
+    PieceInfo { name: "Knight", letter: 'N', betza: "N" },
+    PieceInfo { name: "Bishop", letter: 'B', betza: "B" },
+    PieceInfo { name: "Queen", letter: 'Q', betza: "Q" },
+];
+
+/// Pieces a pawn may promote to in orthodox chess
+pub const STANDARD_PROMOTIONS: &[PieceType] =
+    &[PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
+/// Amazon chess: the Amazon takes the Queen's place, also as a promotion piece
+pub const AMAZON_PROMOTIONS: &[PieceType] =
+    &[PieceType::Amazon, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
+/// Actress chess: the Actress takes the Queen's place
+pub const ACTRESS_PROMOTIONS: &[PieceType] =
+    &[PieceType::Actress, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
 /// A chess piece with type and color
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Piece {
@@ -55,6 +163,26 @@ impl Piece {
     pub fn new(piece_type: PieceType, color: Color) -> Self {
         Piece { piece_type, color }
     }
+
+    /// FEN letter: uppercase for White, lowercase for Black
+    pub fn to_char(self) -> char {
+        let letter = self.piece_type.info().letter;
+        match self.color {
+            Color::White => letter,
+            Color::Black => letter.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Special rule a move follows, found by `apply_move` from the position
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MoveKind {
+    Normal,
+    /// King and rook move together; `to` is the king's target square (e1g1),
+    /// or the rook's square (e1h1, "king takes rook") in Chess960 mode
+    Castling,
+    /// A pawn captures the pawn that just double-stepped past it
+    EnPassant,
 }
 
 /// Represents a chess move
@@ -63,6 +191,11 @@ pub struct Move {
     pub from: Square,
     pub to: Square,
     pub captured: Option<Piece>, // For unmake_move restoration
+    pub halfmove_clock: u32,      // Clock before the move, for unmake_move restoration
+    pub promotion: Option<PieceType>, // Piece a pawn turns into on the last rank
+    pub kind: MoveKind,
+    pub castling_rights: CastlingRights, // Rights before the move, for unmake_move restoration
+    pub en_passant: Option<Square>,      // En passant square before the move, for unmake_move
 }
 
 impl Move {
@@ -71,10 +204,229 @@ impl Move {
             from,
             to,
             captured: None,
+            halfmove_clock: 0,
+            promotion: None,
+            kind: MoveKind::Normal,
+            castling_rights: CastlingRights::default(),
+            en_passant: None,
+        }
+    }
+
+    /// A pawn move to the last rank that promotes to `piece_type`
+    pub fn with_promotion(from: Square, to: Square, piece_type: PieceType) -> Self {
+        Move {
+            promotion: Some(piece_type),
+            ..Move::new(from, to)
+        }
+    }
+
+    /// Whether two moves are the same (ignoring undo information)
+    pub fn same_as(&self, other: &Move) -> bool {
+        self.from == other.from && self.to == other.to && self.promotion == other.promotion
+    }
+}
+
+// =============================================================================
+// Castling
+// =============================================================================
+
+/// Which side of the king a castling move goes to
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CastleSide {
+    King,
+    Queen,
+}
+
+impl CastleSide {
+    /// Both sides, in FEN order
+    pub const BOTH: [CastleSide; 2] = [CastleSide::King, CastleSide::Queen];
+
+    /// Index into per-side lookup tables
+    pub fn index(self) -> usize {
+        match self {
+            CastleSide::King => 0,
+            CastleSide::Queen => 1,
+        }
+    }
+
+    /// Side of a castling move from its encoding: towards the h-file is the king side
+    /// Holds for both the standard (e1g1) and the king-takes-rook (e1h1) form
+    pub fn of_move(from: Square, to: Square) -> CastleSide {
+        if to.1 > from.1 {
+            CastleSide::King
+        } else {
+            CastleSide::Queen
+        }
+    }
+
+    /// File the king ends on (g or c, in Chess960 too)
+    pub fn king_to_file(self) -> u8 {
+        match self {
+            CastleSide::King => 6,
+            CastleSide::Queen => 2,
+        }
+    }
+
+    /// File the rook ends on (f or d)
+    pub fn rook_to_file(self) -> u8 {
+        match self {
+            CastleSide::King => 5,
+            CastleSide::Queen => 3,
+        }
+    }
+}
+
+/// Row of a color's back rank, where its king and rooks castle
+pub fn back_rank(color: Color) -> u8 {
+    match color {
+        Color::White => 7,
+        Color::Black => 0,
+    }
+}
+
+/// Castling rights: for each color and side, the file of the rook that may still castle
+/// Storing the rook's file rather than a flag covers Chess960 starting positions
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct CastlingRights {
+    /// Indexed by [color][castle side]
+    rooks: [[Option<u8>; 2]; 2],
+}
+
+impl CastlingRights {
+    /// File of the rook the given color may castle with on that side
+    pub fn rook_file(&self, color: Color, side: CastleSide) -> Option<u8> {
+        self.rooks[color.index()][side.index()]
+    }
+
+    /// Grant (Some(file)) or remove (None) a castling right
+    pub fn set(&mut self, color: Color, side: CastleSide, rook_file: Option<u8>) {
+        self.rooks[color.index()][side.index()] = rook_file;
+    }
+
+    /// Whether neither side may castle any more
+    pub fn is_empty(&self) -> bool {
+        self.rooks.iter().flatten().all(|file| file.is_none())
+    }
+
+    /// Remove the rights a move gives up: a king move loses both of its rights,
+    /// and a rook leaving or being captured on its square loses that one
+    pub fn update(&mut self, piece: Piece, from: Square, to: Square) {
+        if piece.piece_type == PieceType::King {
+            self.rooks[piece.color.index()] = [None; 2];
+        }
+        for color in [Color::White, Color::Black] {
+            for side in CastleSide::BOTH {
+                let rook = self.rook_file(color, side).map(|file| (back_rank(color), file));
+                if rook == Some(from) || rook == Some(to) {
+                    self.set(color, side, None);
+                }
+            }
+        }
+    }
+
+    /// XOR of the Zobrist keys of the rights held
+    pub fn hash(&self) -> u64 {
+        let mut hash = 0;
+        for color in [Color::White, Color::Black] {
+            for side in CastleSide::BOTH {
+                if self.rook_file(color, side).is_some() {
+                    hash ^= ZOBRIST.castling(color, side);
+                }
+            }
         }
+        hash
+    }
+}
+
+/// Squares of a castling move: (king target, rook origin, rook target)
+/// `rights` are the rights before the move, which name the rook's file
+pub fn castling_squares(from: Square, to: Square, rights: &CastlingRights, color: Color) -> (Square, Square, Square) {
+    let side = CastleSide::of_move(from, to);
+    let row = from.0;
+    let default_file = match side {
+        CastleSide::King => 7,
+        CastleSide::Queen => 0,
+    };
+    let rook_file = rights.rook_file(color, side).unwrap_or(default_file);
+    ((row, side.king_to_file()), (row, rook_file), (row, side.rook_to_file()))
+}
+
+/// Files from `a` to `b`, both included, in increasing order
+pub fn file_span(a: u8, b: u8) -> std::ops::RangeInclusive<u8> {
+    a.min(b)..=a.max(b)
+}
+
+/// Algebraic name of a square (e.g. (5, 4) -> "e3")
+fn square_name(square: Square) -> String {
+    format!("{}{}", (b'a' + square.1) as char, (b'8' - square.0) as char)
+}
+
+/// Parse an algebraic square name (e.g. "e3")
+fn parse_square_name(name: &str) -> Option<Square> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) || !(b'1'..=b'8').contains(&bytes[1]) {
+        return None;
     }
+    Some((b'8' - bytes[1], bytes[0] - b'a'))
 }
 
+// =============================================================================
+// FEN Errors
+// =============================================================================
+
+/// Why a FEN string was rejected by `Board::from_fen`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FenError {
+    /// The string is empty
+    Empty,
+    /// Piece placement does not have 8 ranks (number found)
+    BadRankCount(usize),
+    /// A rank describes more than 8 squares (rank number, 8..1)
+    RankOverflow(u8),
+    /// A rank describes fewer than 8 squares (rank number, 8..1)
+    RankTooShort(u8),
+    /// A piece letter this engine does not know
+    UnknownPiece(char),
+    /// A side has no king
+    MissingKing(Color),
+    /// A side has more than one king
+    TooManyKings(Color),
+    /// Side to move is neither "w" nor "b"
+    BadSideToMove(String),
+    /// Castling field names a right without a king and rook on the back rank
+    BadCastling(String),
+    /// En passant field is not a square just skipped by a double-stepping pawn
+    BadEnPassant(String),
+    /// The side not to move is in check (its king could be captured)
+    OpponentInCheck,
+    /// Halfmove clock is not a non-negative number
+    BadHalfmoveClock(String),
+    /// Fullmove number is not a positive number
+    BadFullmoveNumber(String),
+}
+
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::Empty => write!(f, "empty FEN"),
+            FenError::BadRankCount(n) => write!(f, "expected 8 ranks, found {}", n),
+            FenError::RankOverflow(rank) => write!(f, "rank {} has more than 8 squares", rank),
+            FenError::RankTooShort(rank) => write!(f, "rank {} has fewer than 8 squares", rank),
+            FenError::UnknownPiece(c) => write!(f, "unknown piece '{}'", c),
+            FenError::MissingKing(color) => write!(f, "{:?} has no king", color),
+            FenError::TooManyKings(color) => write!(f, "{:?} has more than one king", color),
+            FenError::BadSideToMove(s) => write!(f, "invalid side to move '{}'", s),
+            FenError::BadCastling(s) => write!(f, "invalid castling rights '{}'", s),
+            FenError::BadEnPassant(s) => write!(f, "invalid en passant square '{}'", s),
+            FenError::OpponentInCheck => write!(f, "side not to move is in check"),
+            FenError::BadHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            FenError::BadFullmoveNumber(s) => write!(f, "invalid fullmove number '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
 // =============================================================================
 // Board Structure
 // =============================================================================
@@ -85,13 +437,33 @@ impl Move {
 /// - squares[0][0] = a8 (top-left from white's perspective)
 /// - squares[7][7] = h1 (bottom-right from white's perspective)
 /// - squares[row][col] where row = 7 - rank, col = file
+#[derive(Clone)]
 pub struct Board {
     /// 8x8 array of squares, each containing an optional piece
     squares: [[Option<Piece>; 8]; 8],
+    /// The same pieces as bitboards, by color and piece type, for fast attack tests
+    piece_sets: [[Bitboard; PieceType::COUNT]; 2],
+    /// Squares occupied by each color
+    occupancy: [Bitboard; 2],
     /// Which side is to move
     side_to_move: Color,
-    /// History of position hashes for repetition detection
+    /// Zobrist key of the current position, updated incrementally
+    hash: u64,
+    /// Zobrist keys of all previous positions for repetition detection
     position_history: Vec<u64>,
+    /// Plies since the last capture (fifty-move rule)
+    halfmove_clock: u32,
+    /// Starts at 1 and is incremented after Black's move
+    fullmove_number: u32,
+    /// Pieces a pawn may promote to (a variant rule)
+    promotion_types: &'static [PieceType],
+    castling_rights: CastlingRights,
+    /// Square a pawn skipped with a double step, set only while it can be captured there
+    en_passant: Option<Square>,
+    /// Encode castling as king-takes-rook (UCI_Chess960)
+    chess960: bool,
+    /// NNUE accumulators, updated incrementally once attached
+    nnue: Option<NnueState>,
 }
 
 impl Board {
@@ -99,42 +471,63 @@ impl Board {
     pub fn new() -> Self {
         Board {
             squares: [[None; 8]; 8],
+            piece_sets: [[0; PieceType::COUNT]; 2],
+            occupancy: [0; 2],
             side_to_move: Color::White,
+            hash: 0,
             position_history: Vec::new(),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            promotion_types: STANDARD_PROMOTIONS,
+            castling_rights: CastlingRights::default(),
+            en_passant: None,
+            chess960: false,
+            nnue: None,
         }
     }
 
-    /// Compute a hash of the current position for repetition detection
-    /// Uses a simple hash combining piece positions and side to move
+    /// Keep NNUE accumulators for `network` in step with every move from now on
+    pub fn attach_nnue(&mut self, network: Arc<Network>) {
+        self.nnue = Some(NnueState::new(network, self));
+    }
+
+    /// Stop updating NNUE accumulators
+    pub fn detach_nnue(&mut self) {
+        self.nnue = None;
+    }
+
+    /// Evaluate with the attached accumulators, if they belong to `network`
+    pub fn nnue_evaluate(&mut self, network: &Arc<Network>) -> Option<i32> {
+        let mut nnue = self.nnue.take()?;
+        let score = nnue.uses(network).then(|| nnue.evaluate(self));
+        self.nnue = Some(nnue);
+        score
+    }
+
+    /// Zobrist hash of the current position
+    /// Maintained incrementally by set_piece, make_move and unmake_move
     pub fn position_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Compute the Zobrist hash from scratch (used to verify the incremental key)
+    pub fn compute_hash(&self) -> u64 {
         let mut hash: u64 = 0;
 
-        // Hash each piece on the board
         for row in 0..8u8 {
             for col in 0..8u8 {
                 if let Some(piece) = self.get_piece((row, col)) {
-                    // Create a unique value for each piece type, color, and position
-                    let piece_value: u64 = match piece.piece_type {
-                        PieceType::King => 1,
-                        PieceType::Amazon => 2,
-                        PieceType::Rook => 3,
-                    };
-                    let color_value: u64 = match piece.color {
-                        Color::White => 0,
-                        Color::Black => 64,
-                    };
-                    let square_value = (row as u64) * 8 + (col as u64);
-
-                    // Combine into hash using prime multiplier
-                    hash ^= (piece_value + color_value) * 31 + square_value * 127;
-                    hash = hash.wrapping_mul(0x517cc1b727220a95);
+                    hash ^= ZOBRIST.piece(piece, (row, col));
                 }
             }
         }
 
-        // Include side to move in hash
         if self.side_to_move == Color::Black {
-            hash ^= 0xF0F0F0F0F0F0F0F0;
+            hash ^= ZOBRIST.side_to_move();
+        }
+        hash ^= self.castling_rights.hash();
+        if let Some(square) = self.en_passant {
+            hash ^= ZOBRIST.en_passant(square.1);
         }
 
         hash
@@ -142,14 +535,12 @@ impl Board {
 
     /// Check if the current position has occurred before (repetition)
     pub fn is_repetition(&self) -> bool {
-        let current_hash = self.position_hash();
-        self.position_history.iter().filter(|&&h| h == current_hash).count() >= 1
+        self.position_history.contains(&self.hash)
     }
 
     /// Count how many times the current position has occurred
     pub fn repetition_count(&self) -> usize {
-        let current_hash = self.position_hash();
-        self.position_history.iter().filter(|&&h| h == current_hash).count()
+        self.position_history.iter().filter(|&&h| h == self.hash).count()
     }
 
     /// Clear position history (e.g., when starting a new game)
@@ -157,6 +548,79 @@ impl Board {
         self.position_history.clear();
     }
 
+    /// Plies since the last capture
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Set the halfmove clock (e.g. from a FEN)
+    pub fn set_halfmove_clock(&mut self, clock: u32) {
+        self.halfmove_clock = clock;
+    }
+
+    /// Current move number (starts at 1)
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Set the fullmove number (e.g. from a FEN)
+    pub fn set_fullmove_number(&mut self, number: u32) {
+        self.fullmove_number = number;
+    }
+
+    /// Pieces a pawn may promote to
+    pub fn promotion_types(&self) -> &'static [PieceType] {
+        self.promotion_types
+    }
+
+    /// Set the promotion pieces (e.g. `AMAZON_PROMOTIONS` for Amazon chess)
+    pub fn set_promotion_types(&mut self, types: &'static [PieceType]) {
+        self.promotion_types = types;
+    }
+
+    /// Castling rights still held by both sides
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// Replace the castling rights, keeping the hash in sync
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.hash ^= self.castling_rights.hash() ^ rights.hash();
+        self.castling_rights = rights;
+    }
+
+    /// Square a pawn can be captured on en passant
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// Set or clear the en passant square, keeping the hash in sync
+    pub fn set_en_passant(&mut self, square: Option<Square>) {
+        if let Some(old) = self.en_passant {
+            self.hash ^= ZOBRIST.en_passant(old.1);
+        }
+        if let Some(new) = square {
+            self.hash ^= ZOBRIST.en_passant(new.1);
+        }
+        self.en_passant = square;
+    }
+
+    /// Whether castling moves are encoded as king-takes-rook
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Choose the castling encoding (UCI_Chess960)
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
+    /// Whether the game is drawn by the fifty-move rule
+    /// 100 plies without a capture, unless the last move delivered checkmate
+    pub fn is_fifty_move_draw(&mut self) -> bool {
+        self.halfmove_clock >= 100 && !self.is_checkmate(self.side_to_move)
+    }
+
     /// Get the piece at a given square
     pub fn get_piece(&self, square: Square) -> Option<Piece> {
         let (row, col) = square;
@@ -171,6 +635,22 @@ impl Board {
     pub fn set_piece(&mut self, square: Square, piece: Option<Piece>) {
         let (row, col) = square;
         if row < 8 && col < 8 {
+            // Keep the Zobrist key in sync: remove the old piece, add the new one
+            // ... and the bitboards
+            let bit: Bitboard = 1 << square_index(square);
+            if let Some(old) = self.squares[row as usize][col as usize] {
+                self.hash ^= ZOBRIST.piece(old, square);
+                self.piece_sets[old.color.index()][old.piece_type.index()] &= !bit;
+                self.occupancy[old.color.index()] &= !bit;
+            }
+            if let Some(new) = piece {
+                self.hash ^= ZOBRIST.piece(new, square);
+                self.piece_sets[new.color.index()][new.piece_type.index()] |= bit;
+                self.occupancy[new.color.index()] |= bit;
+            }
+            if let Some(nnue) = &mut self.nnue {
+                nnue.update(square, self.squares[row as usize][col as usize], piece);
+            }
             self.squares[row as usize][col as usize] = piece;
         }
     }
@@ -182,7 +662,15 @@ impl Board {
 
     /// Set the side to move
     pub fn set_side_to_move(&mut self, color: Color) {
-        self.side_to_move = color;
+        if self.side_to_move != color {
+            self.toggle_side_to_move();
+        }
+    }
+
+    /// Pass the move to the other side, keeping the hash in sync
+    fn toggle_side_to_move(&mut self) {
+        self.side_to_move = self.side_to_move.opposite();
+        self.hash ^= ZOBRIST.side_to_move();
     }
 
     /// Setup the Amazon + K vs R + K starting position
@@ -203,17 +691,25 @@ impl Board {
         // White King on e1 (row 7, col 4)
         board.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
 
-        board.side_to_move = Color::White;
+        board.set_side_to_move(Color::White);
         board
     }
 
     /// Create a board from FEN notation
     /// FEN format: "r3k3/8/8/8/8/8/8/3AK3 w - - 0 1"
-    /// Supported pieces: K/k (King), A/a (Amazon), R/r (Rook), Q/q (Queen as Amazon)
-    pub fn from_fen(fen: &str) -> Option<Self> {
+    /// Supported pieces: K/k (King), A/a (Amazon), R/r (Rook), X/x (Actress),
+    /// P/p (Pawn), N/n (Knight), B/b (Bishop), Q/q (Queen)
+    ///
+    /// Trailing fields may be omitted (EPD style). Castling rights are read as
+    /// "KQkq" or as rook files (Shredder-FEN/X-FEN "HAha"); rights that need
+    /// king-takes-rook encoding (Chess960 setups) switch the board to Chess960 mode.
+    /// An en passant square nobody can capture on is dropped. The position must
+    /// have exactly one king per side, and the side that just moved must not be
+    /// left in check.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
         let parts: Vec<&str> = fen.split_whitespace().collect();
         if parts.is_empty() {
-            return None;
+            return Err(FenError::Empty);
         }
 
         let mut board = Board::new();
@@ -221,65 +717,196 @@ impl Board {
         // Parse piece placement (first part)
         let ranks: Vec<&str> = parts[0].split('/').collect();
         if ranks.len() != 8 {
-            return None;
+            return Err(FenError::BadRankCount(ranks.len()));
         }
 
         for (row, rank_str) in ranks.iter().enumerate() {
+            let rank = 8 - row as u8;
             let mut col = 0usize;
             for c in rank_str.chars() {
-                if col >= 8 {
-                    break;
-                }
-                match c {
-                    '1'..='8' => {
-                        // Empty squares
-                        col += c.to_digit(10).unwrap() as usize;
-                    }
-                    'K' => {
-                        board.set_piece((row as u8, col as u8), Some(Piece::new(PieceType::King, Color::White)));
-                        col += 1;
-                    }
-                    'k' => {
-                        board.set_piece((row as u8, col as u8), Some(Piece::new(PieceType::King, Color::Black)));
-                        col += 1;
-                    }
-                    'A' | 'Q' => {
-                        // Amazon (or Queen treated as Amazon for compatibility)
-                        board.set_piece((row as u8, col as u8), Some(Piece::new(PieceType::Amazon, Color::White)));
-                        col += 1;
-                    }
-                    'a' | 'q' => {
-                        board.set_piece((row as u8, col as u8), Some(Piece::new(PieceType::Amazon, Color::Black)));
-                        col += 1;
-                    }
-                    'R' => {
-                        board.set_piece((row as u8, col as u8), Some(Piece::new(PieceType::Rook, Color::White)));
-                        col += 1;
-                    }
-                    'r' => {
-                        board.set_piece((row as u8, col as u8), Some(Piece::new(PieceType::Rook, Color::Black)));
-                        col += 1;
-                    }
-                    _ => {
-                        // Unknown piece, skip
-                        col += 1;
+                if let Some(empty) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                    col += empty as usize;
+                    if col > 8 {
+                        return Err(FenError::RankOverflow(rank));
                     }
+                    continue;
+                }
+
+                let piece_type = PieceType::from_letter(c).ok_or(FenError::UnknownPiece(c))?;
+                let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+
+                if col >= 8 {
+                    return Err(FenError::RankOverflow(rank));
                 }
+                board.set_piece((row as u8, col as u8), Some(Piece::new(piece_type, color)));
+                col += 1;
+            }
+            if col != 8 {
+                return Err(FenError::RankTooShort(rank));
+            }
+        }
+
+        // Exactly one king per side
+        for color in [Color::White, Color::Black] {
+            let kings = board
+                .squares
+                .iter()
+                .flatten()
+                .filter(|p| **p == Some(Piece::new(PieceType::King, color)))
+                .count();
+            match kings {
+                0 => return Err(FenError::MissingKing(color)),
+                1 => {}
+                _ => return Err(FenError::TooManyKings(color)),
             }
         }
 
-        // Parse side to move (second part)
-        if parts.len() > 1 {
-            board.side_to_move = match parts[1] {
+        // Parse side to move (second part, defaults to White)
+        if let Some(side) = parts.get(1) {
+            board.set_side_to_move(match *side {
                 "w" | "W" => Color::White,
                 "b" | "B" => Color::Black,
-                _ => Color::White,
+                other => return Err(FenError::BadSideToMove(other.to_string())),
+            });
+        }
+
+        // The side that just moved cannot have left its king in check
+        if board.is_in_check(board.side_to_move.opposite()) {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        if let Some(castling) = parts.get(2) {
+            board.parse_castling(castling)?;
+        }
+        if let Some(en_passant) = parts.get(3) {
+            board.parse_en_passant(en_passant)?;
+        }
+
+        // Halfmove clock and fullmove number
+        if let Some(halfmove) = parts.get(4) {
+            board.halfmove_clock = halfmove
+                .parse::<u32>()
+                .map_err(|_| FenError::BadHalfmoveClock(halfmove.to_string()))?;
+        }
+        if let Some(fullmove) = parts.get(5) {
+            board.fullmove_number = match fullmove.parse::<u32>() {
+                Ok(n) if n >= 1 => n,
+                _ => return Err(FenError::BadFullmoveNumber(fullmove.to_string())),
             };
         }
 
-        // Ignore castling, en passant, halfmove clock, and fullmove number for now
+        Ok(board)
+    }
+
+    /// Read the castling field of a FEN
+    fn parse_castling(&mut self, field: &str) -> Result<(), FenError> {
+        let bad = || FenError::BadCastling(field.to_string());
+        if field == "-" {
+            return Ok(());
+        }
+
+        let mut rights = CastlingRights::default();
+        for c in field.chars() {
+            let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+            let row = back_rank(color);
+            let king_file = match self.find_king(color) {
+                Some((king_row, file)) if king_row == row => file,
+                _ => return Err(bad()),
+            };
+            let rook = Some(Piece::new(PieceType::Rook, color));
+            let is_rook = |file: u8| self.get_piece((row, file)) == rook;
+
+            // K and Q name the outermost rook on that side
+            let rook_file = match c.to_ascii_uppercase() {
+                'K' => (king_file + 1..8).rev().find(|&file| is_rook(file)),
+                'Q' => (0..king_file).find(|&file| is_rook(file)),
+                letter @ 'A'..='H' => Some(letter as u8 - b'A').filter(|&file| is_rook(file)),
+                _ => None,
+            }
+            .ok_or_else(bad)?;
+
+            let side = if rook_file > king_file { CastleSide::King } else { CastleSide::Queen };
+            let standard_file = match side {
+                CastleSide::King => 7,
+                CastleSide::Queen => 0,
+            };
+            if king_file != 4 || rook_file != standard_file {
+                self.chess960 = true;
+            }
+            rights.set(color, side, Some(rook_file));
+        }
+
+        self.set_castling_rights(rights);
+        Ok(())
+    }
+
+    /// Read the en passant field of a FEN
+    fn parse_en_passant(&mut self, field: &str) -> Result<(), FenError> {
+        if field == "-" {
+            return Ok(());
+        }
+
+        // The square behind a pawn of the side that just moved, on its double-step rank
+        let bad = || FenError::BadEnPassant(field.to_string());
+        let square = parse_square_name(field).ok_or_else(bad)?;
+        let mover = self.side_to_move.opposite();
+        let pawn_row = square.0 as i8 + PawnMoves::forward(mover);
+        let start_row = PawnMoves::start_row(mover) as i8;
+        if square.0 as i8 - PawnMoves::forward(mover) != start_row
+            || self.get_piece(square).is_some()
+            || self.get_piece((pawn_row as u8, square.1)) != Some(Piece::new(PieceType::Pawn, mover))
+        {
+            return Err(bad());
+        }
+
+        let en_passant = self.en_passant_target(square, (pawn_row as u8, square.1), self.side_to_move);
+        self.set_en_passant(en_passant);
+        Ok(())
+    }
+
+    /// FEN castling field: K/Q for the outermost rook, the rook's file otherwise (X-FEN)
+    fn castling_field(&self) -> String {
+        let mut field = String::new();
+        for color in [Color::White, Color::Black] {
+            let row = back_rank(color);
+            let rook = Some(Piece::new(PieceType::Rook, color));
+            for side in CastleSide::BOTH {
+                let file = match self.castling_rights.rook_file(color, side) {
+                    Some(file) => file,
+                    None => continue,
+                };
+                let (letter, outside) = match side {
+                    CastleSide::King => ('K', file + 1..8),
+                    CastleSide::Queen => ('Q', 0..file),
+                };
+                let letter = if outside.into_iter().any(|f| self.get_piece((row, f)) == rook) {
+                    (b'A' + file) as char
+                } else {
+                    letter
+                };
+                field.push(match color {
+                    Color::White => letter,
+                    Color::Black => letter.to_ascii_lowercase(),
+                });
+            }
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
 
-        Some(board)
+    /// En passant square after a pawn double-stepped over `skipped` to `pawn`
+    /// Only kept if a pawn of `capturer` stands next to it, so positions that
+    /// differ only by an unusable en passant square hash the same
+    fn en_passant_target(&self, skipped: Square, pawn: Square, capturer: Color) -> Option<Square> {
+        let capturing_pawn = Some(Piece::new(PieceType::Pawn, capturer));
+        let beside = [pawn.1.checked_sub(1), Some(pawn.1 + 1).filter(|&file| file < 8)];
+        beside
+            .into_iter()
+            .flatten()
+            .any(|file| self.get_piece((pawn.0, file)) == capturing_pawn)
+            .then_some(skipped)
     }
 
     /// Convert board to FEN notation
@@ -299,16 +926,7 @@ impl Board {
                             fen.push_str(&empty_count.to_string());
                             empty_count = 0;
                         }
-                        let c = match piece.piece_type {
-                            PieceType::King => 'K',
-                            PieceType::Amazon => 'A',
-                            PieceType::Rook => 'R',
-                        };
-                        if piece.color == Color::Black {
-                            fen.push(c.to_ascii_lowercase());
-                        } else {
-                            fen.push(c);
-                        }
+                        fen.push(piece.to_char());
                     }
                 }
             }
@@ -324,38 +942,165 @@ impl Board {
         fen.push(' ');
         fen.push(if self.side_to_move == Color::White { 'w' } else { 'b' });
 
-        // Simplified: no castling, no en passant
-        fen.push_str(" - - 0 1");
+        let en_passant = self.en_passant.map_or("-".to_string(), square_name);
+        fen.push_str(&format!(
+            " {} {} {} {}",
+            self.castling_field(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        ));
 
         fen
     }
 
     /// Execute a move, returns the Move with captured piece info for unmake
     pub fn make_move(&mut self, from: Square, to: Square) -> Move {
+        self.apply_move(Move::new(from, to))
+    }
+
+    /// Execute a generated move (including its promotion piece)
+    /// Castling and en passant are recognised from the position
+    /// Returns the Move with the undo information filled in
+    pub fn apply_move(&mut self, mv: Move) -> Move {
+        let (from, to, promotion) = (mv.from, mv.to, mv.promotion);
+        let color = self.side_to_move;
+
         // Save current position hash to history before making move
-        let hash = self.position_hash();
-        self.position_history.push(hash);
+        self.position_history.push(self.hash);
+        if let Some(nnue) = &mut self.nnue {
+            nnue.push();
+        }
 
-        let captured = self.get_piece(to);
+        let kind = self.move_kind(from, to);
         let piece = self.get_piece(from);
+        let halfmove_clock = self.halfmove_clock;
+        let castling_rights = self.castling_rights;
+        let en_passant = self.en_passant;
+
+        // set_piece and toggle_side_to_move update the hash incrementally
+        let captured = match kind {
+            MoveKind::Castling => {
+                let (king_to, rook_from, rook_to) = castling_squares(from, to, &castling_rights, color);
+                let rook = self.get_piece(rook_from);
+                // Clear both squares first: in Chess960 the king or rook may land on the other's square
+                self.set_piece(from, None);
+                self.set_piece(rook_from, None);
+                self.set_piece(king_to, piece);
+                self.set_piece(rook_to, rook);
+                None
+            }
+            MoveKind::EnPassant | MoveKind::Normal => {
+                let captured_square = if kind == MoveKind::EnPassant { (from.0, to.1) } else { to };
+                let captured = self.get_piece(captured_square);
+                let placed = match (piece, promotion) {
+                    (Some(pawn), Some(piece_type)) => Some(Piece::new(piece_type, pawn.color)),
+                    _ => piece,
+                };
+                self.set_piece(captured_square, None);
+                self.set_piece(to, placed);
+                self.set_piece(from, None);
+                captured
+            }
+        };
 
-        self.set_piece(to, piece);
-        self.set_piece(from, None);
-        self.side_to_move = self.side_to_move.opposite();
+        let pawn_move = piece.is_some_and(|p| p.piece_type == PieceType::Pawn);
+        if let Some(piece) = piece {
+            let mut rights = castling_rights;
+            rights.update(piece, from, to);
+            self.set_castling_rights(rights);
+        }
+        let double_step = pawn_move && from.0.abs_diff(to.0) == 2;
+        let skipped = ((from.0 + to.0) / 2, from.1);
+        let new_en_passant = if double_step { self.en_passant_target(skipped, to, color.opposite()) } else { None };
+        self.set_en_passant(new_en_passant);
+
+        // A capture or pawn move resets the fifty-move count
+        self.halfmove_clock = if captured.is_some() || pawn_move { 0 } else { halfmove_clock + 1 };
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.toggle_side_to_move();
+
+        debug_assert_eq!(self.hash, self.compute_hash(), "Incremental hash out of sync");
 
-        Move { from, to, captured }
+        Move {
+            from,
+            to,
+            captured,
+            halfmove_clock,
+            promotion,
+            kind,
+            castling_rights,
+            en_passant,
+        }
+    }
+
+    /// Classify a move by the special rule it follows, if any
+    /// Castling is a king moving onto its own rook or two files along its rank
+    fn move_kind(&self, from: Square, to: Square) -> MoveKind {
+        let piece = match self.get_piece(from) {
+            Some(piece) => piece,
+            None => return MoveKind::Normal,
+        };
+        match piece.piece_type {
+            PieceType::King => {
+                let onto_own_rook = self.get_piece(to) == Some(Piece::new(PieceType::Rook, piece.color));
+                if onto_own_rook || (from.0 == to.0 && from.1.abs_diff(to.1) == 2) {
+                    MoveKind::Castling
+                } else {
+                    MoveKind::Normal
+                }
+            }
+            PieceType::Pawn if self.en_passant == Some(to) && from.1 != to.1 => MoveKind::EnPassant,
+            _ => MoveKind::Normal,
+        }
     }
 
     /// Undo a move, restoring the previous state
     pub fn unmake_move(&mut self, mv: Move) {
         // Remove the position hash that was added when this move was made
         self.position_history.pop();
+        self.toggle_side_to_move();
+        // The accumulator from before the move is still on the stack
+        let nnue = self.nnue.take();
+
+        match mv.kind {
+            MoveKind::Castling => {
+                let color = self.side_to_move;
+                let (king_to, rook_from, rook_to) = castling_squares(mv.from, mv.to, &mv.castling_rights, color);
+                let king = self.get_piece(king_to);
+                let rook = self.get_piece(rook_to);
+                self.set_piece(king_to, None);
+                self.set_piece(rook_to, None);
+                self.set_piece(mv.from, king);
+                self.set_piece(rook_from, rook);
+            }
+            MoveKind::EnPassant | MoveKind::Normal => {
+                let mut piece = self.get_piece(mv.to);
+                if mv.promotion.is_some() {
+                    // The promoted piece goes back to being a pawn
+                    piece = piece.map(|p| Piece::new(PieceType::Pawn, p.color));
+                }
+                self.set_piece(mv.from, piece);
+                self.set_piece(mv.to, None);
+                let captured_square = if mv.kind == MoveKind::EnPassant { (mv.from.0, mv.to.1) } else { mv.to };
+                self.set_piece(captured_square, mv.captured);
+            }
+        }
 
-        let piece = self.get_piece(mv.to);
+        self.set_castling_rights(mv.castling_rights);
+        self.set_en_passant(mv.en_passant);
+        self.halfmove_clock = mv.halfmove_clock;
+        if self.side_to_move == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        self.nnue = nnue.map(|mut nnue| {
+            nnue.pop();
+            nnue
+        });
 
-        self.set_piece(mv.from, piece);
-        self.set_piece(mv.to, mv.captured);
-        self.side_to_move = self.side_to_move.opposite();
+        debug_assert_eq!(self.hash, self.compute_hash(), "Incremental hash out of sync");
     }
 
     /// Find the position of a King of the given color
@@ -373,25 +1118,22 @@ impl Board {
     }
 
     /// Check if a square is attacked by any piece of the given color
+    /// A square holding a piece of `by_color` counts as not attacked
+    /// (pieces cannot move onto their own side's pieces)
+    /// Looks back from the target with the attack tables: a piece type attacks the square
+    /// if one of its pieces stands where the other color's pattern reaches from it
     pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
-        for row in 0..8 {
-            for col in 0..8 {
-                if let Some(piece) = self.squares[row][col] {
-                    if piece.color == by_color {
-                        let from = (row as u8, col as u8);
-                        let moves = match piece.piece_type {
-                            PieceType::King => KingMoves::generate_moves(self, from),
-                            PieceType::Amazon => AmazonMoves::generate_moves(self, from),
-                            PieceType::Rook => RookMoves::generate_moves(self, from),
-                        };
-                        if moves.contains(&square) {
-                            return true;
-                        }
-                    }
-                }
-            }
+        let index = square_index(square);
+        if self.occupancy[by_color.index()] & (1 << index) != 0 {
+            return false;
         }
-        false
+
+        let occupancy = self.occupancy[0] | self.occupancy[1];
+        let attackers = &self.piece_sets[by_color.index()];
+        PieceType::ALL.into_iter().any(|piece_type| {
+            let pieces = attackers[piece_type.index()];
+            pieces != 0 && piece_attacks(piece_type, by_color.opposite(), index, occupancy) & pieces != 0
+        })
     }
 
     /// Check if the King of the given color is in check
@@ -414,29 +1156,89 @@ impl Board {
                 if let Some(piece) = self.squares[row][col] {
                     if piece.color == color {
                         let from = (row as u8, col as u8);
-                        let pseudo_moves = match piece.piece_type {
-                            PieceType::King => KingMoves::generate_moves(self, from),
-                            PieceType::Amazon => AmazonMoves::generate_moves(self, from),
-                            PieceType::Rook => RookMoves::generate_moves(self, from),
+                        let is_pawn = piece.piece_type == PieceType::Pawn;
+                        let pseudo_moves = if is_pawn {
+                            PawnMoves::generate_moves(self, from)
+                        } else {
+                            piece.piece_type.movement().generate_moves(self, from)
                         };
 
                         // Filter: only keep moves that don't leave King in check
                         for to in pseudo_moves {
                             let mv = self.make_move(from, to);
                             let our_color = color; // make_move toggled side_to_move
-                            if !self.is_in_check(our_color) {
+                            let legal = !self.is_in_check(our_color);
+                            self.unmake_move(mv);
+                            if !legal {
+                                continue;
+                            }
+
+                            // The promotion piece does not change whether our king is safe
+                            if is_pawn && to.0 == PawnMoves::promotion_row(color) {
+                                for &promotion in self.promotion_types {
+                                    legal_moves.push(Move {
+                                        promotion: Some(promotion),
+                                        ..mv
+                                    });
+                                }
+                            } else {
                                 legal_moves.push(mv);
                             }
-                            self.unmake_move(mv);
                         }
                     }
                 }
             }
         }
 
+        for castle in self.castling_moves() {
+            let mv = self.apply_move(castle);
+            let legal = !self.is_in_check(color);
+            self.unmake_move(mv);
+            if legal {
+                legal_moves.push(mv);
+            }
+        }
+
         legal_moves
     }
 
+    /// Castling moves for the side to move, before the final king-safety check
+    /// Every square the king or rook crosses must be empty (apart from the two
+    /// of them), and the king may not start on or pass an attacked square
+    fn castling_moves(&self) -> Vec<Move> {
+        let color = self.side_to_move;
+        let row = back_rank(color);
+        let king_from = match self.find_king(color) {
+            Some(square) if square.0 == row => square,
+            _ => return Vec::new(),
+        };
+
+        let mut moves = Vec::new();
+        for side in CastleSide::BOTH {
+            let rook_file = match self.castling_rights.rook_file(color, side) {
+                Some(file) => file,
+                None => continue,
+            };
+            if self.get_piece((row, rook_file)) != Some(Piece::new(PieceType::Rook, color)) {
+                continue;
+            }
+
+            let king_path = file_span(king_from.1, side.king_to_file());
+            let rook_path = file_span(rook_file, side.rook_to_file());
+            let path_clear = king_path
+                .clone()
+                .chain(rook_path)
+                .all(|file| file == king_from.1 || file == rook_file || self.get_piece((row, file)).is_none());
+            if !path_clear || king_path.into_iter().any(|file| self.is_square_attacked((row, file), color.opposite())) {
+                continue;
+            }
+
+            let to = if self.chess960 { (row, rook_file) } else { (row, side.king_to_file()) };
+            moves.push(Move::new(king_from, to));
+        }
+        moves
+    }
+
     /// Check if the given color is in checkmate
     pub fn is_checkmate(&mut self, color: Color) -> bool {
         if !self.is_in_check(color) {
@@ -445,11 +1247,11 @@ impl Board {
 
         // Save and set correct side to move
         let original_side = self.side_to_move;
-        self.side_to_move = color;
+        self.set_side_to_move(color);
 
         let has_no_moves = self.generate_legal_moves().is_empty();
 
-        self.side_to_move = original_side;
+        self.set_side_to_move(original_side);
         has_no_moves
     }
 
@@ -460,11 +1262,11 @@ impl Board {
         }
 
         let original_side = self.side_to_move;
-        self.side_to_move = color;
+        self.set_side_to_move(color);
 
         let has_no_moves = self.generate_legal_moves().is_empty();
 
-        self.side_to_move = original_side;
+        self.set_side_to_move(original_side);
         has_no_moves
     }
 }
@@ -495,19 +1297,7 @@ impl std::fmt::Display for Board {
             for col in 0..8 {
                 let piece_char = match self.squares[row][col] {
                     None => '.',
-                    Some(piece) => {
-                        let c = match piece.piece_type {
-                            PieceType::King => 'K',
-                            PieceType::Amazon => 'A', // A for Amazon
-                            PieceType::Rook => 'R',
-                        };
-                        // Lowercase for black pieces
-                        if piece.color == Color::Black {
-                            c.to_ascii_lowercase()
-                        } else {
-                            c
-                        }
-                    }
+                    Some(piece) => piece.to_char(), // Lowercase for black pieces
                 };
                 write!(f, "{} ", piece_char)?;
             }
@@ -548,6 +1338,17 @@ mod tests {
         assert_eq!(white_amazon.color, Color::White);
     }
 
+    #[test]
+    fn test_piece_declarations() {
+        for piece_type in PieceType::ALL {
+            assert_eq!(PieceType::ALL[piece_type.index()], piece_type);
+            assert_eq!(PieceType::from_letter(piece_type.info().letter), Some(piece_type));
+            assert!(!piece_type.movement().rays().is_empty(), "{}", piece_type.info().name);
+        }
+        assert_eq!(PieceType::from_letter('a'), Some(PieceType::Amazon));
+        assert_eq!(Piece::new(PieceType::Rook, Color::Black).to_char(), 'r');
+    }
+
     #[test]
     fn test_board_empty() {
         let board = Board::new();
@@ -754,6 +1555,30 @@ mod tests {
         assert!(board.is_in_check(Color::Black));
     }
 
+    #[test]
+    fn test_king_in_check_by_actress_camel_leap() {
+        let mut board = Board::new();
+
+        // Black king at e4 (row 4, col 4)
+        board.set_piece((4, 4), Some(Piece::new(PieceType::King, Color::Black)));
+
+        // White Actress at f7 (row 1, col 5) - Camel leap (3,1), not a Queen or Knight line
+        board.set_piece((1, 5), Some(Piece::new(PieceType::Actress, Color::White)));
+        assert!(board.is_in_check(Color::Black));
+
+        // The same square is not attacked by an Amazon
+        board.set_piece((1, 5), Some(Piece::new(PieceType::Amazon, Color::White)));
+        assert!(!board.is_in_check(Color::Black));
+    }
+
+    #[test]
+    fn test_actress_fen_roundtrip() {
+        let fen = "4k3/8/8/8/8/8/8/3XK3 w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.get_piece((7, 3)), Some(Piece::new(PieceType::Actress, Color::White)));
+        assert_eq!(board.to_fen(), fen);
+    }
+
     #[test]
     fn test_king_in_check_by_rook() {
         let mut board = Board::new();
@@ -995,4 +1820,382 @@ mod tests {
         }
         assert_eq!(original.side_to_move(), restored.side_to_move());
     }
+
+    #[test]
+    fn test_from_fen_accepts_epd_fields() {
+        // startpos.epd style: no clock fields
+        let board = Board::from_fen("8/3r1r2/8/3rkr2/8/8/8/3AKA2 w - -").expect("EPD should parse");
+        assert_eq!(board.side_to_move(), Color::White);
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        let cases = [
+            ("", FenError::Empty),
+            ("r3k3/8/8/8/8/8/3AK3 w - - 0 1", FenError::BadRankCount(7)),
+            ("r3k4/8/8/8/8/8/8/3AK3 w - - 0 1", FenError::RankOverflow(8)),
+            ("r3k3/8/8/8/8/8/8/3AK3R w - - 0 1", FenError::RankOverflow(1)),
+            ("r3k3/8/8/8/8/8/8/3AK2 w - - 0 1", FenError::RankTooShort(1)),
+            ("r3k3/8/8/8/8/8/8/3ZK3 w - - 0 1", FenError::UnknownPiece('Z')),
+            ("r3k3/8/8/8/8/8/8/3A4 w - - 0 1", FenError::MissingKing(Color::White)),
+            ("r3k3/8/8/8/8/8/8/2KAK3 w - - 0 1", FenError::TooManyKings(Color::White)),
+            ("r3k3/8/8/8/8/8/8/3AK3 x - - 0 1", FenError::BadSideToMove("x".to_string())),
+            ("r3k3/8/8/8/8/8/8/3AK3 w - - -1 1", FenError::BadHalfmoveClock("-1".to_string())),
+            ("r3k3/8/8/8/8/8/8/3AK3 w - - 0 0", FenError::BadFullmoveNumber("0".to_string())),
+        ];
+        for (fen, expected) in cases {
+            assert_eq!(Board::from_fen(fen).err(), Some(expected), "FEN: {:?}", fen);
+        }
+    }
+
+    #[test]
+    fn test_from_fen_rejects_opponent_in_check() {
+        // Black king on e8 attacked by the Amazon on e1, but it is White to move
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/3KA3 w - - 0 1").err(),
+            Some(FenError::OpponentInCheck)
+        );
+        // Same position with Black to move is a normal check
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/3KA3 b - - 0 1").is_ok());
+    }
+
+    #[test]
+    fn test_fen_clock_roundtrip() {
+        let fen = "r3k3/8/8/8/8/8/8/3AK3 b - - 37 52";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.halfmove_clock(), 37);
+        assert_eq!(board.fullmove_number(), 52);
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_clocks_follow_make_unmake() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/3r4/3AK3 w - - 5 10").unwrap();
+
+        // Quiet white move: clock counts up, move number unchanged
+        let quiet = board.make_move((7, 4), (7, 5));
+        assert_eq!((board.halfmove_clock(), board.fullmove_number()), (6, 10));
+
+        // Black captures the Amazon: clock resets, move number advances
+        let capture = board.make_move((6, 3), (7, 3));
+        assert_eq!((board.halfmove_clock(), board.fullmove_number()), (0, 11));
+
+        board.unmake_move(capture);
+        assert_eq!((board.halfmove_clock(), board.fullmove_number()), (6, 10));
+        board.unmake_move(quiet);
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/3r4/3AK3 w - - 5 10");
+    }
+
+    #[test]
+    fn test_fifty_move_draw() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert!(!board.is_fifty_move_draw());
+        board.make_move((7, 4), (7, 5));
+        assert!(board.is_fifty_move_draw());
+
+        // Checkmate on the hundredth ply still wins: Ra8# with the king on e6
+        let mut board = Board::from_fen("4k3/8/4K3/8/8/8/8/R7 w - - 99 80").unwrap();
+        board.make_move((7, 0), (0, 0));
+        assert!(board.is_checkmate(Color::Black));
+        assert!(!board.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_queen_and_amazon_are_distinct_in_fen() {
+        let fen = "3qk3/8/8/8/8/8/8/3AK3 w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.get_piece((0, 3)), Some(Piece::new(PieceType::Queen, Color::Black)));
+        assert_eq!(board.get_piece((7, 3)), Some(Piece::new(PieceType::Amazon, Color::White)));
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_promotion_moves() {
+        let mut board = Board::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+        let promotions: Vec<Move> = board
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|mv| mv.from == (1, 4))
+            .collect();
+        let types: Vec<_> = promotions.iter().map(|mv| mv.promotion).collect();
+        assert_eq!(types, STANDARD_PROMOTIONS.iter().map(|&pt| Some(pt)).collect::<Vec<_>>());
+
+        // Promote to a knight and take it back
+        let knight = *promotions.iter().find(|mv| mv.promotion == Some(PieceType::Knight)).unwrap();
+        let made = board.apply_move(knight);
+        assert_eq!(board.get_piece((0, 4)), Some(Piece::new(PieceType::Knight, Color::White)));
+        board.unmake_move(made);
+        assert_eq!(board.get_piece((1, 4)), Some(Piece::new(PieceType::Pawn, Color::White)));
+        assert_eq!(board.to_fen(), "8/4P3/8/8/8/8/8/k1K5 w - - 0 1");
+    }
+
+    #[test]
+    fn test_promotion_to_amazon_variant() {
+        let mut board = Board::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+        board.set_promotion_types(AMAZON_PROMOTIONS);
+        let moves = board.generate_legal_moves();
+        assert!(moves.iter().any(|mv| mv.promotion == Some(PieceType::Amazon)));
+        assert!(!moves.iter().any(|mv| mv.promotion == Some(PieceType::Queen)));
+    }
+
+    #[test]
+    fn test_pawn_move_resets_halfmove_clock() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 30 40").unwrap();
+        board.make_move((6, 4), (4, 4));
+        assert_eq!(board.halfmove_clock(), 0);
+    }
+
+    #[test]
+    fn test_castling_fen_roundtrip() {
+        for fen in [
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 3 9",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.to_fen(), fen);
+            assert!(!board.is_chess960());
+        }
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qk - 0 1").unwrap();
+        assert_eq!(board.castling_rights().rook_file(Color::White, CastleSide::Queen), Some(0));
+        assert_eq!(board.castling_rights().rook_file(Color::White, CastleSide::King), None);
+        assert_eq!(board.castling_rights().rook_file(Color::Black, CastleSide::King), Some(7));
+    }
+
+    #[test]
+    fn test_chess960_castling_fen() {
+        // Shredder-FEN rook files; the king on g1 makes this a Chess960 position
+        let board = Board::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9").unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(board.castling_rights().rook_file(Color::White, CastleSide::Queen), Some(5));
+        assert_eq!(board.to_fen(), "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+
+        // An inner rook is named by its file (X-FEN)
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/RR2K3 w B - 0 1").unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/RR2K3 w B - 0 1");
+    }
+
+    #[test]
+    fn test_castling_fen_errors() {
+        let cases = [
+            ("r3k2r/8/8/8/8/8/8/R3K3 w K - 0 1", FenError::BadCastling("K".to_string())),
+            ("r3k2r/8/8/8/8/8/4K3/R6R w Q - 0 1", FenError::BadCastling("Q".to_string())),
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KX - 0 1", FenError::BadCastling("KX".to_string())),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - e6 0 1", FenError::BadEnPassant("e6".to_string())),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d3 0 1", FenError::BadEnPassant("d3".to_string())),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - z9 0 1", FenError::BadEnPassant("z9".to_string())),
+        ];
+        for (fen, expected) in cases {
+            assert_eq!(Board::from_fen(fen).err(), Some(expected), "FEN: {:?}", fen);
+        }
+    }
+
+    #[test]
+    fn test_castling_make_unmake() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        let castles: Vec<Move> = board
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|mv| mv.kind == MoveKind::Castling)
+            .collect();
+        assert_eq!(castles.len(), 2);
+
+        let short = board.make_move((7, 4), (7, 6));
+        assert_eq!(short.kind, MoveKind::Castling);
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+        let long = board.make_move((0, 4), (0, 2));
+        assert_eq!(board.to_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+
+        board.unmake_move(long);
+        board.unmake_move(short);
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_castling_rights_lost() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        // Rook a1 takes rook a8: both queen-side rights are gone
+        board.make_move((7, 0), (0, 0));
+        assert_eq!(board.to_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1");
+
+        // King move: black loses the remaining right
+        board.make_move((0, 4), (1, 4));
+        assert!(board.castling_rights().rook_file(Color::Black, CastleSide::King).is_none());
+        assert_eq!(board.castling_rights().rook_file(Color::White, CastleSide::King), Some(7));
+    }
+
+    #[test]
+    fn test_no_castling_through_check() {
+        // The black rook on f8 covers f1: only the queen side is open
+        let mut board = Board::from_fen("4kr2/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let castles: Vec<Move> = board
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|mv| mv.kind == MoveKind::Castling)
+            .collect();
+        assert_eq!(castles.len(), 1);
+        assert_eq!(castles[0].to, (7, 2));
+
+        // In check: no castling at all
+        let mut board = Board::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert!(!board.generate_legal_moves().iter().any(|mv| mv.kind == MoveKind::Castling));
+    }
+
+    #[test]
+    fn test_chess960_king_takes_rook() {
+        // King b1 and rook a1: queen-side castling moves the king right, to c1
+        let mut board = Board::from_fen("1k6/8/8/8/8/8/8/RK5R w AH - 0 1").unwrap();
+        assert!(board.is_chess960());
+        let castles: Vec<Move> = board
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|mv| mv.kind == MoveKind::Castling)
+            .collect();
+        let targets: Vec<Square> = castles.iter().map(|mv| mv.to).collect();
+        assert_eq!(targets, vec![(7, 7), (7, 0)]);
+
+        let queen_side = board.apply_move(castles[1]);
+        assert_eq!(board.to_fen(), "1k6/8/8/8/8/8/8/2KR3R b - - 1 1");
+        board.unmake_move(queen_side);
+        assert_eq!(board.to_fen(), "1k6/8/8/8/8/8/8/RK5R w KQ - 0 1");
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let fen = "4k3/8/8/8/5p2/8/4P3/4K3 w - - 0 1";
+        let mut board = Board::from_fen(fen).unwrap();
+        let double_step = board.make_move((6, 4), (4, 4));
+        assert_eq!(board.en_passant(), Some((5, 4)));
+        assert_eq!(board.to_fen(), "4k3/8/8/8/4Pp2/8/8/4K3 b - e3 0 1");
+
+        let capture = board.make_move((4, 5), (5, 4));
+        assert_eq!(capture.kind, MoveKind::EnPassant);
+        assert_eq!(capture.captured, Some(Piece::new(PieceType::Pawn, Color::White)));
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/4p3/8/4K3 w - - 0 2");
+
+        board.unmake_move(capture);
+        board.unmake_move(double_step);
+        assert_eq!(board.to_fen(), fen);
+
+        // Nobody can capture: the square is not recorded
+        board.make_move((6, 4), (5, 4));
+        board.make_move((0, 4), (0, 3));
+        board.make_move((5, 4), (4, 4));
+        assert_eq!(board.en_passant(), None);
+    }
+
+    #[test]
+    fn test_hash_includes_castling_and_en_passant() {
+        let with_rights = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let without = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1").unwrap();
+        assert_ne!(with_rights.position_hash(), without.position_hash());
+        assert_eq!(with_rights.position_hash(), with_rights.compute_hash());
+
+        let en_passant = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        let none = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 2").unwrap();
+        assert_ne!(en_passant.position_hash(), none.position_hash());
+        assert_eq!(en_passant.position_hash(), en_passant.compute_hash());
+    }
+
+    #[test]
+    fn test_hash_incremental_matches_recompute() {
+        let mut board = Board::setup_amazon_vs_rook();
+        assert_eq!(board.position_hash(), board.compute_hash());
+
+        // Walk a few plies deep and back, checking the key at every step
+        let moves = board.generate_legal_moves();
+        for mv in moves {
+            let made = board.make_move(mv.from, mv.to);
+            assert_eq!(board.position_hash(), board.compute_hash());
+            let replies = board.generate_legal_moves();
+            for reply in replies.into_iter().take(5) {
+                let made_reply = board.make_move(reply.from, reply.to);
+                assert_eq!(board.position_hash(), board.compute_hash());
+                board.unmake_move(made_reply);
+            }
+            board.unmake_move(made);
+            assert_eq!(board.position_hash(), board.compute_hash());
+        }
+    }
+
+    #[test]
+    fn test_hash_depends_on_side_to_move() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let white_hash = board.position_hash();
+        board.set_side_to_move(Color::Black);
+        assert_ne!(board.position_hash(), white_hash);
+        board.set_side_to_move(Color::White);
+        assert_eq!(board.position_hash(), white_hash);
+    }
+
+    #[test]
+    fn test_hash_restored_after_capture() {
+        let mut board = Board::new();
+        board.set_piece((4, 4), Some(Piece::new(PieceType::King, Color::White)));
+        board.set_piece((3, 4), Some(Piece::new(PieceType::Rook, Color::Black)));
+        let before = board.position_hash();
+
+        let mv = board.make_move((4, 4), (3, 4));
+        assert_ne!(board.position_hash(), before);
+        board.unmake_move(mv);
+        assert_eq!(board.position_hash(), before);
+    }
+
+    #[test]
+    fn test_fen_and_setup_hash_match() {
+        let setup = Board::setup_amazon_vs_rook();
+        let fen = Board::from_fen("r3k3/8/8/8/8/8/8/3AK3 w - - 0 1").unwrap();
+        assert_eq!(setup.position_hash(), fen.position_hash());
+    }
+
+    #[test]
+    fn test_repetition_detection() {
+        let mut board = Board::setup_amazon_vs_rook();
+        assert_eq!(board.repetition_count(), 0);
+
+        // Shuffle both kings back and forth: e1-f1, e8-f8, f1-e1, f8-e8
+        for _ in 0..2 {
+            board.make_move((7, 4), (7, 5));
+            board.make_move((0, 4), (0, 5));
+            board.make_move((7, 5), (7, 4));
+            board.make_move((0, 5), (0, 4));
+        }
+
+        assert!(board.is_repetition());
+        assert_eq!(board.repetition_count(), 2);
+    }
+
+    #[test]
+    fn test_attack_tables_match_piece_movement() {
+        // Every kind of piece, with blockers, castling rights and a pawn on each side
+        let fens = [
+            "r3k2r/2n5/1b6/8/2X2p2/2q5/4P3/3AK2R w Kkq - 0 1",
+            "r3k3/8/8/8/8/8/8/3AK3 b - - 0 1",
+            "4k3/8/8/3A4/8/8/1r6/4K3 w - - 0 1",
+        ];
+        for fen in fens {
+            let board = Board::from_fen(fen).unwrap();
+            for row in 0..8 {
+                for col in 0..8 {
+                    for by_color in [Color::White, Color::Black] {
+                        let target = (row, col);
+                        let own = board.get_piece(target).is_some_and(|p| p.color == by_color);
+                        let by_movement = !own
+                            && (0..8).any(|r| {
+                                (0..8).any(|c| {
+                                    board.get_piece((r, c)).is_some_and(|piece| {
+                                        piece.color == by_color
+                                            && piece.piece_type.movement().attacks(&board, by_color, (r, c), target)
+                                    })
+                                })
+                            });
+                        assert_eq!(board.is_square_attacked(target, by_color), by_movement, "{} {:?}", fen, target);
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,786 @@
+// Engine matches
+// Plays two engine configurations against each other over a set of opening
+// positions, each opening twice with the colours swapped, and measures the
+// difference in strength: Elo with a 95% error margin, and a sequential
+// probability ratio test (SPRT) that ends the match once it is decided.
+//
+// A configuration is either this engine with its own weights and limits,
+// or an external UCI engine run as a child process.
+
+use crate::board::{Board, Color, Move};
+use crate::eval_params::EvalParams;
+use crate::evaluation::{Evaluator, HandCrafted};
+use crate::pgn::PgnGame;
+use crate::search::{search, SearchLimits, SearchSignals};
+use crate::transposition::TranspositionTable;
+use crate::uci::move_to_uci;
+use crate::uci_client::{UciClientError, UciEngine};
+use crate::variant::variant_of;
+use std::fs;
+use std::path::Path;
+
+/// Depth used when a configuration sets neither depth nor movetime
+pub const DEFAULT_MATCH_DEPTH: i32 = 4;
+
+/// Games longer than this are adjudicated as draws
+pub const DEFAULT_MAX_PLIES: usize = 400;
+
+/// Hash size of the built-in players, in MB
+const PLAYER_HASH_MB: usize = 16;
+
+/// Two-sided 95% quantile of the normal distribution
+const Z_95: f64 = 1.959964;
+
+// =============================================================================
+// Players
+// =============================================================================
+
+/// One side of a match
+pub trait Player {
+    fn name(&self) -> &str;
+
+    /// Called before every game, with its starting position
+    fn new_game(&mut self, start: &Board) -> Result<(), String>;
+
+    /// Pick a move; `start` and `moves` are the game so far, `board` the
+    /// current position (which has at least one legal move)
+    fn choose_move(&mut self, start: &Board, moves: &[Move], board: &Board) -> Result<Move, String>;
+}
+
+/// This engine, with its own evaluation and search limits
+pub struct InternalPlayer {
+    name: String,
+    evaluator: Box<dyn Evaluator>,
+    limits: SearchLimits,
+    tt: TranspositionTable,
+}
+
+impl InternalPlayer {
+    pub fn new(name: &str, evaluator: Box<dyn Evaluator>, limits: SearchLimits) -> Self {
+        InternalPlayer {
+            name: name.to_string(),
+            evaluator,
+            limits,
+            tt: TranspositionTable::new(PLAYER_HASH_MB),
+        }
+    }
+}
+
+impl Player for InternalPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self, _start: &Board) -> Result<(), String> {
+        self.tt.clear();
+        Ok(())
+    }
+
+    fn choose_move(&mut self, _start: &Board, _moves: &[Move], board: &Board) -> Result<Move, String> {
+        let mut board = board.clone();
+        let signals = SearchSignals::new(false);
+        search(&mut board, &self.limits, &mut self.tt, self.evaluator.as_ref(), None, &signals, &mut |_| {})
+            .map(|info| info.best_move())
+            .ok_or_else(|| "search found no move".to_string())
+    }
+}
+
+/// An external UCI engine running as a child process
+pub struct ExternalPlayer {
+    engine: UciEngine,
+    name: String,
+    limits: SearchLimits,
+    /// Set UCI_Variant from each game's start position (the engine has the
+    /// option and the user did not set it)
+    sets_variant: bool,
+    /// UCI_Variant value last sent
+    variant: Option<&'static str>,
+}
+
+impl ExternalPlayer {
+    /// Start the engine (program and arguments separated by spaces) and set its options
+    /// Without a `name` the engine's "id name" is used
+    pub fn start(
+        command: &str,
+        name: Option<&str>,
+        options: &[(String, String)],
+        limits: SearchLimits,
+    ) -> Result<Self, String> {
+        let failed = |e: UciClientError| format!("{}: {}", command, e);
+        let engine = UciEngine::start(command).map_err(failed)?;
+        ExternalPlayer::with_engine(engine, name, options, limits).map_err(failed)
+    }
+
+    /// Player for an engine that has completed the "uci" handshake
+    fn with_engine(
+        mut engine: UciEngine,
+        name: Option<&str>,
+        options: &[(String, String)],
+        limits: SearchLimits,
+    ) -> Result<Self, UciClientError> {
+        for (option, value) in options {
+            engine.set_option(option, value)?;
+        }
+        engine.is_ready()?;
+        let sets_variant = engine.options().iter().any(|line| line.starts_with("option name UCI_Variant "))
+            && !options.iter().any(|(option, _)| option.eq_ignore_ascii_case("UCI_Variant"));
+        Ok(ExternalPlayer {
+            name: name.unwrap_or(engine.name()).to_string(),
+            engine,
+            limits,
+            sets_variant,
+            variant: None,
+        })
+    }
+}
+
+impl Player for ExternalPlayer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self, start: &Board) -> Result<(), String> {
+        let failed = |e: UciClientError| format!("{}: {}", self.name, e);
+        // Positions are sent as FENs, but the rules come from UCI_Variant
+        if self.sets_variant {
+            let variant = variant_of(start).external_name;
+            if self.variant != Some(variant) {
+                self.engine.set_option("UCI_Variant", variant).map_err(failed)?;
+                self.variant = Some(variant);
+            }
+        }
+        self.engine.new_game().map_err(failed)
+    }
+
+    fn choose_move(&mut self, start: &Board, moves: &[Move], board: &Board) -> Result<Move, String> {
+        let failed = |e: UciClientError| format!("{}: {}", self.name, e);
+        self.engine.set_position(start, moves).map_err(failed)?;
+        let reply = self.engine.go(&self.limits, board.side_to_move()).map_err(failed)?;
+        let best = reply.best_move.ok_or_else(|| format!("{} has no move", self.name))?;
+        board
+            .clone()
+            .generate_legal_moves()
+            .into_iter()
+            .find(|mv| mv.same_as(&best))
+            .ok_or_else(|| format!("{} played an illegal move {}", self.name, move_to_uci(&best)))
+    }
+}
+
+// =============================================================================
+// Engine Configurations
+// =============================================================================
+
+/// A player described on the command line as comma-separated key=value pairs:
+/// `depth=6,params=tuned.toml` or `cmd=/path/to/engine --uci,movetime=100,option.Hash=64`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EngineSpec {
+    pub name: Option<String>,
+    /// External UCI engine to launch
+    pub command: Option<String>,
+    /// Evaluation weights for this engine
+    pub params: Option<String>,
+    pub depth: Option<i32>,
+    pub movetime: Option<u64>,
+    /// UCI options for an external engine
+    pub options: Vec<(String, String)>,
+}
+
+impl EngineSpec {
+    /// Parse `key=value,key=value`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parsed = EngineSpec::default();
+        for pair in spec.split(',').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| format!("expected key=value, got {}", pair))?;
+            let number = || format!("{} needs a number", key);
+            match key {
+                "name" => parsed.name = Some(value.to_string()),
+                "cmd" => parsed.command = Some(value.to_string()),
+                "params" => parsed.params = Some(value.to_string()),
+                "depth" => parsed.depth = Some(value.parse().map_err(|_| number())?),
+                "movetime" => parsed.movetime = Some(value.parse().map_err(|_| number())?),
+                _ => match key.strip_prefix("option.") {
+                    Some(option) => parsed.options.push((option.to_string(), value.to_string())),
+                    None => return Err(format!("unknown key {}", key)),
+                },
+            }
+        }
+        if parsed.command.is_some() && parsed.params.is_some() {
+            return Err("params only applies to the built-in engine".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Search limits: the given depth and/or movetime, or the default depth
+    pub fn limits(&self) -> SearchLimits {
+        SearchLimits {
+            depth: self.depth.or(Some(DEFAULT_MATCH_DEPTH).filter(|_| self.movetime.is_none())),
+            movetime: self.movetime,
+            ..SearchLimits::default()
+        }
+    }
+
+    /// Create the player; `default_name` is used when the spec has no name
+    pub fn build(&self, default_name: &str) -> Result<Box<dyn Player>, String> {
+        if let Some(command) = &self.command {
+            let player = ExternalPlayer::start(command, self.name.as_deref(), &self.options, self.limits())?;
+            return Ok(Box::new(player));
+        }
+        let params = match &self.params {
+            Some(path) => EvalParams::load(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?,
+            None => EvalParams::default(),
+        };
+        let name = self.name.as_deref().unwrap_or(default_name);
+        Ok(Box::new(InternalPlayer::new(name, Box::new(HandCrafted::new(params)), self.limits())))
+    }
+}
+
+// =============================================================================
+// Games
+// =============================================================================
+
+/// Final result of a game
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    /// PGN result token
+    pub fn to_pgn(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+
+    /// A win for `color`
+    fn win_for(color: Color) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+}
+
+/// Why a game ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+    /// Adjudicated as a draw after the ply limit
+    MaxPlies,
+    /// A player failed to move (crash, illegal move); it loses
+    Forfeit(String),
+}
+
+impl std::fmt::Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Termination::Checkmate => write!(f, "checkmate"),
+            Termination::Stalemate => write!(f, "stalemate"),
+            Termination::Repetition => write!(f, "threefold repetition"),
+            Termination::FiftyMoves => write!(f, "fifty-move rule"),
+            Termination::MaxPlies => write!(f, "move limit"),
+            Termination::Forfeit(reason) => write!(f, "forfeit: {}", reason),
+        }
+    }
+}
+
+impl Termination {
+    /// Value of the PGN Termination tag
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            Termination::MaxPlies => "adjudication",
+            Termination::Forfeit(_) => "abandoned",
+            _ => "normal",
+        }
+    }
+}
+
+/// A finished game
+pub struct GameRecord {
+    pub white: String,
+    pub black: String,
+    pub start: Board,
+    pub moves: Vec<Move>,
+    pub result: GameResult,
+    pub termination: Termination,
+}
+
+impl GameRecord {
+    /// The game as PGN
+    pub fn to_pgn(&self, event: &str, round: usize) -> String {
+        let mut game = PgnGame::with_tags(&self.start, variant_of(&self.start));
+        game.set_tag("Event", event);
+        game.set_tag("Round", &round.to_string());
+        game.set_tag("White", &self.white);
+        game.set_tag("Black", &self.black);
+        for &mv in &self.moves {
+            game.push(mv, None);
+        }
+        game.finish(self.result.to_pgn(), self.termination.to_pgn(), Some(&self.result_comment()));
+        game.to_pgn()
+    }
+
+    /// How the game ended, in words: "White mates", "Draw by stalemate"
+    fn result_comment(&self) -> String {
+        let winner = match self.result {
+            GameResult::WhiteWins => "White",
+            GameResult::BlackWins => "Black",
+            GameResult::Draw => return format!("Draw by {}", self.termination),
+        };
+        match self.termination {
+            Termination::Checkmate => format!("{} mates", winner),
+            _ => format!("{} wins by {}", winner, self.termination),
+        }
+    }
+}
+
+/// How a position ends the game, if it does
+fn adjudicate(board: &mut Board, plies: usize, max_plies: usize) -> Option<(GameResult, Termination)> {
+    let side = board.side_to_move();
+    if board.is_checkmate(side) {
+        return Some((GameResult::win_for(side.opposite()), Termination::Checkmate));
+    }
+    if board.is_stalemate(side) {
+        return Some((GameResult::Draw, Termination::Stalemate));
+    }
+    if board.repetition_count() >= 2 {
+        return Some((GameResult::Draw, Termination::Repetition));
+    }
+    if board.is_fifty_move_draw() {
+        return Some((GameResult::Draw, Termination::FiftyMoves));
+    }
+    if plies >= max_plies {
+        return Some((GameResult::Draw, Termination::MaxPlies));
+    }
+    None
+}
+
+/// Play one game from `start` until it is adjudicated
+pub fn play_game(white: &mut dyn Player, black: &mut dyn Player, start: &Board, max_plies: usize) -> GameRecord {
+    let mut board = start.clone();
+    board.clear_history();
+    let mut moves = Vec::new();
+
+    let (result, termination) = match white.new_game(start).and_then(|_| black.new_game(start)) {
+        // Nobody is more to blame for a failed start than the other; call it a draw
+        Err(e) => (GameResult::Draw, Termination::Forfeit(e)),
+        Ok(()) => loop {
+            if let Some(end) = adjudicate(&mut board, moves.len(), max_plies) {
+                break end;
+            }
+
+            let side = board.side_to_move();
+            let player: &mut dyn Player = match side {
+                Color::White => &mut *white,
+                Color::Black => &mut *black,
+            };
+            match player.choose_move(start, &moves, &board) {
+                Ok(mv) => moves.push(board.apply_move(mv)),
+                Err(e) => break (GameResult::win_for(side.opposite()), Termination::Forfeit(e)),
+            }
+        },
+    };
+
+    GameRecord {
+        white: white.name().to_string(),
+        black: black.name().to_string(),
+        start: start.clone(),
+        moves,
+        result,
+        termination,
+    }
+}
+
+// =============================================================================
+// Statistics
+// =============================================================================
+
+/// Elo difference for an expected score
+fn score_to_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Expected score for an Elo difference
+fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// Results from the first engine's point of view
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl MatchStats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// Count a game in which the first engine had `first_color`
+    pub fn record(&mut self, result: GameResult, first_color: Color) {
+        match (result, first_color) {
+            (GameResult::Draw, _) => self.draws += 1,
+            (GameResult::WhiteWins, Color::White) | (GameResult::BlackWins, Color::Black) => self.wins += 1,
+            _ => self.losses += 1,
+        }
+    }
+
+    /// Points per game
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+
+    /// Variance of a single game's points
+    fn variance(&self) -> f64 {
+        let n = self.games().max(1) as f64;
+        let s = self.score();
+        (self.wins as f64 * (1.0 - s).powi(2) + self.draws as f64 * (0.5 - s).powi(2) + self.losses as f64 * s.powi(2))
+            / n
+    }
+
+    /// Elo difference (infinite after only wins or only losses)
+    pub fn elo(&self) -> f64 {
+        score_to_elo(self.score())
+    }
+
+    /// Half-width of the 95% confidence interval of `elo`
+    pub fn elo_error(&self) -> f64 {
+        let n = self.games().max(1) as f64;
+        let margin = Z_95 * (self.variance() / n).sqrt();
+        let s = self.score();
+        let low = (s - margin).max(1e-6);
+        let high = (s + margin).min(1.0 - 1e-6);
+        (score_to_elo(high) - score_to_elo(low)) / 2.0
+    }
+
+    /// Log-likelihood ratio of H1 (elo1) against H0 (elo0), using the
+    /// normal approximation of the per-game score
+    pub fn llr(&self, sprt: &Sprt) -> f64 {
+        let variance = self.variance();
+        if self.games() == 0 || variance == 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (elo_to_score(sprt.elo0), elo_to_score(sprt.elo1));
+        let n = self.games() as f64;
+        n * (s1 - s0) * (2.0 * self.score() - s0 - s1) / (2.0 * variance)
+    }
+}
+
+// =============================================================================
+// SPRT
+// =============================================================================
+
+/// Sequential probability ratio test: is the first engine elo1 stronger
+/// (H1) or only elo0 (H0)?
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// False positive rate
+    pub alpha: f64,
+    /// False negative rate
+    pub beta: f64,
+}
+
+/// Outcome of the test so far
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SprtVerdict {
+    AcceptH0,
+    AcceptH1,
+    Continue,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Sprt {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// LLR bounds: below the first H0 is accepted, above the second H1
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    pub fn verdict(&self, llr: f64) -> SprtVerdict {
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtVerdict::AcceptH1
+        } else if llr <= lower {
+            SprtVerdict::AcceptH0
+        } else {
+            SprtVerdict::Continue
+        }
+    }
+}
+
+// =============================================================================
+// Matches
+// =============================================================================
+
+/// Read opening positions, one FEN or EPD per line (`#` starts a comment)
+pub fn parse_openings(text: &str) -> Result<Vec<Board>, String> {
+    let mut openings = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        // EPD operations follow the four position fields; FEN counters may too
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let mut end = fields.len().min(4);
+        while end < fields.len().min(6) && fields[end].parse::<u32>().is_ok() {
+            end += 1;
+        }
+        let board = Board::from_fen(&fields[..end].join(" ")).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        openings.push(board);
+    }
+    Ok(openings)
+}
+
+/// Read an opening file
+pub fn load_openings(path: &Path) -> Result<Vec<Board>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_openings(&text)
+}
+
+/// How a match is played
+#[derive(Clone, Debug)]
+pub struct MatchConfig {
+    /// Total games; openings are reused in order when there are more games than pairs
+    pub games: usize,
+    pub max_plies: usize,
+    /// Stop early once the test is decided
+    pub sprt: Option<Sprt>,
+}
+
+/// Play `first` against `second`: every opening twice, `first` taking White
+/// in the first game of each pair
+/// `on_game` sees each game and the running totals; the final totals are returned
+pub fn run_match(
+    first: &mut dyn Player,
+    second: &mut dyn Player,
+    openings: &[Board],
+    config: &MatchConfig,
+    on_game: &mut dyn FnMut(usize, &GameRecord, &MatchStats),
+) -> MatchStats {
+    let mut stats = MatchStats::default();
+    if openings.is_empty() {
+        return stats;
+    }
+
+    for round in 0..config.games {
+        let opening = &openings[(round / 2) % openings.len()];
+        let first_color = if round % 2 == 0 { Color::White } else { Color::Black };
+        let record = match first_color {
+            Color::White => play_game(first, second, opening, config.max_plies),
+            Color::Black => play_game(second, first, opening, config.max_plies),
+        };
+        stats.record(record.result, first_color);
+        on_game(round + 1, &record, &stats);
+
+        let decided = config.sprt.is_some_and(|sprt| sprt.verdict(stats.llr(&sprt)) != SprtVerdict::Continue);
+        if decided {
+            break;
+        }
+    }
+    stats
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn internal(name: &str, depth: i32) -> InternalPlayer {
+        InternalPlayer::new(name, Box::new(HandCrafted::default()), SearchLimits::depth(depth))
+    }
+
+    /// Always plays its first legal move
+    struct FirstMove;
+
+    impl Player for FirstMove {
+        fn name(&self) -> &str {
+            "first"
+        }
+        fn new_game(&mut self, _start: &Board) -> Result<(), String> {
+            Ok(())
+        }
+        fn choose_move(&mut self, _: &Board, _: &[Move], board: &Board) -> Result<Move, String> {
+            Ok(board.clone().generate_legal_moves()[0])
+        }
+    }
+
+    /// Never manages to move
+    struct Broken;
+
+    impl Player for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+        fn new_game(&mut self, _start: &Board) -> Result<(), String> {
+            Ok(())
+        }
+        fn choose_move(&mut self, _: &Board, _: &[Move], _: &Board) -> Result<Move, String> {
+            Err("crashed".to_string())
+        }
+    }
+
+    #[test]
+    fn test_parse_engine_spec() {
+        let spec = EngineSpec::parse("name=new,depth=6,params=tuned.toml").unwrap();
+        assert_eq!(spec.name.as_deref(), Some("new"));
+        assert_eq!(spec.params.as_deref(), Some("tuned.toml"));
+        assert_eq!(spec.limits().depth, Some(6));
+
+        let spec = EngineSpec::parse("cmd=./sf,movetime=100,option.UCI_Variant=amazon").unwrap();
+        assert_eq!(spec.limits().depth, None);
+        assert_eq!(spec.limits().movetime, Some(100));
+        assert_eq!(spec.options, vec![("UCI_Variant".to_string(), "amazon".to_string())]);
+
+        assert_eq!(EngineSpec::parse("").unwrap().limits().depth, Some(DEFAULT_MATCH_DEPTH));
+        assert!(EngineSpec::parse("depth=deep").is_err());
+        assert!(EngineSpec::parse("colour=blue").is_err());
+        assert!(EngineSpec::parse("cmd=./sf,params=a.toml").is_err());
+    }
+
+    #[test]
+    fn test_parse_openings() {
+        let openings = parse_openings("# comment\n8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1\n\n8/8/4k3/8/8/8/8/3AK3 b - - bm Ad4;\n")
+            .unwrap();
+        assert_eq!(openings.len(), 2);
+        assert_eq!(openings[1].side_to_move(), Color::Black);
+        assert!(parse_openings("not a fen").is_err());
+    }
+
+    #[test]
+    fn test_game_ends_in_mate() {
+        let start = Board::from_fen("k7/8/1K6/8/8/8/8/3A4 w - - 0 1").unwrap();
+        let record = play_game(&mut internal("a", 2), &mut internal("b", 2), &start, DEFAULT_MAX_PLIES);
+        assert_eq!(record.result, GameResult::WhiteWins);
+        assert_eq!(record.termination, Termination::Checkmate);
+        assert_eq!(record.moves.len(), 1);
+
+        let pgn = record.to_pgn("test", 3);
+        assert!(pgn.contains("[Round \"3\"]"));
+        assert!(pgn.contains("[Variant \"amazon\"]"));
+        assert!(pgn.contains("[PlyCount \"1\"]"));
+        assert!(pgn.contains("[Termination \"normal\"]"));
+        assert!(pgn.contains("1. Ad8# {} {White mates} 1-0"));
+    }
+
+    #[test]
+    fn test_adjudication() {
+        let mut stalemate = Board::from_fen("7k/6R1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(adjudicate(&mut stalemate, 0, 10), Some((GameResult::Draw, Termination::Stalemate)));
+        let mut fifty = Board::from_fen("8/8/4k3/4r3/8/8/8/3AK3 w - - 100 80").unwrap();
+        assert_eq!(adjudicate(&mut fifty, 0, 10), Some((GameResult::Draw, Termination::FiftyMoves)));
+        let mut board = Board::setup_amazon_vs_rook();
+        assert_eq!(adjudicate(&mut board, 10, 10), Some((GameResult::Draw, Termination::MaxPlies)));
+        assert_eq!(adjudicate(&mut board, 0, 10), None);
+
+        // Shuffling kings repeat the start position
+        let start = Board::from_fen("k7/8/8/8/8/8/8/7K w - - 0 1").unwrap();
+        let record = play_game(&mut FirstMove, &mut FirstMove, &start, DEFAULT_MAX_PLIES);
+        assert_eq!(record.termination, Termination::Repetition);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_engine_gets_the_variant() {
+        let log = std::env::temp_dir().join(format!("uci_variant_{}.log", std::process::id()));
+        let script = format!(
+            "read l; echo 'id name stub'; echo 'option name UCI_Variant type combo default chess var chess var amazon'; \
+             echo uciok; while read l; do echo \"$l\" >> {}; case $l in isready) echo readyok;; quit) exit 0;; esac; done",
+            log.display()
+        );
+        let spawn = || UciEngine::spawn("sh", &["-c", &script]).unwrap();
+
+        let mut player = ExternalPlayer::with_engine(spawn(), None, &[], SearchLimits::depth(1)).unwrap();
+        let amazon = Board::setup_amazon_vs_rook();
+        player.new_game(&amazon).unwrap();
+        player.new_game(&amazon).unwrap();
+        player.new_game(&Board::from_fen("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1").unwrap()).unwrap();
+        let sent = fs::read_to_string(&log).unwrap();
+        let variants: Vec<&str> = sent.lines().filter(|line| line.contains("UCI_Variant")).collect();
+        assert_eq!(variants, ["setoption name UCI_Variant value amazon", "setoption name UCI_Variant value chess"]);
+
+        // A variant chosen on the command line is left alone
+        fs::remove_file(&log).unwrap();
+        let options = [("UCI_Variant".to_string(), "chess".to_string())];
+        let mut player = ExternalPlayer::with_engine(spawn(), None, &options, SearchLimits::depth(1)).unwrap();
+        player.new_game(&amazon).unwrap();
+        let sent = fs::read_to_string(&log).unwrap();
+        assert_eq!(sent.matches("UCI_Variant").count(), 1);
+        fs::remove_file(&log).unwrap();
+    }
+
+    #[test]
+    fn test_forfeit_loses() {
+        let start = Board::setup_amazon_vs_rook();
+        let record = play_game(&mut FirstMove, &mut Broken, &start, DEFAULT_MAX_PLIES);
+        assert_eq!(record.result, GameResult::WhiteWins);
+        assert_eq!(record.termination, Termination::Forfeit("crashed".to_string()));
+        let pgn = record.to_pgn("test", 1);
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.contains("[Termination \"abandoned\"]"));
+        assert!(pgn.contains("{White wins by forfeit: crashed} 1-0"));
+    }
+
+    #[test]
+    fn test_match_swaps_colours() {
+        let openings = vec![Board::from_fen("k7/8/1K6/8/8/8/8/3A4 w - - 0 1").unwrap()];
+        let config = MatchConfig {
+            games: 2,
+            max_plies: 4,
+            sprt: None,
+        };
+        let mut whites = Vec::new();
+        let stats = run_match(&mut internal("a", 2), &mut internal("b", 2), &openings, &config, &mut |_, record, _| {
+            whites.push(record.white.clone())
+        });
+        assert_eq!(whites, vec!["a", "b"]);
+        assert_eq!(stats, MatchStats { wins: 1, losses: 1, draws: 0 });
+    }
+
+    #[test]
+    fn test_elo_and_error() {
+        let even = MatchStats { wins: 10, losses: 10, draws: 20 };
+        assert!(even.elo().abs() < 1e-9);
+        assert!(even.elo_error() > 0.0);
+        let ahead = MatchStats { wins: 30, losses: 10, draws: 0 };
+        assert!((ahead.elo() - 190.85).abs() < 0.01);
+        // More games, narrower interval
+        let longer = MatchStats { wins: 300, losses: 100, draws: 0 };
+        assert!(longer.elo_error() < ahead.elo_error());
+    }
+
+    #[test]
+    fn test_sprt() {
+        let sprt = Sprt::new(0.0, 10.0);
+        let (lower, upper) = sprt.bounds();
+        assert!((upper - 2.944).abs() < 0.001);
+        assert!((lower + 2.944).abs() < 0.001);
+        assert_eq!(sprt.verdict(0.0), SprtVerdict::Continue);
+
+        let winning = MatchStats { wins: 600, losses: 400, draws: 1000 };
+        assert_eq!(sprt.verdict(winning.llr(&sprt)), SprtVerdict::AcceptH1);
+        let losing = MatchStats { wins: 400, losses: 600, draws: 1000 };
+        assert_eq!(sprt.verdict(losing.llr(&sprt)), SprtVerdict::AcceptH0);
+    }
+}
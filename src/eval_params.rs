@@ -0,0 +1,363 @@
+// Evaluation parameters
+// Every weight of the hand-crafted evaluation, so it can be changed without a
+// rebuild. Parameters are saved as a small TOML subset: one `name = value` line
+// per weight and `name = [ ... ]` with 64 values (a8 first) per piece-square table.
+// Names missing from a file keep their default value.
+
+use crate::board::PieceType;
+use std::fs;
+use std::path::Path;
+
+/// A piece-square table, indexed [row][col] (row 0 = rank 8)
+pub type Pst = [[i32; 8]; 8];
+
+/// Weights of the hand-crafted evaluation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalParams {
+    // Material values
+    pub amazon_value: i32,
+    pub actress_value: i32,
+    pub rook_value: i32,
+    pub queen_value: i32,
+    pub bishop_value: i32,
+    pub knight_value: i32,
+    pub pawn_value: i32,
+
+    // Positional weights
+    pub check_bonus: i32,
+    pub king_proximity_weight: i32,
+    pub amazon_center_bonus: i32,
+    pub actress_center_bonus: i32,
+    pub piece_safety_penalty: i32,
+    /// Multiplier of the enemy king piece-square table
+    pub enemy_king_weight: i32,
+    /// Amazon approaching enemy king
+    pub tropism_weight: i32,
+    /// Per legal move bonus
+    pub mobility_weight: i32,
+    /// King cutting off escape routes
+    pub king_cutoff_bonus: i32,
+    /// Bonus for trapping enemy rook
+    pub rook_trapped_bonus: i32,
+    /// Mating net evaluation
+    pub mating_net_weight: i32,
+
+    /// Enemy King position: higher values = better for the attacker (King pushed to edge/corner)
+    pub enemy_king_pst: Pst,
+    /// Amazon position (center is better)
+    pub amazon_pst: Pst,
+    /// Actress position: like the Amazon, but the Camel leap loses most of its
+    /// squares near the edge, so the outer two rings are worth less
+    pub actress_pst: Pst,
+}
+
+/// The weights the engine was tuned with by hand
+pub const DEFAULT_PARAMS: EvalParams = EvalParams {
+    amazon_value: 1500,  // Very powerful piece (Q + N)
+    actress_value: 1800, // Amazon + Camel (Q + N + C)
+    rook_value: 500,
+    queen_value: 900,
+    bishop_value: 330,
+    knight_value: 320,
+    pawn_value: 100,
+
+    check_bonus: 30,
+    king_proximity_weight: 5,
+    amazon_center_bonus: 20,
+    actress_center_bonus: 20,
+    piece_safety_penalty: 50,
+    enemy_king_weight: 50,
+    tropism_weight: 15,
+    mobility_weight: 3,
+    king_cutoff_bonus: 40,
+    rook_trapped_bonus: 30,
+    mating_net_weight: 25,
+
+    enemy_king_pst: [
+        [4, 3, 3, 3, 3, 3, 3, 4],
+        [3, 2, 2, 2, 2, 2, 2, 3],
+        [3, 2, 1, 1, 1, 1, 2, 3],
+        [3, 2, 1, 0, 0, 1, 2, 3],
+        [3, 2, 1, 0, 0, 1, 2, 3],
+        [3, 2, 1, 1, 1, 1, 2, 3],
+        [3, 2, 2, 2, 2, 2, 2, 3],
+        [4, 3, 3, 3, 3, 3, 3, 4],
+    ],
+    amazon_pst: [
+        [0, 1, 1, 2, 2, 1, 1, 0],
+        [1, 2, 3, 3, 3, 3, 2, 1],
+        [1, 3, 4, 5, 5, 4, 3, 1],
+        [2, 3, 5, 6, 6, 5, 3, 2],
+        [2, 3, 5, 6, 6, 5, 3, 2],
+        [1, 3, 4, 5, 5, 4, 3, 1],
+        [1, 2, 3, 3, 3, 3, 2, 1],
+        [0, 1, 1, 2, 2, 1, 1, 0],
+    ],
+    actress_pst: [
+        [0, 0, 1, 1, 1, 1, 0, 0],
+        [0, 1, 2, 3, 3, 2, 1, 0],
+        [1, 2, 5, 6, 6, 5, 2, 1],
+        [1, 3, 6, 7, 7, 6, 3, 1],
+        [1, 3, 6, 7, 7, 6, 3, 1],
+        [1, 2, 5, 6, 6, 5, 2, 1],
+        [0, 1, 2, 3, 3, 2, 1, 0],
+        [0, 0, 1, 1, 1, 1, 0, 0],
+    ],
+};
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_PARAMS
+    }
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Why a parameter file could not be read
+#[derive(Debug)]
+pub enum ParamsError {
+    Io(std::io::Error),
+    /// Malformed line (line number, description)
+    Syntax(usize, String),
+    /// A name that is not a parameter
+    UnknownParam(String),
+}
+
+impl std::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamsError::Io(e) => write!(f, "{}", e),
+            ParamsError::Syntax(line, message) => write!(f, "line {}: {}", line, message),
+            ParamsError::UnknownParam(name) => write!(f, "unknown parameter '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+impl From<std::io::Error> for ParamsError {
+    fn from(e: std::io::Error) -> Self {
+        ParamsError::Io(e)
+    }
+}
+
+// =============================================================================
+// Parameter Access
+// =============================================================================
+
+/// One named parameter: a single weight or a piece-square table
+pub enum Param<'a> {
+    Weight(&'a mut i32),
+    Table(&'a mut Pst),
+}
+
+impl EvalParams {
+    /// Material value of a piece type (the King has none)
+    pub fn piece_value(&self, piece_type: PieceType) -> i32 {
+        match piece_type {
+            PieceType::Amazon => self.amazon_value,
+            PieceType::Actress => self.actress_value,
+            PieceType::Rook => self.rook_value,
+            PieceType::Queen => self.queen_value,
+            PieceType::Bishop => self.bishop_value,
+            PieceType::Knight => self.knight_value,
+            PieceType::Pawn => self.pawn_value,
+            PieceType::King => 0,
+        }
+    }
+
+    /// Every parameter with its file name, in file order
+    pub fn params_mut(&mut self) -> Vec<(&'static str, Param<'_>)> {
+        vec![
+            ("amazon_value", Param::Weight(&mut self.amazon_value)),
+            ("actress_value", Param::Weight(&mut self.actress_value)),
+            ("rook_value", Param::Weight(&mut self.rook_value)),
+            ("queen_value", Param::Weight(&mut self.queen_value)),
+            ("bishop_value", Param::Weight(&mut self.bishop_value)),
+            ("knight_value", Param::Weight(&mut self.knight_value)),
+            ("pawn_value", Param::Weight(&mut self.pawn_value)),
+            ("check_bonus", Param::Weight(&mut self.check_bonus)),
+            ("king_proximity_weight", Param::Weight(&mut self.king_proximity_weight)),
+            ("amazon_center_bonus", Param::Weight(&mut self.amazon_center_bonus)),
+            ("actress_center_bonus", Param::Weight(&mut self.actress_center_bonus)),
+            ("piece_safety_penalty", Param::Weight(&mut self.piece_safety_penalty)),
+            ("enemy_king_weight", Param::Weight(&mut self.enemy_king_weight)),
+            ("tropism_weight", Param::Weight(&mut self.tropism_weight)),
+            ("mobility_weight", Param::Weight(&mut self.mobility_weight)),
+            ("king_cutoff_bonus", Param::Weight(&mut self.king_cutoff_bonus)),
+            ("rook_trapped_bonus", Param::Weight(&mut self.rook_trapped_bonus)),
+            ("mating_net_weight", Param::Weight(&mut self.mating_net_weight)),
+            ("enemy_king_pst", Param::Table(&mut self.enemy_king_pst)),
+            ("amazon_pst", Param::Table(&mut self.amazon_pst)),
+            ("actress_pst", Param::Table(&mut self.actress_pst)),
+        ]
+    }
+
+    // =========================================================================
+    // Text Format
+    // =========================================================================
+
+    /// Write all parameters in the file format
+    pub fn to_text(&self) -> String {
+        let mut params = self.clone();
+        let mut text = String::from("# Evaluation parameters\n");
+        for (name, param) in params.params_mut() {
+            match param {
+                Param::Weight(value) => text.push_str(&format!("{} = {}\n", name, value)),
+                Param::Table(table) => {
+                    text.push_str(&format!("{} = [\n", name));
+                    for row in table.iter() {
+                        let values: Vec<String> = row.iter().map(|v| format!("{:3}", v)).collect();
+                        text.push_str(&format!("   {},\n", values.join(",")));
+                    }
+                    text.push_str("]\n");
+                }
+            }
+        }
+        text
+    }
+
+    /// Parse the file format; parameters not mentioned keep their default
+    pub fn from_text(text: &str) -> Result<EvalParams, ParamsError> {
+        let mut params = EvalParams::default();
+        let mut lines = text.lines().enumerate().map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("");
+            (i + 1, line.trim())
+        });
+
+        while let Some((number, line)) = lines.next() {
+            if line.is_empty() {
+                continue;
+            }
+            let syntax = |message: &str| ParamsError::Syntax(number, message.to_string());
+            let (name, value) = line.split_once('=').ok_or_else(|| syntax("expected 'name = value'"))?;
+            let (name, mut value) = (name.trim(), value.trim().to_string());
+
+            // Tables may span several lines until the closing bracket
+            if value.starts_with('[') {
+                while !value.contains(']') {
+                    let (_, next) = lines.next().ok_or_else(|| syntax("unterminated table"))?;
+                    value.push(' ');
+                    value.push_str(next);
+                }
+            }
+
+            let mut found = false;
+            for (param_name, param) in params.params_mut() {
+                if param_name != name {
+                    continue;
+                }
+                found = true;
+                match param {
+                    Param::Weight(weight) => {
+                        *weight = value.parse().map_err(|_| syntax("expected an integer"))?;
+                    }
+                    Param::Table(table) => {
+                        *table = parse_table(&value).ok_or_else(|| syntax("expected 64 integers in [ ]"))?;
+                    }
+                }
+            }
+            if !found {
+                return Err(ParamsError::UnknownParam(name.to_string()));
+            }
+        }
+        Ok(params)
+    }
+
+    /// Read a parameter file
+    pub fn load(path: &Path) -> Result<EvalParams, ParamsError> {
+        EvalParams::from_text(&fs::read_to_string(path)?)
+    }
+
+    /// Write a parameter file
+    pub fn save(&self, path: &Path) -> Result<(), ParamsError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
+
+/// Parse "[v1, v2, ..., v64]" into a piece-square table
+fn parse_table(value: &str) -> Option<Pst> {
+    let inner = value.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let values: Vec<i32> = inner
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse().ok())
+        .collect::<Option<_>>()?;
+    if values.len() != 64 {
+        return None;
+    }
+
+    let mut table = [[0; 8]; 8];
+    for (i, value) in values.into_iter().enumerate() {
+        table[i / 8][i % 8] = value;
+    }
+    Some(table)
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut params = EvalParams {
+            tropism_weight: 17,
+            ..EvalParams::default()
+        };
+        params.amazon_pst[3][4] = -9;
+        let parsed = EvalParams::from_text(&params.to_text()).unwrap();
+        assert_eq!(parsed, params);
+    }
+
+    #[test]
+    fn test_missing_params_keep_defaults() {
+        let parsed = EvalParams::from_text("# only one change\nmating_net_weight = 40  # was 25\n").unwrap();
+        assert_eq!(parsed.mating_net_weight, 40);
+        assert_eq!(parsed.amazon_value, DEFAULT_PARAMS.amazon_value);
+        assert_eq!(parsed.enemy_king_pst, DEFAULT_PARAMS.enemy_king_pst);
+    }
+
+    #[test]
+    fn test_single_line_table() {
+        let values = vec!["1"; 64].join(", ");
+        let parsed = EvalParams::from_text(&format!("amazon_pst = [{}]", values)).unwrap();
+        assert_eq!(parsed.amazon_pst, [[1; 8]; 8]);
+    }
+
+    #[test]
+    fn test_bad_files() {
+        assert!(matches!(EvalParams::from_text("amazon_value 1500"), Err(ParamsError::Syntax(1, _))));
+        assert!(matches!(EvalParams::from_text("\nrook_value = many"), Err(ParamsError::Syntax(2, _))));
+        assert!(matches!(EvalParams::from_text("amazon_pst = [1, 2, 3]"), Err(ParamsError::Syntax(1, _))));
+        assert!(matches!(EvalParams::from_text("amazon_pst = [1, 2,\n3"), Err(ParamsError::Syntax(1, _))));
+        assert!(matches!(EvalParams::from_text("dragon_value = 1"), Err(ParamsError::UnknownParam(_))));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("eval_params_{}.toml", std::process::id()));
+        let params = EvalParams {
+            rook_value: 480,
+            ..EvalParams::default()
+        };
+        params.save(&path).unwrap();
+        let loaded = EvalParams::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), params);
+    }
+
+    #[test]
+    fn test_piece_values() {
+        let params = EvalParams::default();
+        assert_eq!(params.piece_value(PieceType::Amazon), 1500);
+        assert_eq!(params.piece_value(PieceType::King), 0);
+    }
+}
@@ -1,94 +1,423 @@
-// Evaluation function for Amazon + K vs R + K endgame
-// Goal: Push enemy King to corner/edge and deliver checkmate
-
-use crate::board::{Board, Color, Square};
-
-// Score constants
-const CHECKMATE_SCORE: i32 = 100_000;
-const CHECK_BONUS: i32 = 50;
-const KING_PROXIMITY_WEIGHT: i32 = 10;
-
-/// Piece-Square Table for enemy King position
-/// Higher values = better for the attacker (King pushed to edge/corner)
-/// Center = 0, Edge = 2-3, Corner = 4
-const ENEMY_KING_PST: [[i32; 8]; 8] = [
-    [4, 3, 3, 3, 3, 3, 3, 4],
-    [3, 2, 2, 2, 2, 2, 2, 3],
-    [3, 2, 1, 1, 1, 1, 2, 3],
-    [3, 2, 1, 0, 0, 1, 2, 3],
-    [3, 2, 1, 0, 0, 1, 2, 3],
-    [3, 2, 1, 1, 1, 1, 2, 3],
-    [3, 2, 2, 2, 2, 2, 2, 3],
-    [4, 3, 3, 3, 3, 3, 3, 4],
-];
-
-/// Evaluate the position from the perspective of the given color
-/// Positive score = good for `for_color`
-/// Negative score = bad for `for_color`
-pub fn evaluate(board: &mut Board, for_color: Color) -> i32 {
-    let enemy_color = for_color.opposite();
+// Static evaluation
+// The search scores leaf positions through the `Evaluator` trait; `HandCrafted`
+// is the default implementation, tuned for pushing a lone king into a corner
+// with the Amazon and Actress.
+
+use crate::board::{Board, Color, PieceType, Square};
+use crate::eval_params::{EvalParams, DEFAULT_PARAMS};
+use crate::search::CHECKMATE_SCORE;
+
+// =============================================================================
+// Evaluator Interface
+// =============================================================================
+
+/// Static evaluation used by the search
+/// Scores are from the side to move's perspective; a position that is already
+/// mate scores ±CHECKMATE_SCORE (the search makes it ply-relative)
+pub trait Evaluator: Send + Sync {
+    fn evaluate(&self, board: &mut Board) -> i32;
+
+    /// Called on the root position before a search; evaluators that keep
+    /// incremental state on the board set it up here
+    fn prepare(&self, _board: &mut Board) {}
 
-    // 1. Terminal state detection
-    // Check if enemy is checkmated (we win!)
-    if board.is_checkmate(enemy_color) {
-        return CHECKMATE_SCORE;
+    /// Called on the root position after a search; removes what `prepare` set up
+    fn finish(&self, _board: &mut Board) {}
+}
+
+/// The hand-crafted evaluation: material, piece-square tables and mating-net terms
+#[derive(Clone, Debug, Default)]
+pub struct HandCrafted {
+    params: EvalParams,
+}
+
+impl Evaluator for HandCrafted {
+    fn evaluate(&self, board: &mut Board) -> i32 {
+        let side = board.side_to_move();
+        self.evaluate_for(board, side)
     }
+}
 
-    // Check if enemy is stalemated (draw - avoid this!)
-    if board.is_stalemate(enemy_color) {
-        return 0;
+// =============================================================================
+// Hand-Crafted Evaluation
+// =============================================================================
+
+impl HandCrafted {
+    /// Hand-crafted evaluation with the given weights
+    pub fn new(params: EvalParams) -> Self {
+        HandCrafted { params }
+    }
+
+    /// The weights in use
+    pub fn params(&self) -> &EvalParams {
+        &self.params
     }
 
-    // Check if we are checkmated (we lose!)
-    if board.is_checkmate(for_color) {
-        return -CHECKMATE_SCORE;
+    /// Evaluate the position from the perspective of `for_color`
+    /// Positive score = good for `for_color`
+    pub fn evaluate_for(&self, board: &mut Board, for_color: Color) -> i32 {
+        let params = &self.params;
+        let enemy_color = for_color.opposite();
+
+        // 1. Terminal state detection
+        if board.is_checkmate(enemy_color) {
+            return CHECKMATE_SCORE;
+        }
+        if board.is_stalemate(enemy_color) {
+            return 0; // Draw - avoid this!
+        }
+        if board.is_checkmate(for_color) {
+            return -CHECKMATE_SCORE;
+        }
+        if board.is_stalemate(for_color) {
+            return 0;
+        }
+
+        let mut score = 0;
+
+        // 2. Material evaluation (MOST IMPORTANT!)
+        score += evaluate_material(params, board, for_color);
+
+        // 3. Piece safety - penalize pieces under attack
+        score += evaluate_piece_safety(params, board, for_color);
+
+        // 4. Amazon and Actress position (center is better)
+        score += evaluate_amazon_position(params, board, for_color);
+        score += evaluate_actress_position(params, board, for_color);
+
+        // 5. Enemy King position (pushed to edge/corner is good)
+        if let Some(enemy_king_sq) = board.find_king(enemy_color) {
+            score += evaluate_enemy_king_position(params, enemy_king_sq);
+        }
+
+        // 6. Check bonus (smaller now since material is more important)
+        if board.is_in_check(enemy_color) {
+            score += params.check_bonus;
+        }
+
+        // 7. King proximity (for endgame)
+        if let (Some(our_king_sq), Some(enemy_king_sq)) =
+            (board.find_king(for_color), board.find_king(enemy_color))
+        {
+            score += evaluate_king_proximity(params, our_king_sq, enemy_king_sq);
+        }
+
+        // 8. Amazon Tropism - Amazon closer to enemy king
+        score += evaluate_amazon_tropism(params, board, for_color);
+
+        // 9. Mobility - more legal moves is better
+        score += evaluate_mobility(params, board, for_color);
+
+        // 10. King Cut-off - cutting enemy king's escape routes
+        score += evaluate_king_cutoff(params, board, for_color);
+
+        // 11. Rook Activity - penalize active enemy rook
+        score += evaluate_rook_activity(params, board, for_color);
+
+        // 12. Mating Distance - how close to checkmate position
+        score += evaluate_mating_distance(params, board, for_color);
+
+        score
     }
+}
 
-    // Check if we are stalemated (draw)
-    if board.is_stalemate(for_color) {
-        return 0;
+/// Evaluate material balance
+fn evaluate_material(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let mut our_material = 0;
+    let mut enemy_material = 0;
+
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                let value = match piece.piece_type {
+                    PieceType::King => 0, // King has no material value
+                    piece_type => params.piece_value(piece_type),
+                };
+                if piece.color == for_color {
+                    our_material += value;
+                } else {
+                    enemy_material += value;
+                }
+            }
+        }
     }
 
-    let mut score = 0;
+    our_material - enemy_material
+}
+
+/// Evaluate piece safety - penalize pieces that are attacked
+fn evaluate_piece_safety(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let mut penalty = 0;
+    let enemy_color = for_color.opposite();
 
-    // 2. Enemy King position (pushed to edge/corner is good)
-    if let Some(enemy_king_sq) = board.find_king(enemy_color) {
-        score += evaluate_enemy_king_position(enemy_king_sq);
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                if piece.color == for_color && piece.piece_type != PieceType::King {
+                    let square = (row, col);
+                    // If our piece is attacked, apply penalty
+                    if board.is_square_attacked(square, enemy_color) {
+                        // Penalty based on piece value
+                        penalty -= params.piece_value(piece.piece_type) / 10 + params.piece_safety_penalty;
+                    }
+                }
+            }
+        }
     }
 
-    // 3. Check bonus (giving check is good - keeps pressure)
-    if board.is_in_check(enemy_color) {
-        score += CHECK_BONUS;
+    penalty
+}
+
+/// Evaluate Amazon position using PST
+fn evaluate_amazon_position(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let mut score = 0;
+
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                if piece.piece_type == PieceType::Amazon {
+                    let pst_value = params.amazon_pst[row as usize][col as usize] * params.amazon_center_bonus;
+                    if piece.color == for_color {
+                        score += pst_value;
+                    } else {
+                        score -= pst_value;
+                    }
+                }
+            }
+        }
     }
 
-    // 4. King proximity (our King closer to enemy King helps with mating)
-    if let (Some(our_king_sq), Some(enemy_king_sq)) =
-        (board.find_king(for_color), board.find_king(enemy_color))
-    {
-        score += evaluate_king_proximity(our_king_sq, enemy_king_sq);
+    score
+}
+
+/// Evaluate Actress position using PST
+fn evaluate_actress_position(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let mut score = 0;
+
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                if piece.piece_type == PieceType::Actress {
+                    let pst_value = params.actress_pst[row as usize][col as usize] * params.actress_center_bonus;
+                    if piece.color == for_color {
+                        score += pst_value;
+                    } else {
+                        score -= pst_value;
+                    }
+                }
+            }
+        }
     }
 
     score
 }
 
-/// Evaluate enemy King position using Piece-Square Table
-/// Higher score = King is pushed toward edge/corner (good for attacker)
-fn evaluate_enemy_king_position(square: Square) -> i32 {
+fn evaluate_enemy_king_position(params: &EvalParams, square: Square) -> i32 {
     let (row, col) = square;
-    ENEMY_KING_PST[row as usize][col as usize] * 100 // Scale for significance
+    params.enemy_king_pst[row as usize][col as usize] * params.enemy_king_weight
 }
 
-/// Evaluate King proximity
-/// Closer Kings = better for the attacker (helps with mating net)
-fn evaluate_king_proximity(our_king: Square, enemy_king: Square) -> i32 {
+fn evaluate_king_proximity(params: &EvalParams, our_king: Square, enemy_king: Square) -> i32 {
     let row_diff = (our_king.0 as i32 - enemy_king.0 as i32).abs();
     let col_diff = (our_king.1 as i32 - enemy_king.1 as i32).abs();
-
-    // Chebyshev distance (max of row/col difference)
     let distance = row_diff.max(col_diff);
+    (7 - distance) * params.king_proximity_weight
+}
+
+/// Find Amazon position for a given color
+fn find_amazon(board: &Board, color: Color) -> Option<Square> {
+    for row in 0..8u8 {
+        for col in 0..8u8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                if piece.piece_type == PieceType::Amazon && piece.color == color {
+                    return Some((row, col));
+                }
+            }
+        }
+    }
+    None
+}
 
-    // Closer is better: invert distance (max distance is 7, so 7 - distance)
-    (7 - distance) * KING_PROXIMITY_WEIGHT
+/// Find Rook position for a given color
+fn find_rook(board: &Board, color: Color) -> Option<Square> {
+    for row in 0..8u8 {
+        for col in 0..8u8 {
+            if let Some(piece) = board.get_piece((row, col)) {
+                if piece.piece_type == PieceType::Rook && piece.color == color {
+                    return Some((row, col));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Evaluate Amazon Tropism - Amazon closer to enemy king is better
+fn evaluate_amazon_tropism(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let enemy_color = for_color.opposite();
+
+    let amazon_sq = find_amazon(board, for_color);
+    let enemy_king_sq = board.find_king(enemy_color);
+
+    if let (Some(amazon), Some(king)) = (amazon_sq, enemy_king_sq) {
+        // Chebyshev distance (max of row/col difference)
+        let row_diff = (amazon.0 as i32 - king.0 as i32).abs();
+        let col_diff = (amazon.1 as i32 - king.1 as i32).abs();
+        let distance = row_diff.max(col_diff);
+
+        // Closer = higher score (max distance is 7, so 7 - distance gives 0-7)
+        return (7 - distance) * params.tropism_weight;
+    }
+
+    0
+}
+
+/// Evaluate Mobility - more legal moves is better
+fn evaluate_mobility(params: &EvalParams, board: &mut Board, for_color: Color) -> i32 {
+    let current_side = board.side_to_move();
+
+    // If it's our turn, count our moves
+    if current_side == for_color {
+        let our_moves = board.generate_legal_moves().len() as i32;
+        return our_moves * params.mobility_weight;
+    }
+
+    // Otherwise, we need to temporarily switch sides to count
+    // But this is expensive, so we'll just use 0 for now
+    0
+}
+
+/// Evaluate King Cut-off - our king cutting off enemy king's escape routes
+fn evaluate_king_cutoff(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let enemy_color = for_color.opposite();
+
+    let our_king_sq = board.find_king(for_color);
+    let enemy_king_sq = board.find_king(enemy_color);
+
+    if let (Some(our_king), Some(enemy_king)) = (our_king_sq, enemy_king_sq) {
+        let mut bonus = 0;
+
+        // Check if our king cuts off the enemy king on the same file
+        if our_king.1 == enemy_king.1 {
+            // Same file - check if we're between enemy king and center/other side
+            let our_dist_to_edge = our_king.0.min(7 - our_king.0);
+            let enemy_dist_to_edge = enemy_king.0.min(7 - enemy_king.0);
+            if our_dist_to_edge > enemy_dist_to_edge {
+                bonus += params.king_cutoff_bonus;
+            }
+        }
+
+        // Check if our king cuts off the enemy king on the same rank
+        if our_king.0 == enemy_king.0 {
+            // Same rank - check if we're between enemy king and center/other side
+            let our_dist_to_edge = our_king.1.min(7 - our_king.1);
+            let enemy_dist_to_edge = enemy_king.1.min(7 - enemy_king.1);
+            if our_dist_to_edge > enemy_dist_to_edge {
+                bonus += params.king_cutoff_bonus;
+            }
+        }
+
+        // Bonus if kings are close (opposition can be useful)
+        let row_diff = (our_king.0 as i32 - enemy_king.0 as i32).abs();
+        let col_diff = (our_king.1 as i32 - enemy_king.1 as i32).abs();
+        if row_diff <= 2 && col_diff <= 2 {
+            bonus += params.king_cutoff_bonus / 2;
+        }
+
+        return bonus;
+    }
+
+    0
+}
+
+/// Evaluate Rook Activity - penalize enemy rook that has many moves
+fn evaluate_rook_activity(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let enemy_color = for_color.opposite();
+
+    let enemy_rook_sq = find_rook(board, enemy_color);
+
+    if let Some(rook) = enemy_rook_sq {
+        // Count how many squares the rook can move to (simplified)
+        let mut rook_mobility = 0;
+
+        // Check horizontal moves
+        for col in 0..8u8 {
+            if col != rook.1 {
+                let sq = (rook.0, col);
+                if board.get_piece(sq).is_none() {
+                    rook_mobility += 1;
+                } else {
+                    break; // Blocked
+                }
+            }
+        }
+
+        // Check vertical moves
+        for row in 0..8u8 {
+            if row != rook.0 {
+                let sq = (row, rook.1);
+                if board.get_piece(sq).is_none() {
+                    rook_mobility += 1;
+                } else {
+                    break; // Blocked
+                }
+            }
+        }
+
+        // Less mobility for enemy rook = better for us
+        // Max rook mobility is 14 (7 + 7)
+        return (14 - rook_mobility) * (params.rook_trapped_bonus / 7);
+    }
+
+    0
+}
+
+/// Evaluate Mating Distance - how close are we to a mating position
+fn evaluate_mating_distance(params: &EvalParams, board: &Board, for_color: Color) -> i32 {
+    let enemy_color = for_color.opposite();
+
+    let our_king_sq = board.find_king(for_color);
+    let enemy_king_sq = board.find_king(enemy_color);
+    let amazon_sq = find_amazon(board, for_color);
+
+    if let (Some(our_king), Some(enemy_king), Some(amazon)) =
+        (our_king_sq, enemy_king_sq, amazon_sq)
+    {
+        // Distance of enemy king to nearest corner
+        let corner_dist = [
+            enemy_king.0.max(enemy_king.1),                    // Distance to (0,0)
+            enemy_king.0.max(7 - enemy_king.1),                // Distance to (0,7)
+            (7 - enemy_king.0).max(enemy_king.1),              // Distance to (7,0)
+            (7 - enemy_king.0).max(7 - enemy_king.1),          // Distance to (7,7)
+        ]
+        .into_iter()
+        .min()
+        .unwrap_or(7) as i32;
+
+        // Distance from our pieces to enemy king
+        let amazon_dist = (amazon.0 as i32 - enemy_king.0 as i32)
+            .abs()
+            .max((amazon.1 as i32 - enemy_king.1 as i32).abs());
+        let our_king_dist = (our_king.0 as i32 - enemy_king.0 as i32)
+            .abs()
+            .max((our_king.1 as i32 - enemy_king.1 as i32).abs());
+
+        // Score: enemy king close to corner + our pieces close to enemy king
+        let corner_score = (7 - corner_dist) * 2;
+        let approach_score = 14 - amazon_dist - our_king_dist;
+
+        return (corner_score + approach_score) * params.mating_net_weight / 10;
+    }
+
+    0
+}
+
+/// Nominal piece value for capture ordering (the King outranks everything)
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::King => 10000,
+        piece_type => DEFAULT_PARAMS.piece_value(piece_type),
+    }
 }
 
 // =============================================================================
@@ -110,11 +439,11 @@ mod tests {
         board.set_piece((2, 1), Some(Piece::new(PieceType::Amazon, Color::White)));
 
         // From White's perspective, this is checkmate (max score)
-        let score = evaluate(&mut board, Color::White);
+        let score = HandCrafted::default().evaluate_for(&mut board, Color::White);
         assert_eq!(score, CHECKMATE_SCORE);
 
         // From Black's perspective, this is being checkmated (negative max score)
-        let score_black = evaluate(&mut board, Color::Black);
+        let score_black = HandCrafted::default().evaluate_for(&mut board, Color::Black);
         assert_eq!(score_black, -CHECKMATE_SCORE);
     }
 
@@ -128,7 +457,7 @@ mod tests {
         board.set_piece((2, 4), Some(Piece::new(PieceType::Amazon, Color::White))); // e6
 
         // Stalemate = draw = 0
-        let score = evaluate(&mut board, Color::White);
+        let score = HandCrafted::default().evaluate_for(&mut board, Color::White);
         assert_eq!(score, 0);
     }
 
@@ -147,8 +476,8 @@ mod tests {
         board_center.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
         board_center.set_piece((7, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
 
-        let score_corner = evaluate(&mut board_corner, Color::White);
-        let score_center = evaluate(&mut board_center, Color::White);
+        let score_corner = HandCrafted::default().evaluate_for(&mut board_corner, Color::White);
+        let score_center = HandCrafted::default().evaluate_for(&mut board_center, Color::White);
 
         // King in corner should be higher score for White
         assert!(
@@ -174,8 +503,8 @@ mod tests {
         board_no_check.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
         board_no_check.set_piece((7, 3), Some(Piece::new(PieceType::Amazon, Color::White))); // Not attacking
 
-        let score_check = evaluate(&mut board_check, Color::White);
-        let score_no_check = evaluate(&mut board_no_check, Color::White);
+        let score_check = HandCrafted::default().evaluate_for(&mut board_check, Color::White);
+        let score_no_check = HandCrafted::default().evaluate_for(&mut board_no_check, Color::White);
 
         // Check position should have higher score
         assert!(
@@ -201,8 +530,8 @@ mod tests {
         board_far.set_piece((7, 7), Some(Piece::new(PieceType::King, Color::White))); // Far
         board_far.set_piece((7, 6), Some(Piece::new(PieceType::Amazon, Color::White)));
 
-        let score_close = evaluate(&mut board_close, Color::White);
-        let score_far = evaluate(&mut board_far, Color::White);
+        let score_close = HandCrafted::default().evaluate_for(&mut board_close, Color::White);
+        let score_far = HandCrafted::default().evaluate_for(&mut board_far, Color::White);
 
         // Closer Kings should give higher score
         assert!(
@@ -212,4 +541,44 @@ mod tests {
             score_far
         );
     }
+
+    #[test]
+    fn test_side_to_move_prefers_corner() {
+        let mut board_corner = Board::new();
+        let mut board_center = Board::new();
+
+        // Black king in corner
+        board_corner.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::Black)));
+        board_corner.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
+        board_corner.set_piece((7, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
+        board_corner.set_side_to_move(Color::White);
+
+        // Black king in center
+        board_center.set_piece((4, 4), Some(Piece::new(PieceType::King, Color::Black)));
+        board_center.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
+        board_center.set_piece((7, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
+        board_center.set_side_to_move(Color::White);
+
+        let score_corner = HandCrafted::default().evaluate(&mut board_corner);
+        let score_center = HandCrafted::default().evaluate(&mut board_center);
+
+        assert!(
+            score_corner > score_center,
+            "Corner ({}) should be better than center ({})",
+            score_corner,
+            score_center
+        );
+    }
+
+    #[test]
+    fn test_evaluator_uses_side_to_move() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let white = HandCrafted::default().evaluate_for(&mut board, Color::White);
+        assert_eq!(HandCrafted::default().evaluate(&mut board), white);
+
+        board.set_side_to_move(Color::Black);
+        let black = HandCrafted::default().evaluate_for(&mut board, Color::Black);
+        assert_eq!(HandCrafted::default().evaluate(&mut board), black);
+        assert!(white > 0 && black < 0, "white {}, black {}", white, black);
+    }
 }
@@ -1,7 +1,22 @@
 // Chess Engine Library
 // Board representation using Mailbox (8x8 array) approach
 
+pub mod analysis;
+pub mod bitboard;
 pub mod board;
+pub mod engine_match;
+pub mod eval_params;
+pub mod evaluation;
+pub mod nnue;
+pub mod perft;
+pub mod pgn;
 pub mod pieces;
 pub mod search;
-pub mod uci;
\ No newline at end of file
+pub mod tablebase;
+pub mod time_manager;
+pub mod transposition;
+pub mod tuner;
+pub mod uci;
+pub mod uci_client;
+pub mod variant;
+pub mod zobrist;
@@ -1,58 +1,456 @@
-use rust_chess_engine::board::{Board, Color, Square};
-use rust_chess_engine::search::find_best_move;
-use rust_chess_engine::uci::uci_loop;
+use rust_chess_engine::analysis::{
+    amazon_side, analyze_game, annotate, slipped_wins, summarize, DEFAULT_ANALYSIS_DEPTH,
+};
+use rust_chess_engine::board::{Board, Color, Move};
+use rust_chess_engine::engine_match::{
+    load_openings, run_match, EngineSpec, MatchConfig, Sprt, SprtVerdict, DEFAULT_MAX_PLIES,
+};
+use rust_chess_engine::eval_params::EvalParams;
+use rust_chess_engine::evaluation::HandCrafted;
+use rust_chess_engine::perft::divide;
+use rust_chess_engine::pgn::{load_pgn, score_comment, PgnGame};
+use rust_chess_engine::search::{search, SearchLimits, SearchSignals};
+use rust_chess_engine::tablebase::{FairyTablebase, Material, FILE_EXTENSION};
+use rust_chess_engine::transposition::TranspositionTable;
+use rust_chess_engine::tuner::{
+    changed_weights, fit_k, load_epd, positions_from_games, prepare_positions, tune,
+};
+use rust_chess_engine::uci::{format_divide, move_to_uci, parse_uci_move, uci_loop};
+use rust_chess_engine::variant::variant_of;
 use std::env;
+use std::fs::OpenOptions;
 use std::io::{self, Write};
+use std::path::Path;
+use std::time::Instant;
 
-/// Parse algebraic notation (e.g., "e2e4") to (from, to) squares
-fn parse_move(input: &str) -> Option<(Square, Square)> {
-    let input = input.trim().to_lowercase();
-    if input.len() != 4 {
-        return None;
+/// Print game instructions
+fn print_help() {
+    println!("Commands:");
+    println!("  <move>  - Enter move in format: e2e4 (from-to), e7e8q (promotion)");
+    println!("  auto    - Let the engine play for current side");
+    println!("  play    - Auto-play: engine vs engine until game ends");
+    println!("  undo    - Undo last move");
+    println!("  moves   - Show all legal moves");
+    println!("  help    - Show this help");
+    println!("  quit    - Exit the game");
+    println!();
+}
+
+/// Run perft from the command line: `perft <depth> [fen]`
+/// Prints the node count per root move (divide) and the total
+fn run_perft(args: &[String]) {
+    let depth = match args.first().and_then(|d| d.parse::<u32>().ok()) {
+        Some(d) => d,
+        None => {
+            eprintln!("Usage: perft <depth> [fen]");
+            return;
+        }
+    };
+
+    let mut board = if args.len() > 1 {
+        let fen = args[1..].join(" ");
+        match Board::from_fen(&fen) {
+            Ok(board) => board,
+            Err(e) => {
+                eprintln!("Invalid FEN: {}", e);
+                return;
+            }
+        }
+    } else {
+        Board::setup_amazon_vs_rook()
+    };
+
+    let start = Instant::now();
+    let split = divide(&mut board, depth);
+    let elapsed = start.elapsed();
+
+    println!("{}", format_divide(&split));
+    println!("Time: {} ms", elapsed.as_millis());
+}
+
+/// Build endgame tables from the command line: `tablebase <dir> <material>...`
+/// Tables already in the directory are reused; missing sub-tables are built as well
+fn run_tablebase(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!("Usage: tablebase <dir> <material>...  (e.g. tablebase tb KAvKR)");
+        return;
     }
 
-    let chars: Vec<char> = input.chars().collect();
+    let dir = Path::new(&args[0]);
+    let mut tablebase = if dir.is_dir() {
+        match FairyTablebase::load_dir(dir) {
+            Ok(tablebase) => tablebase,
+            Err(e) => {
+                eprintln!("Cannot load {}: {}", dir.display(), e);
+                return;
+            }
+        }
+    } else if let Err(e) = std::fs::create_dir_all(dir) {
+        eprintln!("Cannot create {}: {}", dir.display(), e);
+        return;
+    } else {
+        FairyTablebase::new()
+    };
 
-    // Parse "from" square (e.g., "e2")
-    let from_col = (chars[0] as u8).checked_sub(b'a')?;
-    let from_row = (b'8').checked_sub(chars[1] as u8)?;
+    for name in &args[1..] {
+        let start = Instant::now();
+        let built = match Material::parse(name).and_then(|material| tablebase.generate(&material)) {
+            Ok(built) => built,
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                return;
+            }
+        };
+        if built.is_empty() {
+            println!("{}: already built", name);
+        }
 
-    // Parse "to" square (e.g., "e4")
-    let to_col = (chars[2] as u8).checked_sub(b'a')?;
-    let to_row = (b'8').checked_sub(chars[3] as u8)?;
+        for material in built {
+            let table = tablebase.get(&material).expect("Table just built");
+            let path = dir.join(format!("{}.{}", material.name(), FILE_EXTENSION));
+            if let Err(e) = table.write(&path) {
+                eprintln!("Cannot write {}: {}", path.display(), e);
+                return;
+            }
+            match table.longest_mate() {
+                Some(plies) => println!("{}: longest mate {} plies", material.name(), plies),
+                None => println!("{}: no wins", material.name()),
+            }
+        }
+        println!("Time: {} ms", start.elapsed().as_millis());
+    }
+}
 
-    if from_col > 7 || from_row > 7 || to_col > 7 || to_row > 7 {
-        return None;
+/// Fit the evaluation weights to game results:
+/// `tune <positions.epd|games.pgn> [--out <file>] [--passes <n>] [--eval-params <start>]`
+fn run_tune(args: &[String], params: EvalParams) {
+    let path = match args.first().filter(|arg| !arg.starts_with("--")) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: tune <positions.epd|games.pgn> [--out <file>] [--passes <n>] [--eval-params <start>]");
+            return;
+        }
+    };
+    let out = flag_value(args, "--out", 1).map_or("tuned_params.toml", String::as_str);
+    let passes = match flag_number(args, "--passes", 1) {
+        Ok(passes) => passes.unwrap_or(100),
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    // Games are turned into positions labelled with their results
+    let loaded = if path.ends_with(".pgn") {
+        load_pgn(Path::new(path)).map(|games| positions_from_games(&games)).map_err(|e| e.to_string())
+    } else {
+        load_epd(Path::new(path)).map_err(|e| e.to_string())
+    };
+    let mut positions = match loaded {
+        Ok(positions) => positions,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return;
+        }
+    };
+    let loaded = positions.len();
+    prepare_positions(&mut positions, &params);
+    println!("{} positions ({} quiet, non-terminal)", loaded, positions.len());
+    if positions.is_empty() {
+        return;
     }
 
-    Some(((from_row, from_col), (to_row, to_col)))
+    let start = Instant::now();
+    let k = fit_k(&mut positions, &params);
+    println!("K = {:.4}", k);
+
+    let tuned = tune(&mut positions, params.clone(), k, passes, &mut |pass, error| {
+        println!("Pass {}: error {:.6} ({} s)", pass, error, start.elapsed().as_secs());
+    });
+    println!("Changed: {}", changed_weights(&params, &tuned).join(", "));
+
+    match tuned.save(Path::new(out)) {
+        Ok(()) => println!("Wrote {}", out),
+        Err(e) => eprintln!("Cannot write {}: {}", out, e),
+    }
 }
 
-/// Convert a square to algebraic notation (e.g., (7, 4) -> "e1")
-fn square_to_notation(square: Square) -> String {
-    let col = (b'a' + square.1) as char;
-    let row = (b'8' - square.0) as char;
-    format!("{}{}", col, row)
+/// The argument `offset` places after `flag`, if the flag is present
+fn flag_value<'a>(args: &'a [String], flag: &str, offset: usize) -> Option<&'a String> {
+    args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + offset))
 }
 
-/// Print game instructions
-fn print_help() {
-    println!("Commands:");
-    println!("  <move>  - Enter move in format: e2e4 (from-to)");
-    println!("  auto    - Let the engine play for current side");
-    println!("  play    - Auto-play: engine vs engine until game ends");
-    println!("  undo    - Undo last move");
-    println!("  moves   - Show all legal moves");
-    println!("  help    - Show this help");
-    println!("  quit    - Exit the game");
+/// A flag's numeric argument, if the flag is present
+fn flag_number<T: std::str::FromStr>(args: &[String], flag: &str, offset: usize) -> Result<Option<T>, String> {
+    flag_value(args, flag, offset)
+        .map(|value| value.parse::<T>().map_err(|_| format!("{} needs a number", flag)))
+        .transpose()
+}
+
+/// Engines, openings and settings of a `match` command
+fn match_settings(args: &[String]) -> Result<(EngineSpec, EngineSpec, Vec<Board>, MatchConfig), String> {
+    let first = EngineSpec::parse(&args[0])?;
+    let second = EngineSpec::parse(&args[1])?;
+    let openings = match flag_value(args, "--openings", 1) {
+        Some(path) => load_openings(Path::new(path))?,
+        None => vec![Board::setup_amazon_vs_rook()],
+    };
+    let sprt = match (flag_number(args, "--sprt", 1)?, flag_number(args, "--sprt", 2)?) {
+        (Some(elo0), Some(elo1)) => Some(Sprt {
+            alpha: flag_number(args, "--alpha", 1)?.unwrap_or(0.05),
+            beta: flag_number(args, "--beta", 1)?.unwrap_or(0.05),
+            ..Sprt::new(elo0, elo1)
+        }),
+        (None, None) => None,
+        _ => return Err("--sprt needs <elo0> <elo1>".to_string()),
+    };
+    let config = MatchConfig {
+        games: flag_number(args, "--games", 1)?.unwrap_or(2 * openings.len()),
+        max_plies: flag_number(args, "--max-plies", 1)?.unwrap_or(DEFAULT_MAX_PLIES),
+        sprt,
+    };
+    Ok((first, second, openings, config))
+}
+
+/// Play two engine configurations against each other:
+/// `match <engine1> <engine2> [--openings <file>] [--games <n>] [--pgn <file>]
+///        [--sprt <elo0> <elo1>] [--alpha <a>] [--beta <b>] [--max-plies <n>]`
+/// Engines are `key=value` lists, e.g. `depth=6,params=new.toml` or `cmd=./engine,movetime=100`
+fn run_engine_match(args: &[String]) {
+    const USAGE: &str = "Usage: match <engine1> <engine2> [--openings <file>] [--games <n>] [--pgn <file>] \
+                         [--sprt <elo0> <elo1>] [--alpha <a>] [--beta <b>] [--max-plies <n>]";
+    if args.len() < 2 || args[0].starts_with("--") || args[1].starts_with("--") {
+        eprintln!("{}", USAGE);
+        return;
+    }
+    let (first, second, openings, config) = match match_settings(args) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", USAGE);
+            return;
+        }
+    };
+    if openings.is_empty() {
+        eprintln!("No openings");
+        return;
+    }
+
+    let players = first.build("engine1").and_then(|a| second.build("engine2").map(|b| (a, b)));
+    let (mut first, mut second) = match players {
+        Ok(players) => players,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let mut pgn = match flag_value(args, "--pgn", 1).map(std::fs::File::create).transpose() {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Cannot create PGN file: {}", e);
+            return;
+        }
+    };
+
+    println!("{} vs {}: {} games, {} openings", first.name(), second.name(), config.games, openings.len());
+    let event = format!("{} vs {}", first.name(), second.name());
+    let stats = run_match(first.as_mut(), second.as_mut(), &openings, &config, &mut |round, record, stats| {
+        println!(
+            "Game {}: {} - {} {} ({})  +{} -{} ={}",
+            round,
+            record.white,
+            record.black,
+            record.result.to_pgn(),
+            record.termination,
+            stats.wins,
+            stats.losses,
+            stats.draws
+        );
+        if let Some(file) = pgn.as_mut() {
+            if let Err(e) = file.write_all(record.to_pgn(&event, round).as_bytes()) {
+                eprintln!("Cannot write PGN: {}", e);
+            }
+        }
+    });
+
+    println!();
+    println!("Score: +{} -{} ={} ({:.1}%)", stats.wins, stats.losses, stats.draws, 100.0 * stats.score());
+    println!("Elo: {:.1} +/- {:.1}", stats.elo(), stats.elo_error());
+    if let Some(sprt) = config.sprt {
+        let llr = stats.llr(&sprt);
+        let (lower, upper) = sprt.bounds();
+        let verdict = match sprt.verdict(llr) {
+            SprtVerdict::AcceptH1 => "H1 accepted",
+            SprtVerdict::AcceptH0 => "H0 accepted",
+            SprtVerdict::Continue => "inconclusive",
+        };
+        println!(
+            "SPRT [{}, {}]: LLR {:.2} ({:.2}, {:.2}) - {}",
+            sprt.elo0, sprt.elo1, llr, lower, upper, verdict
+        );
+    }
+}
+
+/// Engine move for the interactive game: the move, its score and a PGN
+/// comment with score, depth and time
+fn engine_move(
+    board: &mut Board,
+    depth: i32,
+    tt: &mut TranspositionTable,
+    evaluator: &HandCrafted,
+) -> Option<(Move, i32, String)> {
+    let signals = SearchSignals::default();
+    let info = search(board, &SearchLimits::depth(depth), tt, evaluator, None, &signals, &mut |_| {})?;
+    let comment = score_comment(info.score, info.depth, info.time);
+    Some((info.best_move(), info.score, comment))
+}
+
+/// Append the interactive game to `path` as PGN, with its result if it ended
+fn save_game(path: &str, game: &mut PgnGame, board: &mut Board, move_limit: bool) {
+    let side = board.side_to_move();
+    let winner = match side.opposite() {
+        Color::White => ("1-0", "White mates"),
+        Color::Black => ("0-1", "Black mates"),
+    };
+    if board.is_checkmate(side) {
+        game.finish(winner.0, "normal", Some(winner.1));
+    } else if board.is_stalemate(side) {
+        game.finish("1/2-1/2", "normal", Some("Draw by stalemate"));
+    } else if move_limit {
+        game.finish("1/2-1/2", "adjudication", Some("Draw by move limit"));
+    } else {
+        game.finish("*", "unterminated", None);
+    }
+
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(game.to_pgn().as_bytes()));
+    match written {
+        Ok(()) => println!("Game saved to {}", path),
+        Err(e) => eprintln!("Cannot write {}: {}", path, e),
+    }
+}
+
+/// Annotate the games of a PGN file with engine evaluations:
+/// `analyze <games.pgn> [--depth <n>] [--movetime <ms>] [--out <file>] [--eval-params <file>]`
+/// Prints each game's judgements and where the Amazon side let the win slip
+fn run_analyze(args: &[String], params: EvalParams) {
+    const USAGE: &str =
+        "Usage: analyze <games.pgn> [--depth <n>] [--movetime <ms>] [--out <file>] [--eval-params <file>]";
+    let path = match args.first().filter(|arg| !arg.starts_with("--")) {
+        Some(path) => path,
+        None => {
+            eprintln!("{}", USAGE);
+            return;
+        }
+    };
+    let (depth, movetime) = match (flag_number(args, "--depth", 1), flag_number(args, "--movetime", 1)) {
+        (Ok(depth), Ok(movetime)) => (depth, movetime),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return;
+        }
+    };
+    let limits = SearchLimits {
+        depth: depth.or(movetime.map_or(Some(DEFAULT_ANALYSIS_DEPTH), |_| None)),
+        movetime,
+        ..SearchLimits::default()
+    };
+    let annotator = match (limits.depth, movetime) {
+        (Some(depth), _) => format!("rust-chess-engine, depth {}", depth),
+        (None, Some(ms)) => format!("rust-chess-engine, {} ms", ms),
+        (None, None) => "rust-chess-engine".to_string(),
+    };
+    let out = flag_value(args, "--out", 1).map_or("analysis.pgn", String::as_str);
+
+    let games = match load_pgn(Path::new(path)) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return;
+        }
+    };
+
+    let evaluator = HandCrafted::new(params);
+    let mut annotated = String::new();
+    let mut amazon_games = 0;
+    let mut slipped_games = 0;
+    for (i, game) in games.iter().enumerate() {
+        let analysis = analyze_game(game, &limits, &evaluator, &mut |_| {});
+        print!("Game {}/{}: {}", i + 1, games.len(), summarize(game, &analysis));
+        if amazon_side(&game.start).is_some() {
+            amazon_games += 1;
+            if !slipped_wins(game, &analysis).is_empty() {
+                slipped_games += 1;
+            }
+        }
+        annotated.push_str(&annotate(game, &analysis, &annotator).to_pgn());
+    }
     println!();
+    println!("The Amazon side let the win slip in {} of {} games", slipped_games, amazon_games);
+
+    match std::fs::write(out, annotated) {
+        Ok(()) => println!("Wrote {}", out),
+        Err(e) => eprintln!("Cannot write {}: {}", out, e),
+    }
+}
+
+/// Evaluation weights from `--eval-params <file>`, or the defaults
+fn eval_params(args: &[String]) -> Result<EvalParams, String> {
+    match args.iter().position(|arg| arg == "--eval-params") {
+        None => Ok(EvalParams::default()),
+        Some(i) => {
+            let path = args.get(i + 1).ok_or("--eval-params needs a file")?;
+            EvalParams::load(Path::new(path)).map_err(|e| format!("{}: {}", path, e))
+        }
+    }
 }
 
 fn main() {
-    // Check for UCI mode
     let args: Vec<String> = env::args().collect();
+    let params = match eval_params(&args) {
+        Ok(params) => params,
+        Err(e) => {
+            eprintln!("Cannot load evaluation parameters: {}", e);
+            return;
+        }
+    };
+
+    // Check for UCI mode
     if args.iter().any(|arg| arg == "--uci" || arg == "uci") {
-        uci_loop();
+        uci_loop(params);
+        return;
+    }
+
+    // Move generator verification
+    if args.get(1).map(String::as_str) == Some("perft") {
+        run_perft(&args[2..]);
+        return;
+    }
+
+    // Endgame table generation
+    if args.get(1).map(String::as_str) == Some("tablebase") {
+        run_tablebase(&args[2..]);
+        return;
+    }
+
+    // Engine-vs-engine testing
+    if args.get(1).map(String::as_str) == Some("match") {
+        run_engine_match(&args[2..]);
+        return;
+    }
+
+    // Evaluation weight tuning
+    if args.get(1).map(String::as_str) == Some("tune") {
+        run_tune(&args[2..], params);
+        return;
+    }
+
+    // Engine review of recorded games
+    if args.get(1).map(String::as_str) == Some("analyze") {
+        run_analyze(&args[2..], params);
         return;
     }
 
@@ -62,12 +460,19 @@ fn main() {
     println!("║   Checkmate the defended King!        ║");
     println!("╚═══════════════════════════════════════╝");
     println!();
-    println!("Run with --uci for UCI protocol mode.");
+    println!("Run with --uci for UCI protocol mode, --eval-params <file> for custom evaluation weights,");
+    println!("--pgn-out <file> to append the game to a PGN file.");
     println!();
 
     let mut board = Board::setup_amazon_vs_rook();
-    let mut move_history: Vec<rust_chess_engine::board::Move> = Vec::new();
+    let pgn_out = flag_value(&args, "--pgn-out", 1).cloned();
+    let mut game = PgnGame::with_tags(&board, variant_of(&board));
+    game.set_tag("Event", "Interactive game");
+    // Set when `play` stops at its move limit, until the position changes again
+    let mut move_limit = false;
     let search_depth = 4;
+    let mut tt = TranspositionTable::default();
+    let evaluator = HandCrafted::new(params);
 
     print_help();
     println!("{}", board);
@@ -96,9 +501,14 @@ fn main() {
         io::stdout().flush().unwrap();
 
         let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            println!("Error reading input");
-            continue;
+        match io::stdin().read_line(&mut input) {
+            // End of input: leave like "quit"
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => {
+                println!("Error reading input");
+                continue;
+            }
         }
 
         let input = input.trim().to_lowercase();
@@ -113,15 +523,15 @@ fn main() {
             }
             "auto" | "a" => {
                 println!("Engine thinking (depth {})...", search_depth);
-                if let Some((best_move, score)) = find_best_move(&mut board, search_depth) {
-                    let from_str = square_to_notation(best_move.from);
-                    let to_str = square_to_notation(best_move.to);
+                if let Some((best_move, score, comment)) = engine_move(&mut board, search_depth, &mut tt, &evaluator) {
                     println!(
-                        "Engine plays: {}{} (score: {})",
-                        from_str, to_str, score
+                        "Engine plays: {} (score: {})",
+                        move_to_uci(&best_move),
+                        score
                     );
-                    let mv = board.make_move(best_move.from, best_move.to);
-                    move_history.push(mv);
+                    let mv = board.apply_move(best_move);
+                    game.push(mv, Some(comment));
+                    move_limit = false;
                     println!();
                     println!("{}", board);
                 } else {
@@ -161,6 +571,7 @@ fn main() {
 
                     // Safety limit
                     if move_count >= max_moves {
+                        move_limit = true;
                         println!();
                         println!("*** Draw by {} move limit. ***", max_moves);
                         println!();
@@ -169,14 +580,15 @@ fn main() {
                     }
 
                     // Engine plays
-                    if let Some((best_move, score)) = find_best_move(&mut board, search_depth) {
+                    if let Some((best_move, score, comment)) =
+                        engine_move(&mut board, search_depth, &mut tt, &evaluator)
+                    {
                         move_count += 1;
-                        let from_str = square_to_notation(best_move.from);
-                        let to_str = square_to_notation(best_move.to);
+                        let move_str = move_to_uci(&best_move);
 
                         // Make move first to check if it results in check
-                        let mv = board.make_move(best_move.from, best_move.to);
-                        move_history.push(mv);
+                        let mv = board.apply_move(best_move);
+                        game.push(mv, Some(comment));
 
                         let check_marker = if board.is_in_check(board.side_to_move()) {
                             "+"
@@ -185,8 +597,8 @@ fn main() {
                         };
 
                         println!(
-                            "{}. {} {}{}{} (score: {})",
-                            move_count, current_side_name, from_str, to_str, check_marker, score
+                            "{}. {} {}{} (score: {})",
+                            move_count, current_side_name, move_str, check_marker, score
                         );
                     } else {
                         println!("No legal moves for {}!", current_side_name);
@@ -195,8 +607,9 @@ fn main() {
                 }
             }
             "undo" | "u" => {
-                if let Some(mv) = move_history.pop() {
+                if let Some(mv) = game.pop() {
                     board.unmake_move(mv);
+                    move_limit = false;
                     println!("Move undone.");
                     println!();
                     println!("{}", board);
@@ -211,25 +624,22 @@ fn main() {
                 } else {
                     println!("Legal moves ({}):", moves.len());
                     for mv in &moves {
-                        let from_str = square_to_notation(mv.from);
-                        let to_str = square_to_notation(mv.to);
-                        print!("{}{} ", from_str, to_str);
+                        print!("{} ", move_to_uci(mv));
                     }
                     println!();
                 }
             }
             _ => {
                 // Try to parse as a move
-                if let Some((from, to)) = parse_move(&input) {
+                if let Some(parsed) = parse_uci_move(&input) {
                     // Check if the move is legal
                     let legal_moves = board.generate_legal_moves();
-                    let is_legal = legal_moves
-                        .iter()
-                        .any(|mv| mv.from == from && mv.to == to);
+                    let legal = legal_moves.into_iter().find(|mv| mv.same_as(&parsed));
 
-                    if is_legal {
-                        let mv = board.make_move(from, to);
-                        move_history.push(mv);
+                    if let Some(legal) = legal {
+                        let mv = board.apply_move(legal);
+                        game.push(mv, None);
+                        move_limit = false;
                         println!();
                         println!("{}", board);
                     } else {
@@ -241,4 +651,8 @@ fn main() {
             }
         }
     }
+
+    if let Some(path) = &pgn_out {
+        save_game(path, &mut game, &mut board, move_limit);
+    }
 }
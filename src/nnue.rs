@@ -0,0 +1,571 @@
+// NNUE evaluation
+// An efficiently updatable neural network: a wide first layer whose output
+// (the accumulator) is kept up to date move by move, followed by a small
+// integer output layer evaluated at every leaf.
+//
+// Features (HalfKP-like, per perspective): the perspective's own king square
+// selects one of 64 buckets; inside it, every other piece sets one input
+// for (piece kind, square). The 15 piece kinds are the 7 non-king piece types
+// of either colour plus the enemy king. Black's perspective mirrors the board
+// vertically, so both sides share the same weights.
+//
+//   feature = (king_square * 15 + kind) * 64 + square
+//   kind    = 2 * (piece_type.index() - 1) + (1 if the piece is the enemy's)
+//             14 for the enemy king
+//
+// Inference: accumulator[perspective] = bias + sum of active feature columns
+// (i16); the output is
+//
+//   (output_bias + crelu(acc[us]) . w[..H] + crelu(acc[them]) . w[H..]) * SCALE / (QA * QB)
+//
+// with crelu clamping to 0..=QA.
+//
+// File format (little endian):
+//
+//   4 bytes          magic "ANN1"
+//   u32              feature count (61440)
+//   u32              hidden size H (64)
+//   i16 x H          feature-transformer biases
+//   i16 x 61440 x H  feature-transformer weights, one row of H per feature
+//   i32              output bias
+//   i16 x 2H         output weights, side to move's half first
+
+use crate::board::{Board, Color, Piece, PieceType, Square};
+use crate::evaluation::Evaluator;
+use crate::search::{CHECKMATE_SCORE, TB_BOUND};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Width of the accumulator for each perspective
+pub const HIDDEN: usize = 64;
+
+/// Non-king piece types of both colours, plus the enemy king
+const PIECE_KINDS: usize = 15;
+
+/// Number of input features per perspective
+pub const FEATURES: usize = 64 * PIECE_KINDS * 64;
+
+/// Clipped ReLU ceiling (quantisation of the hidden layer)
+const QA: i32 = 255;
+
+/// Quantisation of the output weights
+const QB: i32 = 64;
+
+/// Centipawns per unit of network output
+const SCALE: i64 = 400;
+
+/// Accumulator lanes summed side by side in the output layer
+const LANES: usize = 16;
+
+const MAGIC: &[u8; 4] = b"ANN1";
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Reasons a network cannot be loaded
+#[derive(Debug)]
+pub enum NnueError {
+    Io(std::io::Error),
+    BadFile(String),
+}
+
+impl std::fmt::Display for NnueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NnueError::Io(e) => write!(f, "{}", e),
+            NnueError::BadFile(s) => write!(f, "not a network file: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for NnueError {}
+
+impl From<std::io::Error> for NnueError {
+    fn from(e: std::io::Error) -> Self {
+        NnueError::Io(e)
+    }
+}
+
+// =============================================================================
+// Features
+// =============================================================================
+
+/// Square index as seen from `perspective`: a1 = 0 for White, a8 = 0 for Black
+fn orient(perspective: Color, square: Square) -> usize {
+    let (row, col) = (square.0 as usize, square.1 as usize);
+    match perspective {
+        Color::White => (7 - row) * 8 + col,
+        Color::Black => row * 8 + col,
+    }
+}
+
+/// Input feature of `piece` on `square` for the perspective whose king is on
+/// `king` (oriented); None for that king itself, which selects the bucket instead
+fn feature_index(perspective: Color, king: usize, piece: Piece, square: Square) -> Option<usize> {
+    let kind = match piece.piece_type {
+        PieceType::King if piece.color == perspective => return None,
+        PieceType::King => PIECE_KINDS - 1,
+        pt => 2 * (pt.index() - 1) + usize::from(piece.color != perspective),
+    };
+    Some((king * PIECE_KINDS + kind) * 64 + orient(perspective, square))
+}
+
+// =============================================================================
+// Network
+// =============================================================================
+
+/// Network weights, shared between threads and boards
+pub struct Network {
+    feature_bias: [i16; HIDDEN],
+    /// FEATURES rows of HIDDEN weights
+    feature_weights: Vec<i16>,
+    output_bias: i32,
+    output_weights: [i16; 2 * HIDDEN],
+}
+
+impl Network {
+    /// A network with every weight zero (scores every position 0)
+    pub fn zeroed() -> Self {
+        Network {
+            feature_bias: [0; HIDDEN],
+            feature_weights: vec![0; FEATURES * HIDDEN],
+            output_bias: 0,
+            output_weights: [0; 2 * HIDDEN],
+        }
+    }
+
+    /// Weights of one input feature
+    fn feature(&self, index: usize) -> &[i16] {
+        &self.feature_weights[index * HIDDEN..(index + 1) * HIDDEN]
+    }
+
+    /// Accumulator of one perspective computed from scratch, with its king square
+    fn refresh(&self, board: &Board, perspective: Color) -> ([i16; HIDDEN], Option<usize>) {
+        let mut values = self.feature_bias;
+        let king = match board.find_king(perspective) {
+            Some(square) => orient(perspective, square),
+            None => return (values, None),
+        };
+        for row in 0..8u8 {
+            for col in 0..8u8 {
+                let square = (row, col);
+                let index = board.get_piece(square).and_then(|piece| feature_index(perspective, king, piece, square));
+                if let Some(index) = index {
+                    add_feature(&mut values, self.feature(index));
+                }
+            }
+        }
+        (values, Some(king))
+    }
+
+    /// Output layer: side to move's accumulator first
+    fn output(&self, us: &[i16; HIDDEN], them: &[i16; HIDDEN]) -> i32 {
+        let sum = self.output_bias as i64
+            + crelu_dot(us, &self.output_weights[..HIDDEN]) as i64
+            + crelu_dot(them, &self.output_weights[HIDDEN..]) as i64;
+        let score = sum * SCALE / (QA * QB) as i64;
+        // Stay clear of the mate and tablebase score ranges
+        score.clamp(-(TB_BOUND - 1) as i64, (TB_BOUND - 1) as i64) as i32
+    }
+
+    /// Evaluate a position from scratch, from the side to move's perspective
+    pub fn evaluate(&self, board: &Board) -> i32 {
+        let side = board.side_to_move();
+        let (us, _) = self.refresh(board, side);
+        let (them, _) = self.refresh(board, side.opposite());
+        self.output(&us, &them)
+    }
+
+    // =========================================================================
+    // File Format
+    // =========================================================================
+
+    /// Serialize in the file format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + 2 * (HIDDEN + FEATURES * HIDDEN + 2 * HIDDEN));
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(FEATURES as u32).to_le_bytes());
+        bytes.extend_from_slice(&(HIDDEN as u32).to_le_bytes());
+        bytes.extend(self.feature_bias.iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend(self.feature_weights.iter().flat_map(|w| w.to_le_bytes()));
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+        bytes.extend(self.output_weights.iter().flat_map(|w| w.to_le_bytes()));
+        bytes
+    }
+
+    /// Parse the file format; the sizes in the header must match this build
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let header = MAGIC.len() + 8;
+        let expected = header + 2 * HIDDEN + 2 * FEATURES * HIDDEN + 4 + 4 * HIDDEN;
+        if bytes.len() != expected || !is_network(bytes) {
+            return None;
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+        if u32_at(4) != FEATURES || u32_at(8) != HIDDEN {
+            return None;
+        }
+
+        let mut network = Network::zeroed();
+        let rest = read_i16s(&bytes[header..], &mut network.feature_bias);
+        let rest = read_i16s(rest, &mut network.feature_weights);
+        network.output_bias = i32::from_le_bytes(rest[..4].try_into().unwrap());
+        read_i16s(&rest[4..], &mut network.output_weights);
+        Some(network)
+    }
+
+    /// Write a network file
+    pub fn write(&self, path: &Path) -> Result<(), NnueError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Read a network file
+    pub fn read(path: &Path) -> Result<Self, NnueError> {
+        let bytes = fs::read(path)?;
+        Network::from_bytes(&bytes).ok_or_else(|| NnueError::BadFile(path.display().to_string()))
+    }
+}
+
+/// Fill `out` from little-endian bytes and return the bytes after them
+fn read_i16s<'a>(bytes: &'a [u8], out: &mut [i16]) -> &'a [u8] {
+    for (value, chunk) in out.iter_mut().zip(bytes.chunks_exact(2)) {
+        *value = i16::from_le_bytes([chunk[0], chunk[1]]);
+    }
+    &bytes[2 * out.len()..]
+}
+
+/// Whether the bytes start like a network file
+pub fn is_network(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+// =============================================================================
+// Vector Kernels
+// =============================================================================
+// Fixed-width loops over i16 lanes that the compiler turns into SIMD adds
+// and multiply-adds
+
+fn add_feature(values: &mut [i16; HIDDEN], weights: &[i16]) {
+    for (value, weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+fn sub_feature(values: &mut [i16; HIDDEN], weights: &[i16]) {
+    for (value, weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+/// Dot product of the clipped accumulator with a row of output weights
+fn crelu_dot(values: &[i16; HIDDEN], weights: &[i16]) -> i32 {
+    let mut lanes = [0i32; LANES];
+    for (values, weights) in values.chunks_exact(LANES).zip(weights.chunks_exact(LANES)) {
+        for ((lane, value), weight) in lanes.iter_mut().zip(values).zip(weights) {
+            *lane += (*value as i32).clamp(0, QA) * *weight as i32;
+        }
+    }
+    lanes.iter().sum()
+}
+
+// =============================================================================
+// Incremental Accumulator
+// =============================================================================
+
+/// First-layer output for both perspectives
+/// A perspective whose king has moved has no king square and is rebuilt
+/// from scratch when next evaluated
+#[derive(Clone)]
+struct Accumulator {
+    values: [[i16; HIDDEN]; 2],
+    kings: [Option<usize>; 2],
+}
+
+/// Accumulators along the current line of play, attached to a board
+/// The board pushes a copy before each move and pops it on unmake, so
+/// undoing a move costs nothing
+#[derive(Clone)]
+pub struct NnueState {
+    network: Arc<Network>,
+    stack: Vec<Accumulator>,
+}
+
+impl NnueState {
+    /// State for `network` on the current position
+    pub fn new(network: Arc<Network>, board: &Board) -> Self {
+        let (white, white_king) = network.refresh(board, Color::White);
+        let (black, black_king) = network.refresh(board, Color::Black);
+        let root = Accumulator {
+            values: [white, black],
+            kings: [white_king, black_king],
+        };
+        NnueState { network, stack: vec![root] }
+    }
+
+    /// Whether the accumulators were built for `network`
+    pub fn uses(&self, network: &Arc<Network>) -> bool {
+        Arc::ptr_eq(&self.network, network)
+    }
+
+    /// Start a new move: the next changes apply to a copy of the accumulator
+    pub fn push(&mut self) {
+        let top = self.stack.last().expect("Accumulator stack is never empty").clone();
+        self.stack.push(top);
+    }
+
+    /// Undo a move; below the attach point both perspectives are rebuilt lazily
+    pub fn pop(&mut self) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        } else {
+            self.stack[0].kings = [None, None];
+        }
+    }
+
+    /// A square changed from `old` to `new`
+    pub fn update(&mut self, square: Square, old: Option<Piece>, new: Option<Piece>) {
+        let network = &self.network;
+        let acc = self.stack.last_mut().expect("Accumulator stack is never empty");
+        for perspective in [Color::White, Color::Black] {
+            let p = perspective.index();
+            for (piece, added) in [(old, false), (new, true)] {
+                let (piece, king) = match (piece, acc.kings[p]) {
+                    (Some(piece), Some(king)) => (piece, king),
+                    _ => continue,
+                };
+                match feature_index(perspective, king, piece, square) {
+                    // Own king moved: the whole perspective changes bucket
+                    None => acc.kings[p] = None,
+                    Some(index) if added => add_feature(&mut acc.values[p], network.feature(index)),
+                    Some(index) => sub_feature(&mut acc.values[p], network.feature(index)),
+                }
+            }
+        }
+    }
+
+    /// Evaluate from the side to move's perspective, rebuilding stale perspectives
+    pub fn evaluate(&mut self, board: &Board) -> i32 {
+        let acc = self.stack.last_mut().expect("Accumulator stack is never empty");
+        for perspective in [Color::White, Color::Black] {
+            let p = perspective.index();
+            if acc.kings[p].is_none() {
+                (acc.values[p], acc.kings[p]) = self.network.refresh(board, perspective);
+            }
+        }
+        let side = board.side_to_move();
+        self.network.output(&acc.values[side.index()], &acc.values[side.opposite().index()])
+    }
+}
+
+// =============================================================================
+// Evaluator
+// =============================================================================
+
+/// Evaluation by a network; the search board carries its accumulators while a search runs
+pub struct NnueEvaluator {
+    network: Arc<Network>,
+}
+
+impl NnueEvaluator {
+    pub fn new(network: Arc<Network>) -> Self {
+        NnueEvaluator { network }
+    }
+}
+
+impl Evaluator for NnueEvaluator {
+    fn prepare(&self, board: &mut Board) {
+        board.attach_nnue(Arc::clone(&self.network));
+    }
+
+    fn finish(&self, board: &mut Board) {
+        board.detach_nnue();
+    }
+
+    fn evaluate(&self, board: &mut Board) -> i32 {
+        // Moves are only generated when in check; stalemates are left to the search
+        let side = board.side_to_move();
+        if board.is_checkmate(side) {
+            return -CHECKMATE_SCORE;
+        }
+        // Boards the search did not prepare are evaluated from scratch
+        board.nnue_evaluate(&self.network).unwrap_or_else(|| self.network.evaluate(board))
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{find_best_move_with, SearchLimits};
+    use crate::transposition::TranspositionTable;
+
+    /// Network with pseudo-random weights, to catch any missed update
+    fn random_network() -> Network {
+        let mut state: u32 = 12345;
+        let mut next = || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            ((state >> 16) % 61) as i16 - 30
+        };
+        let mut network = Network::zeroed();
+        network.feature_weights.iter_mut().for_each(|w| *w = next());
+        network.feature_bias.iter_mut().for_each(|w| *w = next() + 40);
+        network.output_weights.iter_mut().for_each(|w| *w = next());
+        network.output_bias = 1000;
+        network
+    }
+
+    /// Network counting material: hidden unit 0 sums the perspective's own
+    /// pieces, unit 1 the enemy's
+    fn material_network() -> Network {
+        let mut network = Network::zeroed();
+        for king in 0..64 {
+            for pt in PieceType::ALL.into_iter().filter(|&pt| pt != PieceType::King) {
+                let value = match pt {
+                    PieceType::Amazon => 120,
+                    PieceType::Rook => 50,
+                    _ => 30,
+                };
+                for (kind, unit) in [(0, 0), (1, 1)] {
+                    for square in 0..64 {
+                        let feature = (king * PIECE_KINDS + 2 * (pt.index() - 1) + kind) * 64 + square;
+                        network.feature_weights[feature * HIDDEN + unit] = value;
+                    }
+                }
+            }
+        }
+        network.output_weights[0] = QB as i16;
+        network.output_weights[1] = -QB as i16;
+        network
+    }
+
+    #[test]
+    fn test_feature_index() {
+        let white_king = Piece::new(PieceType::King, Color::White);
+        let black_rook = Piece::new(PieceType::Rook, Color::Black);
+        // Own king selects the bucket
+        assert_eq!(feature_index(Color::White, 4, white_king, (7, 4)), None);
+        // Rook on a8 seen by White (king e1), and on a1 seen by Black
+        assert_eq!(feature_index(Color::White, 4, black_rook, (0, 0)), Some((4 * 15 + 3) * 64 + 56));
+        assert_eq!(feature_index(Color::Black, 4, black_rook, (0, 0)), Some((4 * 15 + 2) * 64));
+        assert_eq!(feature_index(Color::Black, 63, white_king, (7, 7)), Some((63 * 15 + 14) * 64 + 63));
+    }
+
+    #[test]
+    fn test_material_network_prefers_amazon() {
+        let network = material_network();
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3AK3 w - -").unwrap();
+        assert_eq!(network.evaluate(&board), 120 * 400 / 255);
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3AK3 b - -").unwrap();
+        assert_eq!(network.evaluate(&board), -(120 * 400 / 255));
+    }
+
+    #[test]
+    fn test_incremental_matches_refresh() {
+        let network = Arc::new(random_network());
+        let mut board = Board::from_fen("r3k2r/1P6/8/3a4/8/8/6p1/R3K2R w KQkq - 0 1").unwrap();
+        board.attach_nnue(Arc::clone(&network));
+
+        let mut played = Vec::new();
+        for ply in 0..24 {
+            let moves = board.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            played.push(board.apply_move(moves[(ply * 7 + 3) % moves.len()]));
+            assert_eq!(board.nnue_evaluate(&network), Some(network.evaluate(&board)), "ply {}", ply);
+        }
+        while let Some(mv) = played.pop() {
+            board.unmake_move(mv);
+            assert_eq!(board.nnue_evaluate(&network), Some(network.evaluate(&board)));
+        }
+        // Unmaking past the attach point rebuilds instead of going stale
+        let mv = board.generate_legal_moves()[0];
+        let played = board.apply_move(mv);
+        board.detach_nnue();
+        board.attach_nnue(Arc::clone(&network));
+        board.unmake_move(played);
+        assert_eq!(board.nnue_evaluate(&network), Some(network.evaluate(&board)));
+    }
+
+    #[test]
+    fn test_castling_and_promotion_updates() {
+        let network = Arc::new(random_network());
+        let mut board = Board::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        board.attach_nnue(Arc::clone(&network));
+        for name in ["e1c1", "e8g8", "b7a8"] {
+            let mv = board
+                .generate_legal_moves()
+                .into_iter()
+                .find(|mv| crate::uci::move_to_uci(mv).starts_with(name))
+                .unwrap();
+            board.apply_move(mv);
+            assert_eq!(board.nnue_evaluate(&network), Some(network.evaluate(&board)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_other_network_is_not_used() {
+        let network = Arc::new(Network::zeroed());
+        let mut board = Board::setup_amazon_vs_rook();
+        assert_eq!(board.nnue_evaluate(&network), None);
+        board.attach_nnue(Arc::new(Network::zeroed()));
+        assert_eq!(board.nnue_evaluate(&network), None);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let network = random_network();
+        let path = std::env::temp_dir().join(format!("nnue_round_trip_{}.nnue", std::process::id()));
+        network.write(&path).unwrap();
+        let read = Network::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        let read = read.unwrap();
+
+        assert_eq!(read.feature_bias, network.feature_bias);
+        assert!(read.feature_weights == network.feature_weights);
+        assert_eq!(read.output_bias, network.output_bias);
+        assert_eq!(read.output_weights, network.output_weights);
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        assert!(Network::from_bytes(b"ANN1").is_none());
+        let mut bytes = Network::zeroed().to_bytes();
+        bytes[8] = 32; // Hidden size of another build
+        assert!(Network::from_bytes(&bytes).is_none());
+        assert!(!is_network(b"# Evaluation parameters"));
+        assert!(matches!(Network::read(Path::new("/nonexistent/net.nnue")), Err(NnueError::Io(_))));
+    }
+
+    #[test]
+    fn test_search_with_network() {
+        let evaluator = NnueEvaluator::new(Arc::new(material_network()));
+        // The rook hangs to the amazon
+        let mut board = Board::from_fen("4k3/8/8/8/3r4/8/8/3AK3 w - -").unwrap();
+        let mut tt = TranspositionTable::new(1);
+        let (best, _) = find_best_move_with(&mut board, &SearchLimits::depth(2), &mut tt, &evaluator).unwrap();
+        assert_eq!(crate::uci::move_to_uci(&best), "d1d4");
+    }
+
+    #[test]
+    fn test_search_leaves_board_without_accumulators() {
+        let network = Arc::new(material_network());
+        let evaluator = NnueEvaluator::new(Arc::clone(&network));
+        let mut board = Board::from_fen("4k3/8/8/8/3r4/8/8/3AK3 w - -").unwrap();
+        let mut tt = TranspositionTable::new(1);
+        find_best_move_with(&mut board, &SearchLimits::depth(2), &mut tt, &evaluator).unwrap();
+        assert_eq!(board.nnue_evaluate(&network), None);
+    }
+
+    #[test]
+    fn test_evaluator_scores_mate() {
+        let evaluator = NnueEvaluator::new(Arc::new(Network::zeroed()));
+        let mut board = Board::from_fen("k7/2A5/1K6/8/8/8/8/8 b - -").unwrap();
+        assert_eq!(evaluator.evaluate(&mut board), -CHECKMATE_SCORE);
+    }
+}
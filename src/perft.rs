@@ -0,0 +1,130 @@
+// Perft (performance test) - move generator verification
+// Counts the leaf nodes of the legal move tree to a fixed depth. The numbers
+// can be compared with another engine (e.g. Fairy-Stockfish "go perft N")
+// to find move generation bugs long before they show up as illegal moves.
+
+use crate::bitboard::BitBoard;
+use crate::board::{Board, Move};
+
+/// The minimal position interface perft needs
+/// Implemented by both board representations so they can be cross-checked
+pub trait PerftPosition {
+    fn generate_legal_moves(&mut self) -> Vec<Move>;
+    fn make_move(&mut self, mv: Move) -> Move;
+    fn unmake_move(&mut self, mv: Move);
+}
+
+impl PerftPosition for Board {
+    fn generate_legal_moves(&mut self) -> Vec<Move> {
+        Board::generate_legal_moves(self)
+    }
+
+    fn make_move(&mut self, mv: Move) -> Move {
+        Board::apply_move(self, mv)
+    }
+
+    fn unmake_move(&mut self, mv: Move) {
+        Board::unmake_move(self, mv)
+    }
+}
+
+impl PerftPosition for BitBoard {
+    fn generate_legal_moves(&mut self) -> Vec<Move> {
+        BitBoard::generate_legal_moves(self)
+    }
+
+    fn make_move(&mut self, mv: Move) -> Move {
+        BitBoard::apply_move(self, mv)
+    }
+
+    fn unmake_move(&mut self, mv: Move) {
+        BitBoard::unmake_move(self, mv)
+    }
+}
+
+/// Count the leaf nodes of the legal move tree `depth` plies deep
+pub fn perft<P: PerftPosition>(position: &mut P, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = position.generate_legal_moves();
+
+    // Bulk counting: no need to make the last ply of moves
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for mv in moves {
+        let made = position.make_move(mv);
+        nodes += perft(position, depth - 1);
+        position.unmake_move(made);
+    }
+    nodes
+}
+
+/// Perft split by root move: the node count below each legal move
+/// Comparing this with a reference engine pinpoints the faulty subtree
+pub fn divide<P: PerftPosition>(position: &mut P, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let moves = position.generate_legal_moves();
+    let mut result = Vec::with_capacity(moves.len());
+    for mv in moves {
+        let made = position.make_move(mv);
+        let nodes = perft(position, depth - 1);
+        position.unmake_move(made);
+        result.push((mv, nodes));
+    }
+    result
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece, PieceType};
+
+    #[test]
+    fn test_perft_depth_zero_and_one() {
+        let mut board = Board::setup_amazon_vs_rook();
+        assert_eq!(perft(&mut board, 0), 1);
+        let legal = board.generate_legal_moves().len() as u64;
+        assert_eq!(perft(&mut board, 1), legal);
+    }
+
+    #[test]
+    fn test_perft_lone_kings() {
+        // Kings on a8 and h1: 3 moves each, never interacting within 2 plies
+        let mut board = Board::new();
+        board.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::White)));
+        board.set_piece((7, 7), Some(Piece::new(PieceType::King, Color::Black)));
+        assert_eq!(perft(&mut board, 1), 3);
+        assert_eq!(perft(&mut board, 2), 9);
+    }
+
+    #[test]
+    fn test_divide_sums_to_perft() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let split = divide(&mut board, 2);
+        let total: u64 = split.iter().map(|(_, nodes)| nodes).sum();
+        assert_eq!(total, perft(&mut board, 2));
+        assert_eq!(split.len(), board.generate_legal_moves().len());
+    }
+
+    #[test]
+    fn test_perft_leaves_board_unchanged() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let fen = board.to_fen();
+        let hash = board.position_hash();
+        perft(&mut board, 3);
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.position_hash(), hash);
+    }
+}
@@ -0,0 +1,978 @@
+// PGN input and output
+// Games are written in Standard Algebraic Notation, with the Amazon and
+// Actress using their FEN letters (A, X) like any other piece. Engine moves
+// carry cutechess-style comments ("{+0.45/12 0.50s}", "{+M5/9 0.02s}").
+// The reader accepts what cutechess and the writer produce: tag pairs, FEN
+// setups, comments, NAGs, variations (skipped) and result tokens.
+
+use crate::board::{Board, CastleSide, Color, FenError, Move, PieceType, Piece};
+use crate::search::{score_to_cp, CHECKMATE_SCORE, MATE_BOUND};
+use crate::uci::{parse_square, square_to_uci};
+use crate::variant::{find_external_variant, find_variant, Variant};
+use std::fs;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Movetext lines are wrapped before this many characters
+const LINE_WIDTH: usize = 80;
+
+// =============================================================================
+// SAN
+// =============================================================================
+
+/// Whether a legal move castles: the king moves two files or onto its own rook
+fn is_castling(board: &Board, mv: &Move) -> bool {
+    match board.get_piece(mv.from) {
+        Some(king) if king.piece_type == PieceType::King => {
+            board.get_piece(mv.to) == Some(Piece::new(PieceType::Rook, king.color))
+                || (mv.from.0 == mv.to.0 && mv.from.1.abs_diff(mv.to.1) == 2)
+        }
+        _ => false,
+    }
+}
+
+/// SAN of a legal move in the given position (before the move is played),
+/// with a "+" or "#" suffix when it gives check or mate
+pub fn move_to_san(board: &mut Board, mv: &Move) -> String {
+    let mut san = san_without_suffix(board, mv);
+    if san.is_empty() {
+        return san;
+    }
+
+    let played = board.apply_move(*mv);
+    let side = board.side_to_move();
+    if board.is_checkmate(side) {
+        san.push('#');
+    } else if board.is_in_check(side) {
+        san.push('+');
+    }
+    board.unmake_move(played);
+    san
+}
+
+/// SAN without the check or mate suffix
+fn san_without_suffix(board: &mut Board, mv: &Move) -> String {
+    let piece = match board.get_piece(mv.from) {
+        Some(piece) => piece,
+        None => return String::new(),
+    };
+
+    if is_castling(board, mv) {
+        return match CastleSide::of_move(mv.from, mv.to) {
+            CastleSide::King => "O-O".to_string(),
+            CastleSide::Queen => "O-O-O".to_string(),
+        };
+    }
+
+    let destination = square_to_uci(mv.to);
+    let capture = mv.captured.is_some() || board.get_piece(mv.to).is_some();
+    let mut san = String::new();
+
+    if piece.piece_type == PieceType::Pawn {
+        if capture || mv.from.1 != mv.to.1 {
+            san.push((b'a' + mv.from.1) as char);
+            san.push('x');
+        }
+        san.push_str(&destination);
+        if let Some(promotion) = mv.promotion {
+            san.push('=');
+            san.push(promotion.info().letter);
+        }
+        return san;
+    }
+
+    san.push(piece.piece_type.info().letter);
+
+    // Other pieces of the same kind that could also reach the square
+    let rivals: Vec<Move> = board
+        .generate_legal_moves()
+        .into_iter()
+        .filter(|other| other.to == mv.to && other.from != mv.from)
+        .filter(|other| board.get_piece(other.from) == Some(piece))
+        .collect();
+    if !rivals.is_empty() {
+        let file = (b'a' + mv.from.1) as char;
+        let rank = (b'8' - mv.from.0) as char;
+        if rivals.iter().all(|other| other.from.1 != mv.from.1) {
+            san.push(file);
+        } else if rivals.iter().all(|other| other.from.0 != mv.from.0) {
+            san.push(rank);
+        } else {
+            san.push(file);
+            san.push(rank);
+        }
+    }
+
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&destination);
+    san
+}
+
+/// The legal move a SAN string names, or None if it names no move or more
+/// than one; check, mate and annotation suffixes are ignored, and a missing
+/// "x" or a superfluous disambiguation is accepted
+pub fn san_to_move(board: &mut Board, san: &str) -> Option<Move> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    let moves = board.generate_legal_moves();
+
+    let castle = match san {
+        "O-O" | "0-0" => Some(CastleSide::King),
+        "O-O-O" | "0-0-0" => Some(CastleSide::Queen),
+        _ => None,
+    };
+    if let Some(side) = castle {
+        return moves
+            .into_iter()
+            .find(|mv| is_castling(board, mv) && CastleSide::of_move(mv.from, mv.to) == side);
+    }
+
+    // Promotion: "e8=A", or "e8A" without the "="
+    let mut body: Vec<char> = san.chars().filter(|&c| c != 'x' && c != '=').collect();
+    let promotion = match body.last() {
+        Some(&c) if c.is_ascii_uppercase() && body.len() > 1 => {
+            body.pop();
+            Some(PieceType::from_letter(c)?)
+        }
+        _ => None,
+    };
+    let piece_type = match body.first() {
+        Some(&c) if c.is_ascii_uppercase() => {
+            body.remove(0);
+            PieceType::from_letter(c)?
+        }
+        _ => PieceType::Pawn,
+    };
+    if body.len() < 2 {
+        return None;
+    }
+    let to = parse_square(&body[body.len() - 2..].iter().collect::<String>())?;
+
+    // Whatever precedes the destination narrows down the origin
+    let mut from_file = None;
+    let mut from_rank = None;
+    for &c in &body[..body.len() - 2] {
+        match c {
+            'a'..='h' => from_file = Some(c as u8 - b'a'),
+            '1'..='8' => from_rank = Some(b'8' - c as u8),
+            _ => return None,
+        }
+    }
+
+    let mut candidates = moves.into_iter().filter(|mv| {
+        board.get_piece(mv.from).map(|piece| piece.piece_type) == Some(piece_type)
+            && mv.to == to
+            && mv.promotion == promotion
+            && from_file.is_none_or(|file| mv.from.1 == file)
+            && from_rank.is_none_or(|rank| mv.from.0 == rank)
+            && !is_castling(board, mv)
+    });
+    let found = candidates.next()?;
+    match candidates.next() {
+        Some(_) => None,
+        None => Some(found),
+    }
+}
+
+// =============================================================================
+// Comments and Tags
+// =============================================================================
+
+/// Score in pawns, or plies to mate: "+0.45", "-1.20", "+M5", "-M4"
+pub fn format_score(score: i32) -> String {
+    if score >= MATE_BOUND {
+        format!("+M{}", CHECKMATE_SCORE - score)
+    } else if score <= -MATE_BOUND {
+        format!("-M{}", CHECKMATE_SCORE + score)
+    } else {
+        format!("{:+.2}", score_to_cp(score) as f64 / 100.0)
+    }
+}
+
+/// Engine comment for a move: score, depth and thinking time,
+/// e.g. "+0.45/12 0.50s" or "-M4/20 1.20s"
+pub fn score_comment(score: i32, depth: i32, time: Duration) -> String {
+    format!("{}/{} {:.2}s", format_score(score), depth, time.as_secs_f64())
+}
+
+/// PGN date ("YYYY.MM.DD") of a day counted from 1970-01-01
+fn date_from_days(days: i64) -> String {
+    // Civil-from-days conversion on the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+/// Today's date for the Date tag (UTC), or "????.??.??" if the clock is unset
+pub fn today() -> String {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => date_from_days((elapsed.as_secs() / 86_400) as i64),
+        Err(_) => "????.??.??".to_string(),
+    }
+}
+
+/// A comment in braces; a closing brace would end it early, so it is dropped
+fn brace_comment(comment: &str) -> String {
+    format!("{{{}}}", comment.replace('}', ""))
+}
+
+/// Number before a move: "12." for White, "12..." for Black when `numbered`
+fn push_move_number(board: &Board, numbered: bool, tokens: &mut Vec<String>) {
+    match board.side_to_move() {
+        Color::White => tokens.push(format!("{}.", board.fullmove_number())),
+        Color::Black if numbered => tokens.push(format!("{}...", board.fullmove_number())),
+        Color::Black => {}
+    }
+}
+
+/// Numbered SAN of a line of legal moves from `board`
+fn line_tokens(board: &Board, line: &[Move]) -> Vec<String> {
+    let mut board = board.clone();
+    let mut tokens = Vec::new();
+    for (i, mv) in line.iter().enumerate() {
+        push_move_number(&board, i == 0, &mut tokens);
+        tokens.push(move_to_san(&mut board, mv));
+        board.apply_move(*mv);
+    }
+    tokens
+}
+
+// =============================================================================
+// Games
+// =============================================================================
+
+/// A game to be written as PGN: tags, the starting position and the moves
+#[derive(Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub moves: Vec<Move>,
+    /// Comments by ply; missing entries have no comment
+    pub comments: Vec<Option<String>>,
+    /// Numeric annotation glyphs by ply ($2 = mistake, $4 = blunder, ...)
+    pub nags: Vec<Option<u8>>,
+    /// Alternative lines by ply, played from the position before that ply's move
+    pub variations: Vec<Option<Vec<Move>>>,
+    /// "1-0", "0-1", "1/2-1/2" or "*"
+    pub result: String,
+    /// Written before the result, e.g. "White mates"
+    pub result_comment: Option<String>,
+}
+
+impl PgnGame {
+    /// A game from `start` without moves, tags or result yet
+    pub fn new(start: &Board) -> Self {
+        PgnGame {
+            tags: Vec::new(),
+            start: start.clone(),
+            moves: Vec::new(),
+            comments: Vec::new(),
+            nags: Vec::new(),
+            variations: Vec::new(),
+            result: "*".to_string(),
+            result_comment: None,
+        }
+    }
+
+    /// A game from `start` with the Seven Tag Roster plus FEN, SetUp, Variant
+    /// and PlyCount, dated today; players and event are left as "?"
+    /// The Variant tag holds the Fairy-Stockfish name, so cutechess and
+    /// Fairy-Stockfish can load the game; the FEN gives the actual setup
+    pub fn with_tags(start: &Board, variant: &Variant) -> Self {
+        let mut game = PgnGame::new(start);
+        for (name, value) in [("Event", "?"), ("Site", "?"), ("Date", &today()), ("Round", "?")] {
+            game.set_tag(name, value);
+        }
+        for (name, value) in [("White", "?"), ("Black", "?"), ("Result", "*")] {
+            game.set_tag(name, value);
+        }
+        game.set_tag("FEN", &start.to_fen());
+        game.set_tag("SetUp", "1");
+        game.set_tag("Variant", variant.external_name);
+        game.set_tag("PlyCount", "0");
+        game
+    }
+
+    /// Append a played move with an optional comment
+    pub fn push(&mut self, mv: Move, comment: Option<String>) {
+        self.comments.resize(self.moves.len(), None);
+        self.moves.push(mv);
+        self.comments.push(comment);
+        self.set_tag("PlyCount", &self.moves.len().to_string());
+    }
+
+    /// Take back the last move
+    pub fn pop(&mut self) -> Option<Move> {
+        let mv = self.moves.pop()?;
+        self.comments.truncate(self.moves.len());
+        self.nags.truncate(self.moves.len());
+        self.variations.truncate(self.moves.len());
+        self.set_tag("PlyCount", &self.moves.len().to_string());
+        Some(mv)
+    }
+
+    /// Record the outcome: the Result and Termination tags, the result token
+    /// and optionally a comment describing how the game ended
+    pub fn finish(&mut self, result: &str, termination: &str, comment: Option<&str>) {
+        self.set_tag("Result", result);
+        self.set_tag("Termination", termination);
+        self.result = result.to_string();
+        self.result_comment = comment.map(str::to_string);
+    }
+
+    /// Value of a tag, if present
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Set a tag, replacing any earlier value
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Movetext: numbered SAN moves with their annotations, followed by the result
+    fn movetext(&self) -> String {
+        let mut board = self.start.clone();
+        let mut tokens = Vec::new();
+        // Black's moves are numbered at the start and after a variation
+        let mut numbered = true;
+
+        for (i, mv) in self.moves.iter().enumerate() {
+            push_move_number(&board, numbered, &mut tokens);
+            tokens.push(move_to_san(&mut board, mv));
+            numbered = false;
+            if let Some(Some(nag)) = self.nags.get(i) {
+                tokens.push(format!("${}", nag));
+            }
+            match self.comments.get(i) {
+                Some(Some(comment)) => tokens.push(brace_comment(comment)),
+                // An empty comment keeps the result comment from being read as the last move's
+                _ if i + 1 == self.moves.len() && self.result_comment.is_some() => tokens.push("{}".to_string()),
+                _ => {}
+            }
+            if let Some(Some(line)) = self.variations.get(i) {
+                let mut variation = line_tokens(&board, line);
+                if !variation.is_empty() {
+                    variation[0].insert(0, '(');
+                    if let Some(last) = variation.last_mut() {
+                        last.push(')');
+                    }
+                    tokens.append(&mut variation);
+                    numbered = true;
+                }
+            }
+            board.apply_move(*mv);
+        }
+        if let Some(comment) = &self.result_comment {
+            tokens.push(brace_comment(comment));
+        }
+        tokens.push(self.result.clone());
+
+        // Wrap into lines
+        let mut text = String::new();
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > LINE_WIDTH {
+                text.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                text.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            text.push_str(&token);
+        }
+        text
+    }
+
+    /// The game as PGN text, ending with a blank line
+    pub fn to_pgn(&self) -> String {
+        let mut text = String::new();
+        for (name, value) in &self.tags {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            text.push_str(&format!("[{} \"{}\"]\n", name, value));
+        }
+        text.push('\n');
+        text.push_str(&self.movetext());
+        text.push_str("\n\n");
+        text
+    }
+}
+
+// =============================================================================
+// Reading
+// =============================================================================
+
+/// Reasons PGN text cannot be read
+#[derive(Debug)]
+pub enum PgnError {
+    Io(std::io::Error),
+    /// A tag pair that is not `[Name "value"]` (line number)
+    BadTag(usize),
+    /// The FEN tag cannot be parsed (game number)
+    BadFen(usize, FenError),
+    /// A move that is illegal or ambiguous in its position (game number, ply, SAN)
+    BadMove(usize, usize, String),
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::Io(e) => write!(f, "{}", e),
+            PgnError::BadTag(line) => write!(f, "line {}: malformed tag pair", line),
+            PgnError::BadFen(game, e) => write!(f, "game {}: {}", game, e),
+            PgnError::BadMove(game, ply, san) => {
+                write!(f, "game {}: illegal or ambiguous move {} at ply {}", game, san, ply)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<std::io::Error> for PgnError {
+    fn from(e: std::io::Error) -> Self {
+        PgnError::Io(e)
+    }
+}
+
+/// Result tokens that end a game's movetext
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+/// Lexical element of PGN text
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    /// A move, a move number or a result
+    Symbol(String),
+    /// Numeric annotation glyph ($1, $2, ...); None if out of range
+    Nag(Option<u8>),
+    VariationStart,
+    VariationEnd,
+}
+
+/// Skip the rest of the line, newline included
+fn skip_line(chars: &mut Peekable<Chars>) {
+    for c in chars.by_ref() {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+/// Name and value of a tag pair, after its opening bracket
+fn read_tag(chars: &mut Peekable<Chars>) -> Option<(String, String)> {
+    let skip_spaces = |chars: &mut Peekable<Chars>| while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {};
+
+    skip_spaces(chars);
+    let mut name = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+        name.push(c);
+    }
+    skip_spaces(chars);
+    if name.is_empty() || chars.next() != Some('"') {
+        return None;
+    }
+
+    let mut value = String::new();
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => value.push(chars.next()?),
+            '\n' => return None,
+            c => value.push(c),
+        }
+    }
+    skip_spaces(chars);
+    (chars.next() == Some(']')).then_some((name, value))
+}
+
+/// Split PGN text into tokens
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        let at_line_start = line_start;
+        line_start = c == '\n';
+        match c {
+            '\n' => line += 1,
+            // Escaped lines and rest-of-line comments
+            '%' if at_line_start => {
+                skip_line(&mut chars);
+                line += 1;
+                line_start = true;
+            }
+            ';' => {
+                skip_line(&mut chars);
+                line += 1;
+                line_start = true;
+            }
+            '[' => {
+                let tag = read_tag(&mut chars).ok_or(PgnError::BadTag(line))?;
+                tokens.push(Token::Tag(tag.0, tag.1));
+            }
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                line += comment.matches('\n').count();
+                tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<_>>().join(" ")));
+            }
+            '(' => tokens.push(Token::VariationStart),
+            ')' => tokens.push(Token::VariationEnd),
+            '$' => {
+                let mut digits = String::new();
+                while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(c);
+                }
+                tokens.push(Token::Nag(digits.parse().ok()));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut symbol = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"[]{}();$".contains(*c)) {
+                    symbol.push(c);
+                }
+                tokens.push(Token::Symbol(symbol));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Starting position of a game: its FEN tag, or the start position of its
+/// variant; the Variant tag's rules apply if it names a known variant, by
+/// our own name or by its Fairy-Stockfish name
+fn start_position(tags: &[(String, String)]) -> Result<Board, FenError> {
+    let tag = |name: &str| tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str());
+    let variant = tag("Variant").and_then(|name| find_external_variant(name, tag("FEN")));
+    match (tag("FEN"), variant) {
+        (Some(fen), Some(variant)) => variant.from_fen(fen),
+        (Some(fen), None) => Board::from_fen(fen),
+        (None, Some(variant)) => Ok(variant.start_position()),
+        (None, None) => Ok(find_variant("chess").expect("chess is registered").start_position()),
+    }
+}
+
+/// Parse every game of a PGN text: tags, starting position, moves, move
+/// comments, NAGs and result; variations are skipped
+/// A comment before the result belongs to the result when the last move already has
+/// one; to_pgn writes an empty "{}" for a last move without one
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut tokens = tokenize(text)?.into_iter().peekable();
+    let mut games = Vec::new();
+
+    while tokens.peek().is_some() {
+        let number = games.len() + 1;
+        let mut tags = Vec::new();
+        while let Some(Token::Tag(name, value)) = tokens.next_if(|token| matches!(token, Token::Tag(..))) {
+            tags.push((name, value));
+        }
+
+        let start = start_position(&tags).map_err(|e| PgnError::BadFen(number, e))?;
+        let mut board = start.clone();
+        let mut game = PgnGame::new(&start);
+        game.tags = tags;
+        let mut ended = false;
+        let mut variation_depth = 0usize;
+        // Whether the last move has had its comment (possibly an empty one)
+        let mut commented = false;
+
+        while let Some(token) = tokens.next_if(|token| !matches!(token, Token::Tag(..))) {
+            match token {
+                Token::Tag(..) => unreachable!(),
+                Token::VariationStart => variation_depth += 1,
+                Token::VariationEnd => variation_depth = variation_depth.saturating_sub(1),
+                _ if variation_depth > 0 => {}
+                Token::Nag(nag) => {
+                    if !game.moves.is_empty() {
+                        game.nags.resize(game.moves.len(), None);
+                        game.nags[game.moves.len() - 1] = nag;
+                    }
+                }
+                Token::Comment(comment) => {
+                    let before_result = matches!(tokens.peek(), Some(Token::Symbol(s)) if RESULTS.contains(&s.as_str()));
+                    game.comments.resize(game.moves.len(), None);
+                    match game.comments.last_mut() {
+                        // A second comment just before the result describes the result,
+                        // as does one in a game without moves
+                        _ if before_result && (commented || game.moves.is_empty()) => {
+                            game.result_comment = Some(comment)
+                        }
+                        Some(Some(previous)) => *previous = format!("{} {}", previous, comment),
+                        Some(last) => *last = Some(comment).filter(|c| !c.is_empty()),
+                        None => {}
+                    }
+                    commented = !game.moves.is_empty();
+                }
+                Token::Symbol(symbol) => {
+                    if RESULTS.contains(&symbol.as_str()) {
+                        game.result = symbol;
+                        ended = true;
+                        break;
+                    }
+                    // Move numbers ("12.", "12...") may be glued to the move
+                    let san = match symbol.rfind('.') {
+                        Some(dot) if symbol.starts_with(|c: char| c.is_ascii_digit()) => &symbol[dot + 1..],
+                        _ => &symbol,
+                    };
+                    if san.is_empty() || san.chars().all(|c| c.is_ascii_digit()) {
+                        continue;
+                    }
+                    let mv = san_to_move(&mut board, san)
+                        .ok_or_else(|| PgnError::BadMove(number, game.moves.len() + 1, san.to_string()))?;
+                    game.moves.push(board.apply_move(mv));
+                    commented = false;
+                }
+            }
+        }
+
+        if !ended {
+            game.result = game.tag("Result").filter(|r| RESULTS.contains(r)).unwrap_or("*").to_string();
+        }
+        if !game.tags.is_empty() || !game.moves.is_empty() {
+            games.push(game);
+        }
+    }
+    Ok(games)
+}
+
+/// Read every game of a PGN file
+pub fn load_pgn(path: &Path) -> Result<Vec<PgnGame>, PgnError> {
+    parse_pgn(&fs::read_to_string(path)?)
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uci::parse_uci_move;
+    use crate::variant::default_variant;
+
+    /// Legal move matching a UCI string
+    fn legal(board: &mut Board, uci: &str) -> Move {
+        let parsed = parse_uci_move(uci).unwrap();
+        board.generate_legal_moves().into_iter().find(|mv| mv.same_as(&parsed)).unwrap()
+    }
+
+    fn san(fen: &str, uci: &str) -> String {
+        let mut board = Board::from_fen(fen).unwrap();
+        let mv = legal(&mut board, uci);
+        move_to_san(&mut board, &mv)
+    }
+
+    #[test]
+    fn test_piece_moves() {
+        assert_eq!(san("8/8/4k3/3r4/8/8/8/3AK3 w - - 0 1", "d1d4"), "Ad4+");
+        assert_eq!(san("8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1", "e1f2"), "Kf2");
+        assert_eq!(san("8/8/4k3/8/8/1r6/8/3AK3 w - - 0 1", "d1b3"), "Axb3+");
+    }
+
+    #[test]
+    fn test_pawn_moves() {
+        assert_eq!(san("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4e5"), "e5");
+        assert_eq!(san("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5"), "exd5");
+        assert_eq!(san("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), "b8=Q+");
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        assert_eq!(san("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "a1d1"), "Rad1");
+        assert_eq!(san("R7/8/8/4k3/8/8/8/R3K3 w - - 0 1", "a1a4"), "R1a4");
+        assert_eq!(san("8/8/8/7k/A7/8/8/A1A1K3 w - - 0 1", "a1b2"), "Aa1b2");
+    }
+
+    #[test]
+    fn test_castling() {
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1"), "O-O");
+        assert_eq!(san("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8"), "O-O-O");
+    }
+
+    #[test]
+    fn test_game_text() {
+        let start = Board::from_fen("8/8/4k3/8/8/8/8/r2AK3 b - - 0 1").unwrap();
+        let mut game = PgnGame::new(&start);
+        let mut board = start.clone();
+        for uci in ["a1d1", "e1d1"] {
+            let mv = legal(&mut board, uci);
+            game.moves.push(board.apply_move(mv));
+        }
+        game.set_tag("White", "A");
+        game.set_tag("White", "B \"quoted\"");
+        game.result = "1-0".to_string();
+        assert_eq!(game.to_pgn(), "[White \"B \\\"quoted\\\"\"]\n\n1... Rxd1+ 2. Kxd1 1-0\n\n");
+    }
+
+    #[test]
+    fn test_check_and_mate_markers() {
+        assert_eq!(san("k7/8/1K6/8/8/8/8/3A4 w - - 0 1", "d1d8"), "Ad8#");
+        assert_eq!(san("k7/8/8/8/8/8/8/3AK3 w - - 0 1", "d1d5"), "Ad5+");
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1"), "O-O-O");
+    }
+
+    #[test]
+    fn test_score_comments() {
+        let time = Duration::from_millis(500);
+        assert_eq!(score_comment(45, 12, time), "+0.45/12 0.50s");
+        assert_eq!(score_comment(-120, 3, time), "-1.20/3 0.50s");
+        assert_eq!(score_comment(0, 1, Duration::ZERO), "+0.00/1 0.00s");
+        assert_eq!(score_comment(CHECKMATE_SCORE - 5, 9, time), "+M5/9 0.50s");
+        assert_eq!(score_comment(-CHECKMATE_SCORE + 4, 9, time), "-M4/9 0.50s");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(date_from_days(0), "1970.01.01");
+        assert_eq!(date_from_days(11_016), "2000.02.29");
+        assert_eq!(date_from_days(20_742), "2026.10.16");
+        assert_eq!(today().len(), 10);
+    }
+
+    #[test]
+    fn test_recorded_game() {
+        let start = Board::from_fen("k7/8/1K6/8/8/8/8/3A4 w - - 0 1").unwrap();
+        let mut game = PgnGame::with_tags(&start, default_variant());
+        let mut board = start.clone();
+
+        let wrong = legal(&mut board, "d1d2");
+        game.push(board.apply_move(wrong), None);
+        board.unmake_move(game.pop().unwrap());
+        let mate = legal(&mut board, "d1d8");
+        game.push(board.apply_move(mate), Some(score_comment(CHECKMATE_SCORE - 1, 2, Duration::ZERO)));
+        game.finish("1-0", "normal", Some("White mates"));
+
+        let pgn = game.to_pgn();
+        let names: Vec<&str> = game.tags.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Event", "Site", "Date", "Round", "White", "Black", "Result", "FEN", "SetUp", "Variant", "PlyCount", "Termination"]
+        );
+        assert!(pgn.contains("[FEN \"k7/8/1K6/8/8/8/8/3A4 w - - 0 1\"]\n[SetUp \"1\"]\n[Variant \"amazon\"]\n"));
+        assert!(pgn.contains("[PlyCount \"1\"]"));
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.ends_with("\n1. Ad8# {+M1/2 0.00s} {White mates} 1-0\n\n"));
+    }
+
+    #[test]
+    fn test_variant_tag_uses_fairy_stockfish_names() {
+        let full = crate::variant::find_variant("amazon-chess").unwrap();
+        let game = PgnGame::with_tags(&full.start_position(), full);
+        assert_eq!(game.tag("Variant"), Some("amazon"));
+
+        // Read back, the FEN decides between the Amazon endgame and the full board
+        let read = parse_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(read[0].start.to_fen(), full.start_fen);
+        assert_eq!(read[0].start.promotion_types(), full.promotion_types);
+
+        let chess = crate::variant::find_variant("chess").unwrap();
+        assert_eq!(PgnGame::with_tags(&chess.start_position(), chess).tag("Variant"), Some("chess"));
+    }
+
+    #[test]
+    fn test_annotations() {
+        let start = Board::from_fen("1k6/8/1K6/8/8/8/8/3A4 b - - 0 1").unwrap();
+        let mut game = PgnGame::new(&start);
+        game.set_tag("FEN", &start.to_fen());
+        let mut board = start.clone();
+        for uci in ["b8a8", "b6c5", "a8b7"] {
+            let mv = legal(&mut board, uci);
+            game.push(board.apply_move(mv), None);
+        }
+        // White missed the mate after 1... Ka8
+        let mut before = start.clone();
+        before.apply_move(game.moves[0]);
+        let mate = legal(&mut before, "d1d8");
+        game.nags = vec![None, Some(2)];
+        game.variations = vec![None, Some(vec![mate])];
+        game.comments = vec![None, Some("+0.50".to_string())];
+
+        assert!(game.to_pgn().ends_with("\n\n1... Ka8 2. Kc5 $2 {+0.50} (2. Ad8#) 2... Kb7 *\n\n"));
+        let read = parse_pgn(&game.to_pgn()).unwrap();
+        assert_eq!(read[0].nags, game.nags);
+        assert_eq!(read[0].moves.len(), 3);
+    }
+
+    #[test]
+    fn test_long_games_wrap() {
+        let start = Board::setup_amazon_vs_rook();
+        let mut game = PgnGame::new(&start);
+        let mut board = start.clone();
+        for i in 0..60 {
+            let moves = board.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            game.moves.push(board.apply_move(moves[i % moves.len()]));
+        }
+        assert!(game.to_pgn().lines().all(|line| line.len() <= LINE_WIDTH));
+    }
+
+    #[test]
+    fn test_san_to_move() {
+        let parse = |fen: &str, san: &str| {
+            let mut board = Board::from_fen(fen).unwrap();
+            san_to_move(&mut board, san).map(|mv| crate::uci::move_to_uci(&mv))
+        };
+        assert_eq!(parse("8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1", "Kd2").as_deref(), Some("e1d2"));
+        assert_eq!(parse("8/8/4k3/3r4/8/8/8/3AK3 w - - 0 1", "Axd5+").as_deref(), Some("d1d5"));
+        assert_eq!(parse("8/8/4k3/3r4/8/8/8/3AK3 w - - 0 1", "Ad5!?").as_deref(), Some("d1d5"));
+        assert_eq!(parse("k7/8/1K6/8/8/8/8/3A4 w - - 0 1", "Ad8#").as_deref(), Some("d1d8"));
+        assert_eq!(parse("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8=Q+").as_deref(), Some("b7b8q"));
+        assert_eq!(parse("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8R").as_deref(), Some("b7b8r"));
+        assert_eq!(parse("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "exd5").as_deref(), Some("e4d5"));
+        assert_eq!(parse("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O").as_deref(), Some("e1g1"));
+        assert_eq!(parse("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "0-0-0").as_deref(), Some("e8c8"));
+        assert_eq!(parse("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "Kg1"), None);
+        assert_eq!(parse("8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1", "Ke9"), None);
+        assert_eq!(parse("8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1", "Qd2"), None);
+    }
+
+    #[test]
+    fn test_san_disambiguation() {
+        let parse = |fen: &str, san: &str| {
+            let mut board = Board::from_fen(fen).unwrap();
+            san_to_move(&mut board, san).map(|mv| crate::uci::move_to_uci(&mv))
+        };
+        let rooks = "4k3/3r1r2/8/3r1r2/8/8/8/3AKA2 b - - 0 1";
+        assert_eq!(parse(rooks, "Rfe5").as_deref(), Some("f5e5"));
+        assert_eq!(parse(rooks, "Rde7").as_deref(), Some("d7e7"));
+        assert_eq!(parse(rooks, "R7d6").as_deref(), Some("d7d6"));
+        assert_eq!(parse(rooks, "Re7"), None);
+        assert_eq!(parse(rooks, "Rd6"), None);
+        let amazons = "8/8/8/7k/A7/8/8/A1A1K3 w - - 0 1";
+        assert_eq!(parse(amazons, "Aa1b2").as_deref(), Some("a1b2"));
+        assert_eq!(parse(amazons, "Ab2"), None);
+        // Redundant disambiguation is accepted
+        assert_eq!(parse("8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1", "Ke1d2").as_deref(), Some("e1d2"));
+    }
+
+    const CUTECHESS_GAME: &str = r#"[Event "?"]
+[White "Amazon-Engine"]
+[Black "Fairy-Stockfish"]
+[Result "0-1"]
+[FEN "8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1"]
+[SetUp "1"]
+[Variant "amazon"]
+
+1. Kd2 Rd5+ {0.00/77 1.7s} 2. Ke3 Rxd1 {+M29/35 0.91s} 3. Ke4 Rd8 {+M25/36 1.0s}
+4. Kf4 Rd4+ {+M23/39 1.0s} 5. Ke3 Ke5 {+M21/38 1.1s} 6. Kf3 Rd3+ {+M19/40 1.6s}
+7. Kg4 Rb3 {+M15/44 1.1s} 8. Kg5 Rg3+ {+M13/50 1.3s} 9. Kh6 Kf5 {+M7/245 0.36s}
+10. Kh7 Kf6 {+M5/245 0.007s} 11. Kh6 Rh3# {+M1/245 0.001s, Black mates} 0-1
+
+[Event "?"]
+[Result "1-0"]
+[FEN "k7/8/1K6/8/8/8/8/3A4 w - - 0 1"]
+
+1. Ad8# 1-0
+"#;
+
+    #[test]
+    fn test_parse_cutechess_games() {
+        let games = parse_pgn(CUTECHESS_GAME).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let game = &games[0];
+        assert_eq!(game.tag("Black"), Some("Fairy-Stockfish"));
+        assert_eq!(game.start.to_fen(), "8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1");
+        assert_eq!(game.moves.len(), 22);
+        assert_eq!(game.result, "0-1");
+        assert_eq!(game.comments[1].as_deref(), Some("0.00/77 1.7s"));
+        assert_eq!(game.comments[0], None);
+        assert_eq!(game.comments[21].as_deref(), Some("+M1/245 0.001s, Black mates"));
+
+        let mut board = game.start.clone();
+        for mv in &game.moves {
+            board.apply_move(*mv);
+        }
+        assert!(board.is_checkmate(Color::White));
+        assert_eq!(games[1].moves.len(), 1);
+        assert_eq!(games[1].result, "1-0");
+    }
+
+    #[test]
+    fn test_written_games_read_back() {
+        let start = Board::setup_amazon_vs_rook();
+        let mut game = PgnGame::with_tags(&start, default_variant());
+        let mut board = start.clone();
+        for i in 0..40 {
+            let moves = board.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            let comment = (i % 3 == 0).then(|| score_comment(i * 7, 4, Duration::from_millis(20)));
+            game.push(board.apply_move(moves[(i as usize * 7) % moves.len()]), comment);
+        }
+        game.finish("*", "unterminated", Some("Stopped"));
+
+        let text = game.to_pgn();
+        let read = parse_pgn(&format!("{}{}", text, text)).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].tags, game.tags);
+        assert_eq!(read[0].to_pgn(), text);
+        assert!(read[1].moves.iter().zip(&game.moves).all(|(a, b)| a.same_as(b)));
+    }
+
+    #[test]
+    fn test_result_comment_after_uncommented_move() {
+        let start = Board::from_fen("3rk3/8/4K3/8/8/8/8/3A4 b - - 0 1").unwrap();
+        let mut game = PgnGame::with_tags(&start, default_variant());
+        let mut board = start.clone();
+        for uci in ["d8d1", "e6f6"] {
+            let mv = legal(&mut board, uci);
+            game.push(board.apply_move(mv), None);
+        }
+        game.finish("*", "unterminated", Some("Stopped"));
+        let text = game.to_pgn();
+        assert!(text.contains("2. Kf6 {} {Stopped} *"), "{}", text);
+
+        let read = parse_pgn(&text).unwrap();
+        assert_eq!(read[0].comments, [None, None]);
+        assert_eq!(read[0].result_comment.as_deref(), Some("Stopped"));
+        assert_eq!(read[0].to_pgn(), text);
+
+        // Without moves the comment is the result's
+        let read = parse_pgn("[Result \"*\"]\n\n{Abandoned} *").unwrap();
+        assert_eq!(read[0].result_comment.as_deref(), Some("Abandoned"));
+    }
+
+    #[test]
+    fn test_skipped_movetext() {
+        let text = "; a comment line\n% escaped\n[Result \"*\"]\n[FEN \"8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1\"]\n\n\
+                    1.Kd2 $1 (1. Kf2 Rf5+ (1... Re4)) 1...Rd5+ $4 2. Ke3 {+1.00/4\n0.10s} {Abandoned} *";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games.len(), 1);
+        let uci: Vec<String> = games[0].moves.iter().map(crate::uci::move_to_uci).collect();
+        assert_eq!(uci, ["e1d2", "e5d5", "d2e3"]);
+        assert_eq!(games[0].comments[2].as_deref(), Some("+1.00/4 0.10s"));
+        assert_eq!(games[0].result_comment.as_deref(), Some("Abandoned"));
+        assert_eq!(games[0].nags, [Some(1), Some(4)]);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert!(matches!(parse_pgn("[Event \"?\"]\n[White unquoted]\n"), Err(PgnError::BadTag(2))));
+        assert!(matches!(parse_pgn("[FEN \"8/8/8\"]\n1. e4 *"), Err(PgnError::BadFen(1, _))));
+        let illegal = "[FEN \"8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1\"]\n\n1. Kd2 Rd5+ 2. Kd3 *";
+        match parse_pgn(illegal) {
+            Err(PgnError::BadMove(1, 3, san)) => assert_eq!(san, "Kd3"),
+            _ => panic!("expected an illegal move"),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+/// Actress (Queen + Knight + Camel) move generation
+/// The piece this project was started for: an Amazon that can also
+/// make the Camel's long (3,1) leap
+use crate::board::{Board, PieceType, Square};
+
+pub struct ActressMoves;
+
+impl ActressMoves {
+    /// Generate all pseudo-legal moves for an Actress
+    /// Actress combines: Queen (sliding) + Knight (2,1 jump) + Camel (3,1 jump), Betza "QNC"
+    pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
+        PieceType::Actress.movement().generate_moves(board, from)
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece};
+
+    /// Camel destinations from d4 (row 4, col 3)
+    const CAMEL_FROM_D4: [Square; 8] = [(1, 2), (1, 4), (7, 2), (7, 4), (3, 0), (3, 6), (5, 0), (5, 6)];
+
+    #[test]
+    fn test_actress_on_empty_board_center() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Actress, Color::White)));
+
+        let moves = ActressMoves::generate_moves(&board, (4, 3));
+
+        // Queen 27 + Knight 8 + Camel 8 from d4
+        assert_eq!(moves.len(), 43);
+        for square in CAMEL_FROM_D4 {
+            assert!(moves.contains(&square), "Camel leap to {:?} should be possible", square);
+        }
+        assert!(moves.contains(&(6, 2)), "Knight move to c2 should be possible");
+        assert!(moves.contains(&(0, 7)), "Queen move to h8 should be possible");
+    }
+
+    #[test]
+    fn test_actress_camel_from_corner() {
+        let mut board = Board::new();
+        board.set_piece((7, 0), Some(Piece::new(PieceType::Actress, Color::White)));
+
+        let moves = ActressMoves::generate_moves(&board, (7, 0));
+
+        // From a1 the Camel only reaches b4 and d2
+        assert!(moves.contains(&(4, 1)), "Camel leap to b4");
+        assert!(moves.contains(&(6, 3)), "Camel leap to d2");
+        // Queen 21 + Knight 2 + Camel 2
+        assert_eq!(moves.len(), 25);
+    }
+
+    #[test]
+    fn test_actress_camel_jumps_over_blockers() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Actress, Color::White)));
+        // Surround d4 with own pieces: all slides are blocked
+        for (row, col) in [(3, 2), (3, 3), (3, 4), (4, 2), (4, 4), (5, 2), (5, 3), (5, 4)] {
+            board.set_piece((row, col), Some(Piece::new(PieceType::Rook, Color::White)));
+        }
+
+        let moves = ActressMoves::generate_moves(&board, (4, 3));
+
+        // Only the 8 Knight and 8 Camel leaps remain
+        assert_eq!(moves.len(), 16);
+        for square in CAMEL_FROM_D4 {
+            assert!(moves.contains(&square));
+        }
+    }
+
+    #[test]
+    fn test_actress_camel_capture_and_own_piece() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Actress, Color::White)));
+        board.set_piece((1, 2), Some(Piece::new(PieceType::Rook, Color::Black)));
+        board.set_piece((1, 4), Some(Piece::new(PieceType::King, Color::White)));
+
+        let moves = ActressMoves::generate_moves(&board, (4, 3));
+
+        assert!(moves.contains(&(1, 2)), "Camel can capture an enemy piece");
+        assert!(!moves.contains(&(1, 4)), "Camel cannot land on an own piece");
+    }
+
+    #[test]
+    fn test_actress_no_piece_returns_empty() {
+        let board = Board::new();
+        let moves = ActressMoves::generate_moves(&board, (4, 3));
+        assert!(moves.is_empty(), "No piece at square should return empty moves");
+    }
+}
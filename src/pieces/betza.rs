@@ -0,0 +1,379 @@
+/// Betza notation - data-driven piece movement
+/// A piece is described by a string of atoms, e.g. "QN" (Amazon) or "QNC" (Actress),
+/// and one generic generator handles every leaper, rider and compound piece.
+///
+/// Atoms (one leap, in all 8 symmetric directions):
+///   W (1,0)  F (1,1)  D (2,0)  N (2,1)  A (2,2)  H (3,0)  C (3,1)  Z (3,2)  G (3,3)
+/// Shorthands:
+///   K = WF (King), R = WW (Rook), B = FF (Bishop), Q = RB (Queen)
+/// Riders:
+///   a doubled atom slides any distance ("NN" = Nightrider),
+///   a number limits the range ("W2" = up to two squares, "N0" = unlimited)
+/// Modifiers (lowercase, before an atom):
+///   f / b: only the forward / backward directions (seen from the piece's side)
+///   m / c: move only / capture only ("fmWfcF" = Pawn without its double step)
+use crate::board::{Board, Color, Square};
+
+/// Longest possible slide on an 8x8 board
+pub const UNLIMITED: u8 = 7;
+
+/// One movement direction and how far the piece may travel along it
+/// Directions are given for White (forward = decreasing row); Black mirrors them
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Ray {
+    pub dr: i8,
+    pub dc: i8,
+    /// Number of steps: 1 for a leaper, UNLIMITED for a full slider
+    pub range: u8,
+    /// The piece may move to an empty square along this ray
+    pub can_move: bool,
+    /// The piece may capture along this ray
+    pub can_capture: bool,
+}
+
+impl Ray {
+    /// Single leap, no sliding
+    pub fn is_leap(&self) -> bool {
+        self.range == 1
+    }
+
+    /// The ray as seen by a piece of the given color
+    pub fn oriented(&self, color: Color) -> Ray {
+        match color {
+            Color::White => *self,
+            Color::Black => Ray { dr: -self.dr, ..*self },
+        }
+    }
+}
+
+/// Why a Betza string could not be parsed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BetzaError {
+    /// The string has no atoms
+    Empty,
+    /// A letter that is not a known atom or shorthand
+    UnknownAtom(char),
+    /// Modifiers at the end of the string, with no atom to apply to
+    DanglingModifier,
+}
+
+impl std::fmt::Display for BetzaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BetzaError::Empty => write!(f, "empty Betza string"),
+            BetzaError::UnknownAtom(c) => write!(f, "unknown Betza atom '{}'", c),
+            BetzaError::DanglingModifier => write!(f, "Betza modifier without an atom"),
+        }
+    }
+}
+
+impl std::error::Error for BetzaError {}
+
+/// The complete movement of a piece: every ray it can move along
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Movement {
+    rays: Vec<Ray>,
+}
+
+/// Base leaps for an atom or shorthand, and whether it slides by default
+fn atom(c: char) -> Option<(&'static [(i8, i8)], bool)> {
+    let atom: (&'static [(i8, i8)], bool) = match c {
+        'W' => (&[(1, 0)], false),
+        'F' => (&[(1, 1)], false),
+        'D' => (&[(2, 0)], false),
+        'N' => (&[(2, 1)], false),
+        'A' => (&[(2, 2)], false),
+        'H' => (&[(3, 0)], false),
+        'C' => (&[(3, 1)], false),
+        'Z' => (&[(3, 2)], false),
+        'G' => (&[(3, 3)], false),
+        'K' => (&[(1, 0), (1, 1)], false),
+        'R' => (&[(1, 0)], true),
+        'B' => (&[(1, 1)], true),
+        'Q' => (&[(1, 0), (1, 1)], true),
+        _ => return None,
+    };
+    Some(atom)
+}
+
+/// Whether a letter is a shorthand (and so cannot be doubled into a rider)
+fn is_shorthand(c: char) -> bool {
+    matches!(c, 'K' | 'R' | 'B' | 'Q')
+}
+
+impl Movement {
+    /// Parse a Betza string such as "QN" or "WFC"
+    pub fn parse(betza: &str) -> Result<Self, BetzaError> {
+        let chars: Vec<char> = betza.chars().filter(|c| !c.is_whitespace()).collect();
+        if chars.is_empty() {
+            return Err(BetzaError::Empty);
+        }
+
+        let mut movement = Movement { rays: Vec::new() };
+        let mut i = 0;
+        while i < chars.len() {
+            // Modifiers apply to the next atom only
+            let modifiers: String = chars[i..].iter().take_while(|c| c.is_ascii_lowercase()).collect();
+            if let Some(unknown) = modifiers.chars().find(|c| !matches!(c, 'f' | 'b' | 'm' | 'c')) {
+                return Err(BetzaError::UnknownAtom(unknown));
+            }
+            i += modifiers.len();
+            let forward = modifiers.contains('f');
+            let backward = modifiers.contains('b');
+            let move_only = modifiers.contains('m');
+            let capture_only = modifiers.contains('c');
+
+            let c = *chars.get(i).ok_or(BetzaError::DanglingModifier)?;
+            let (leaps, slides) = atom(c).ok_or(BetzaError::UnknownAtom(c))?;
+            i += 1;
+
+            let mut range = if slides { UNLIMITED } else { 1 };
+
+            // Doubled atom: rider ("WW", "NN")
+            if !is_shorthand(c) && chars.get(i) == Some(&c) {
+                range = UNLIMITED;
+                i += 1;
+            }
+
+            // Numeric suffix: range limit, 0 = unlimited
+            let digits: String = chars[i..].iter().take_while(|d| d.is_ascii_digit()).collect();
+            if !digits.is_empty() {
+                i += digits.len();
+                let n: u32 = digits.parse().unwrap_or(0);
+                range = if n == 0 { UNLIMITED } else { n.min(UNLIMITED as u32) as u8 };
+            }
+
+            for &(a, b) in leaps {
+                for (dr, dc) in symmetric(a, b) {
+                    // Direction filter: forward is towards rank 8 for White
+                    if (forward || backward) && !((forward && dr < 0) || (backward && dr > 0)) {
+                        continue;
+                    }
+                    movement.add(Ray {
+                        dr,
+                        dc,
+                        range,
+                        can_move: move_only || !capture_only,
+                        can_capture: capture_only || !move_only,
+                    });
+                }
+            }
+        }
+
+        Ok(movement)
+    }
+
+    /// Add a ray, keeping the longer range if the same kind of ray already exists
+    fn add(&mut self, ray: Ray) {
+        let same = |r: &&mut Ray| {
+            r.dr == ray.dr && r.dc == ray.dc && r.can_move == ray.can_move && r.can_capture == ray.can_capture
+        };
+        match self.rays.iter_mut().find(same) {
+            Some(existing) => existing.range = existing.range.max(ray.range),
+            None => self.rays.push(ray),
+        }
+    }
+
+    /// All rays of this piece
+    pub fn rays(&self) -> &[Ray] {
+        &self.rays
+    }
+
+    /// Generate all pseudo-legal destination squares for the piece on `from`
+    /// The piece's color is read from the board; own pieces block, enemy pieces can be captured
+    pub fn generate_moves(&self, board: &Board, from: Square) -> Vec<Square> {
+        let mut moves = Vec::with_capacity(self.rays.len() * 2);
+
+        let our_color = match board.get_piece(from) {
+            Some(p) => p.color,
+            None => return moves, // No piece at 'from', return empty
+        };
+
+        for ray in &self.rays {
+            let ray = ray.oriented(our_color);
+            let mut row = from.0 as i8;
+            let mut col = from.1 as i8;
+            for _ in 0..ray.range {
+                row += ray.dr;
+                col += ray.dc;
+                if !(0..8).contains(&row) || !(0..8).contains(&col) {
+                    break; // Off the board, stop this direction
+                }
+
+                let to = (row as u8, col as u8);
+                match board.get_piece(to) {
+                    None => {
+                        if ray.can_move {
+                            moves.push(to);
+                        }
+                    }
+                    Some(p) => {
+                        if p.color != our_color && ray.can_capture {
+                            moves.push(to); // Enemy piece - can capture
+                        }
+                        break; // Blocked
+                    }
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Whether a piece of `color` on `from` attacks `target` (whatever stands on the target)
+    /// Move-only rays (the Pawn's push) do not attack
+    pub fn attacks(&self, board: &Board, color: Color, from: Square, target: Square) -> bool {
+        for ray in self.rays.iter().filter(|r| r.can_capture) {
+            let ray = ray.oriented(color);
+            let mut row = from.0 as i8;
+            let mut col = from.1 as i8;
+            for _ in 0..ray.range {
+                row += ray.dr;
+                col += ray.dc;
+                if !(0..8).contains(&row) || !(0..8).contains(&col) {
+                    break;
+                }
+
+                let to = (row as u8, col as u8);
+                if to == target {
+                    return true;
+                }
+                if board.get_piece(to).is_some() {
+                    break;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// The (up to) 8 symmetric images of a leap
+fn symmetric(a: i8, b: i8) -> Vec<(i8, i8)> {
+    let mut result = Vec::with_capacity(8);
+    for (x, y) in [(a, b), (b, a)] {
+        for (sx, sy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let offset = (x * sx, y * sy);
+            if !result.contains(&offset) {
+                result.push(offset);
+            }
+        }
+    }
+    result
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece, PieceType};
+
+    fn sorted(mut squares: Vec<Square>) -> Vec<Square> {
+        squares.sort();
+        squares
+    }
+
+    #[test]
+    fn test_parse_atoms_and_shorthands() {
+        assert_eq!(Movement::parse("W").unwrap().rays().len(), 4);
+        assert_eq!(Movement::parse("N").unwrap().rays().len(), 8);
+        assert_eq!(Movement::parse("K").unwrap().rays().len(), 8);
+        assert!(Movement::parse("K").unwrap().rays().iter().all(Ray::is_leap));
+        assert!(Movement::parse("R").unwrap().rays().iter().all(|r| r.range == UNLIMITED));
+        assert_eq!(Movement::parse("QN").unwrap().rays().len(), 16);
+        assert_eq!(Movement::parse("QNC").unwrap().rays().len(), 24);
+    }
+
+    #[test]
+    fn test_parse_riders_and_ranges() {
+        assert_eq!(Movement::parse("WW").unwrap(), Movement::parse("R").unwrap());
+        assert_eq!(Movement::parse("W0").unwrap(), Movement::parse("R").unwrap());
+        assert!(Movement::parse("NN").unwrap().rays().iter().all(|r| r.range == UNLIMITED));
+        assert!(Movement::parse("F2").unwrap().rays().iter().all(|r| r.range == 2));
+    }
+
+    #[test]
+    fn test_parse_merges_duplicate_directions() {
+        // The King's W leaps are covered by the Rook's W slides
+        assert_eq!(Movement::parse("RK").unwrap(), Movement::parse("RF").unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Movement::parse(""), Err(BetzaError::Empty));
+        assert_eq!(Movement::parse("QX"), Err(BetzaError::UnknownAtom('X')));
+    }
+
+    #[test]
+    fn test_compound_equals_parts() {
+        // Amazon moves = Queen moves + Knight moves, on a board with blockers
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
+        board.set_piece((2, 3), Some(Piece::new(PieceType::Rook, Color::Black)));
+        board.set_piece((4, 5), Some(Piece::new(PieceType::King, Color::White)));
+
+        let amazon = Movement::parse("QN").unwrap().generate_moves(&board, (4, 3));
+        let mut parts = Movement::parse("Q").unwrap().generate_moves(&board, (4, 3));
+        parts.extend(Movement::parse("N").unwrap().generate_moves(&board, (4, 3)));
+        assert_eq!(sorted(amazon), sorted(parts));
+    }
+
+    #[test]
+    fn test_limited_range_rider() {
+        let mut board = Board::new();
+        board.set_piece((7, 0), Some(Piece::new(PieceType::Rook, Color::White)));
+        let moves = Movement::parse("W2").unwrap().generate_moves(&board, (7, 0));
+        assert_eq!(sorted(moves), vec![(5, 0), (6, 0), (7, 1), (7, 2)]);
+    }
+
+    #[test]
+    fn test_nightrider_is_blocked() {
+        let mut board = Board::new();
+        board.set_piece((7, 0), Some(Piece::new(PieceType::Rook, Color::White)));
+        // b3 blocks the a1-b3-c5-d7 line
+        board.set_piece((5, 1), Some(Piece::new(PieceType::King, Color::White)));
+        let movement = Movement::parse("NN").unwrap();
+        let moves = movement.generate_moves(&board, (7, 0));
+        assert!(!moves.contains(&(5, 1)));
+        assert!(!moves.contains(&(3, 2)));
+        // The other line (a1-c2-e3-g4) is open
+        assert!(moves.contains(&(4, 6)));
+        assert!(movement.attacks(&board, Color::White, (7, 0), (5, 1)));
+        assert!(!movement.attacks(&board, Color::White, (7, 0), (3, 2)));
+    }
+
+    #[test]
+    fn test_parse_modifiers() {
+        let pawn = Movement::parse("fmWfcF").unwrap();
+        assert_eq!(pawn.rays().len(), 3);
+        let push = pawn.rays().iter().find(|r| r.dc == 0).unwrap();
+        assert_eq!((push.dr, push.can_move, push.can_capture), (-1, true, false));
+        assert!(pawn.rays().iter().filter(|r| r.dc != 0).all(|r| r.dr == -1 && !r.can_move));
+
+        assert_eq!(Movement::parse("bN").unwrap().rays().len(), 4);
+        assert_eq!(Movement::parse("fmW").unwrap().rays().len(), 1);
+        assert_eq!(Movement::parse("fm"), Err(BetzaError::DanglingModifier));
+        assert_eq!(Movement::parse("xW"), Err(BetzaError::UnknownAtom('x')));
+    }
+
+    #[test]
+    fn test_pawn_pattern_is_mirrored_for_black() {
+        let pawn = Movement::parse("fmWfcF").unwrap();
+        let mut board = Board::new();
+        board.set_piece((3, 3), Some(Piece::new(PieceType::Rook, Color::Black)));
+        board.set_piece((3, 4), Some(Piece::new(PieceType::Rook, Color::White)));
+        board.set_piece((4, 4), Some(Piece::new(PieceType::Rook, Color::White)));
+        board.set_piece((5, 3), Some(Piece::new(PieceType::Rook, Color::White)));
+
+        // A white "pawn" on e4 is blocked on e5 and captures d5
+        assert_eq!(pawn.generate_moves(&board, (4, 4)), vec![(3, 3)]);
+        assert!(pawn.attacks(&board, Color::White, (4, 4), (3, 5)));
+        assert!(!pawn.attacks(&board, Color::White, (4, 4), (5, 5)));
+
+        // A black one on d5 pushes to d4 and captures e4
+        assert_eq!(sorted(pawn.generate_moves(&board, (3, 3))), vec![(4, 3), (4, 4)]);
+        assert!(!pawn.attacks(&board, Color::Black, (3, 3), (4, 3)), "Pushes do not attack");
+    }
+}
@@ -0,0 +1,46 @@
+/// Bishop move generation
+/// Slides diagonally
+use crate::board::{Board, PieceType, Square};
+
+pub struct BishopMoves;
+
+impl BishopMoves {
+    /// Generate all pseudo-legal moves for a Bishop, Betza "B"
+    pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
+        PieceType::Bishop.movement().generate_moves(board, from)
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece};
+
+    #[test]
+    fn test_bishop_center() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Bishop, Color::White)));
+
+        let moves = BishopMoves::generate_moves(&board, (4, 3));
+        assert_eq!(moves.len(), 13, "Bishop on d4 has 13 moves");
+        assert!(moves.iter().all(|&(r, c)| (r as i8 - 4).abs() == (c as i8 - 3).abs()));
+    }
+
+    #[test]
+    fn test_bishop_blocked_and_capture() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Bishop, Color::White)));
+        board.set_piece((2, 5), Some(Piece::new(PieceType::Rook, Color::Black))); // f6
+        board.set_piece((6, 1), Some(Piece::new(PieceType::Rook, Color::White))); // b2
+
+        let moves = BishopMoves::generate_moves(&board, (4, 3));
+        assert!(moves.contains(&(2, 5)), "Should capture on f6");
+        assert!(!moves.contains(&(1, 6)), "Should not slide past f6");
+        assert!(moves.contains(&(5, 2)), "Should reach c3");
+        assert!(!moves.contains(&(6, 1)), "Should not capture own piece on b2");
+    }
+}
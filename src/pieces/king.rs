@@ -1,51 +1,15 @@
 /// Here we define associated movements, captures or 
 /// other traits with this piece, the almighty King !!
-use crate::board::{Board, Square};
+use crate::board::{Board, PieceType, Square};
 
 /// KingMoves is an Unit Struct, namespace to group related functions together.
 pub struct KingMoves;
 
 impl KingMoves {
     // Functions are grouped under KingMoves
+    // One step in any of the 8 directions, Betza "K"
     pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
-        let mut moves = Vec::new();
-        
-        // Get the color of the piece that's moving
-        let piece = board.get_piece(from);
-        let our_color = match piece {
-            Some(p) => p.color,
-            None => return moves, // No piece at 'from', return empty
-        };
-
-        // King's 8 directions
-        let directions: [(i8, i8); 8] = [
-            (-1, -1), (-1, 0), (-1, 1),
-            ( 0, -1),          ( 0, 1),
-            ( 1, -1), ( 1, 0), ( 1, 1),
-        ];
-
-        for (dr, dc) in directions {
-            let new_row = from.0 as i8 + dr;
-            let new_col = from.1 as i8 + dc;
-
-            // Check 1: Is the square on the board?
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                let to = (new_row as u8, new_col as u8);
-
-                // Check 2: Is the square occupied by our own pieces?
-                match board.get_piece(to) {
-                    None => moves.push(to), // Empty square
-                    Some(p) => {
-                        if p.color != our_color {
-                            moves.push(to); // Enemy pieces can be captured
-                        }
-                        // Don't add when own piece
-                    }
-                }
-            }
-        }
-
-        moves // Return all valid squares
+        PieceType::King.movement().generate_moves(board, from)
     }
 }
 
@@ -56,7 +20,7 @@ impl KingMoves {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::{Color, Piece, PieceType};
+    use crate::board::{Color, Piece};
 
     #[test]
     fn test_king_moves_center() {
@@ -0,0 +1,52 @@
+/// Knight move generation
+/// Jumps in an L-shape (2,1), ignoring pieces in between
+use crate::board::{Board, PieceType, Square};
+
+pub struct KnightMoves;
+
+impl KnightMoves {
+    /// Generate all pseudo-legal moves for a Knight, Betza "N"
+    pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
+        PieceType::Knight.movement().generate_moves(board, from)
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece};
+
+    #[test]
+    fn test_knight_center_and_corner() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Knight, Color::White)));
+        board.set_piece((7, 7), Some(Piece::new(PieceType::Knight, Color::White)));
+
+        assert_eq!(KnightMoves::generate_moves(&board, (4, 3)).len(), 8, "Knight on d4 has 8 moves");
+        let corner = KnightMoves::generate_moves(&board, (7, 7));
+        assert_eq!(corner.len(), 2, "Knight on h1 has 2 moves");
+        assert!(corner.contains(&(5, 6)) && corner.contains(&(6, 5)));
+    }
+
+    #[test]
+    fn test_knight_jumps_over_pieces() {
+        let mut board = Board::new();
+        board.set_piece((7, 6), Some(Piece::new(PieceType::Knight, Color::White)));
+        // Surround g1 with own pieces
+        for square in [(6, 5), (6, 6), (6, 7), (7, 5), (7, 7)] {
+            board.set_piece(square, Some(Piece::new(PieceType::Rook, Color::White)));
+        }
+        // Own piece on f3 blocks that target, enemy on h3 can be captured
+        board.set_piece((5, 5), Some(Piece::new(PieceType::Rook, Color::White)));
+        board.set_piece((5, 7), Some(Piece::new(PieceType::Rook, Color::Black)));
+
+        let moves = KnightMoves::generate_moves(&board, (7, 6));
+        assert!(moves.contains(&(5, 7)), "Should capture on h3");
+        assert!(!moves.contains(&(5, 5)), "Should not capture own piece on f3");
+        assert!(moves.contains(&(6, 4)), "Should jump to e2");
+    }
+}
@@ -1,4 +1,10 @@
 // Piece movement modules
+pub mod actress;
 pub mod amazon;
+pub mod betza;
+pub mod bishop;
 pub mod king;
+pub mod knight;
+pub mod pawn;
+pub mod queen;
 pub mod rook;
@@ -0,0 +1,136 @@
+/// Pawn move generation
+/// Single steps and diagonal captures come from the Betza definition ("fmWfcF");
+/// the double step from the starting rank and en passant are added here. Promotions are
+/// expanded by the board when the destination squares are turned into moves.
+use crate::board::{Board, Color, PieceType, Square};
+
+pub struct PawnMoves;
+
+impl PawnMoves {
+    /// Generate all pseudo-legal destination squares for a Pawn
+    pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
+        let mut moves = PieceType::Pawn.movement().generate_moves(board, from);
+
+        let color = match board.get_piece(from) {
+            Some(p) => p.color,
+            None => return moves,
+        };
+
+        // Double step: both squares in front must be empty
+        if let Some(to) = Self::double_step(from, color) {
+            let middle = ((from.0 as i8 + Self::forward(color)) as u8, from.1);
+            if board.get_piece(middle).is_none() && board.get_piece(to).is_none() {
+                moves.push(to);
+            }
+        }
+
+        // En passant: capture onto the empty square the enemy pawn skipped
+        if let Some(target) = board.en_passant() {
+            if PieceType::Pawn.movement().attacks(board, color, from, target) {
+                moves.push(target);
+            }
+        }
+
+        moves
+    }
+
+    /// Row direction the pawns of a color move in
+    pub fn forward(color: Color) -> i8 {
+        match color {
+            Color::White => -1,
+            Color::Black => 1,
+        }
+    }
+
+    /// Row the pawns of a color start on
+    pub fn start_row(color: Color) -> u8 {
+        match color {
+            Color::White => 6,
+            Color::Black => 1,
+        }
+    }
+
+    /// Row on which the pawns of a color promote
+    pub fn promotion_row(color: Color) -> u8 {
+        match color {
+            Color::White => 0,
+            Color::Black => 7,
+        }
+    }
+
+    /// Destination of the double step, if the pawn is still on its starting row
+    pub fn double_step(from: Square, color: Color) -> Option<Square> {
+        if from.0 == Self::start_row(color) {
+            Some(((from.0 as i8 + 2 * Self::forward(color)) as u8, from.1))
+        } else {
+            None
+        }
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Piece;
+
+    fn sorted(mut squares: Vec<Square>) -> Vec<Square> {
+        squares.sort();
+        squares
+    }
+
+    #[test]
+    fn test_pawn_single_and_double_step() {
+        let mut board = Board::new();
+        board.set_piece((6, 4), Some(Piece::new(PieceType::Pawn, Color::White))); // e2
+        board.set_piece((1, 3), Some(Piece::new(PieceType::Pawn, Color::Black))); // d7
+
+        assert_eq!(sorted(PawnMoves::generate_moves(&board, (6, 4))), vec![(4, 4), (5, 4)]);
+        assert_eq!(sorted(PawnMoves::generate_moves(&board, (1, 3))), vec![(2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn test_pawn_double_step_blocked() {
+        let mut board = Board::new();
+        board.set_piece((6, 4), Some(Piece::new(PieceType::Pawn, Color::White))); // e2
+        board.set_piece((4, 4), Some(Piece::new(PieceType::Rook, Color::Black))); // e4
+        assert_eq!(PawnMoves::generate_moves(&board, (6, 4)), vec![(5, 4)]);
+
+        // A piece directly in front blocks both steps
+        board.set_piece((5, 4), Some(Piece::new(PieceType::Rook, Color::Black))); // e3
+        assert!(PawnMoves::generate_moves(&board, (6, 4)).is_empty());
+    }
+
+    #[test]
+    fn test_pawn_captures_diagonally_only() {
+        let mut board = Board::new();
+        board.set_piece((4, 4), Some(Piece::new(PieceType::Pawn, Color::White))); // e4
+        board.set_piece((3, 3), Some(Piece::new(PieceType::Rook, Color::Black))); // d5
+        board.set_piece((3, 5), Some(Piece::new(PieceType::Rook, Color::White))); // f5
+        board.set_piece((3, 4), Some(Piece::new(PieceType::Rook, Color::Black))); // e5
+
+        // Blocked forward, captures d5, cannot take its own piece on f5
+        assert_eq!(PawnMoves::generate_moves(&board, (4, 4)), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn test_pawn_en_passant_target() {
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        assert_eq!(board.en_passant(), Some((2, 3)));
+        assert_eq!(sorted(PawnMoves::generate_moves(&board, (3, 4))), vec![(2, 3), (2, 4)]);
+
+        board.set_en_passant(None);
+        assert_eq!(PawnMoves::generate_moves(&board, (3, 4)), vec![(2, 4)]);
+    }
+
+    #[test]
+    fn test_pawn_rows() {
+        assert_eq!(PawnMoves::promotion_row(Color::White), 0);
+        assert_eq!(PawnMoves::promotion_row(Color::Black), 7);
+        assert_eq!(PawnMoves::double_step((1, 0), Color::Black), Some((3, 0)));
+        assert_eq!(PawnMoves::double_step((5, 0), Color::White), None);
+    }
+}
@@ -0,0 +1,44 @@
+/// Queen move generation
+/// Slides orthogonally and diagonally (Rook + Bishop)
+use crate::board::{Board, PieceType, Square};
+
+pub struct QueenMoves;
+
+impl QueenMoves {
+    /// Generate all pseudo-legal moves for a Queen, Betza "Q"
+    pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
+        PieceType::Queen.movement().generate_moves(board, from)
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece};
+
+    #[test]
+    fn test_queen_center() {
+        let mut board = Board::new();
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Queen, Color::White)));
+
+        let moves = QueenMoves::generate_moves(&board, (4, 3));
+        assert_eq!(moves.len(), 27, "Queen on d4 has 27 moves");
+        assert!(!moves.contains(&(6, 2)), "Queen has no Knight jumps");
+    }
+
+    #[test]
+    fn test_queen_blocked_by_own_piece() {
+        let mut board = Board::new();
+        board.set_piece((7, 3), Some(Piece::new(PieceType::Queen, Color::White)));
+        board.set_piece((6, 3), Some(Piece::new(PieceType::Pawn, Color::White)));
+
+        let moves = QueenMoves::generate_moves(&board, (7, 3));
+        assert!(!moves.contains(&(6, 3)));
+        assert!(!moves.contains(&(5, 3)));
+        assert!(moves.contains(&(4, 0)), "Diagonal to a4 is open");
+    }
+}
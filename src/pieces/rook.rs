@@ -1,62 +1,14 @@
 /// Rook move generation
 /// Moves horizontally and vertically (orthogonally)
-use crate::board::{Board, Square};
+use crate::board::{Board, PieceType, Square};
 
 pub struct RookMoves;
 
 impl RookMoves {
     /// Generate all pseudo-legal moves for a Rook
-    /// Rook slides horizontally and vertically
+    /// Rook slides horizontally and vertically, Betza "R"
     pub fn generate_moves(board: &Board, from: Square) -> Vec<Square> {
-        let mut moves = Vec::with_capacity(14); // Rook can have up to 14 moves
-
-        // Get the color of the piece that's moving
-        let piece = board.get_piece(from);
-        let our_color = match piece {
-            Some(p) => p.color,
-            None => return moves, // No piece at 'from', return empty
-        };
-
-        // 4 orthogonal directions: up, down, left, right
-        let directions: [(i8, i8); 4] = [
-            (-1, 0), // up
-            ( 1, 0), // down
-            ( 0, -1), // left
-            ( 0, 1), // right
-        ];
-
-        for (dr, dc) in directions {
-            let mut distance = 1;
-            loop {
-                let new_row = from.0 as i8 + dr * distance;
-                let new_col = from.1 as i8 + dc * distance;
-
-                // Check: Is the square on the board?
-                if new_row < 0 || new_row >= 8 || new_col < 0 || new_col >= 8 {
-                    break; // Off the board, stop this direction
-                }
-
-                let to = (new_row as u8, new_col as u8);
-
-                match board.get_piece(to) {
-                    None => {
-                        // Empty square - can move here, continue searching
-                        moves.push(to);
-                        distance += 1;
-                    }
-                    Some(p) => {
-                        if p.color != our_color {
-                            // Enemy piece - can capture
-                            moves.push(to);
-                        }
-                        // Blocked by a piece (own or enemy), stop this direction
-                        break;
-                    }
-                }
-            }
-        }
-
-        moves
+        PieceType::Rook.movement().generate_moves(board, from)
     }
 }
 
@@ -67,7 +19,7 @@ impl RookMoves {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::{Color, Piece, PieceType};
+    use crate::board::{Color, Piece};
 
     #[test]
     fn test_rook_on_empty_board_center() {
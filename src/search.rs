@@ -1,595 +1,663 @@
 // Search algorithm for finding the best move
-// Uses Negamax with Alpha-Beta pruning
+// Uses iterative deepening over Negamax with Alpha-Beta pruning
 
-use crate::board::{Board, Color, Move, PieceType, Square};
+use crate::board::{Board, Move};
+use crate::evaluation::{piece_value, Evaluator, HandCrafted};
+use crate::tablebase::{filter_root_moves, TbProbe, Wdl};
+use crate::time_manager::TimeManager;
+use crate::transposition::{score_from_tt, score_to_tt, Bound, TranspositionTable};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 // Score constants
-const CHECKMATE_SCORE: i32 = 100_000;
+/// Score for delivering checkmate at the root; mate at ply N scores CHECKMATE_SCORE - N
+pub const CHECKMATE_SCORE: i32 = 100_000;
+/// Scores beyond this bound (in absolute value) are mate scores
+pub const MATE_BOUND: i32 = CHECKMATE_SCORE - 1000;
+/// Score of a tablebase win, less its distance; below MATE_BOUND, so mates found by search rank higher
+pub const TB_WIN_SCORE: i32 = MATE_BOUND - 1000;
+/// Scores beyond this bound (and within MATE_BOUND) are tablebase wins or losses
+pub const TB_BOUND: i32 = TB_WIN_SCORE - 1000;
+/// Centipawns shown for a tablebase win at the root: UCI and PGN have no tablebase score,
+/// so wins are reported as a large, clamped evaluation as other engines do
+pub const TB_WIN_CP: i32 = 20_000;
 const INFINITY: i32 = i32::MAX;
 
-// Material values
-const AMAZON_VALUE: i32 = 1500;  // Very powerful piece (Q + N)
-const ROOK_VALUE: i32 = 500;
-
-// Positional weights
-const CHECK_BONUS: i32 = 30;
-const KING_PROXIMITY_WEIGHT: i32 = 5;
-const AMAZON_CENTER_BONUS: i32 = 20;
-const PIECE_SAFETY_PENALTY: i32 = 50;
-
-// New evaluation weights
-const TROPISM_WEIGHT: i32 = 15;       // Amazon approaching enemy king
-const MOBILITY_WEIGHT: i32 = 3;        // Per legal move bonus
-const KING_CUTOFF_BONUS: i32 = 40;     // King cutting off escape routes
-const ROOK_TRAPPED_BONUS: i32 = 30;    // Bonus for trapping enemy rook
-const MATING_NET_WEIGHT: i32 = 25;     // Mating net evaluation
-
-/// Piece-Square Table for enemy King position
-/// Higher values = better for the attacker (King pushed to edge/corner)
-const ENEMY_KING_PST: [[i32; 8]; 8] = [
-    [4, 3, 3, 3, 3, 3, 3, 4],
-    [3, 2, 2, 2, 2, 2, 2, 3],
-    [3, 2, 1, 1, 1, 1, 2, 3],
-    [3, 2, 1, 0, 0, 1, 2, 3],
-    [3, 2, 1, 0, 0, 1, 2, 3],
-    [3, 2, 1, 1, 1, 1, 2, 3],
-    [3, 2, 2, 2, 2, 2, 2, 3],
-    [4, 3, 3, 3, 3, 3, 3, 4],
-];
-
-/// Piece-Square Table for Amazon (center is better)
-const AMAZON_PST: [[i32; 8]; 8] = [
-    [0, 1, 1, 2, 2, 1, 1, 0],
-    [1, 2, 3, 3, 3, 3, 2, 1],
-    [1, 3, 4, 5, 5, 4, 3, 1],
-    [2, 3, 5, 6, 6, 5, 3, 2],
-    [2, 3, 5, 6, 6, 5, 3, 2],
-    [1, 3, 4, 5, 5, 4, 3, 1],
-    [1, 2, 3, 3, 3, 3, 2, 1],
-    [0, 1, 1, 2, 2, 1, 1, 0],
-];
+/// Plies without a capture or pawn move that draw the game
+const FIFTY_MOVE_PLIES: i32 = 100;
 
-// =============================================================================
-// Evaluation Function
-// =============================================================================
-
-/// Evaluate the position from the perspective of the side to move
-/// Positive score = good for side to move
-pub fn evaluate(board: &mut Board) -> i32 {
-    let for_color = board.side_to_move();
-    let enemy_color = for_color.opposite();
+/// Move ordering score for the transposition table move (searched before captures)
+const TT_MOVE_SCORE: i32 = 1_000_000;
 
-    // 1. Terminal state detection
-    if board.is_checkmate(enemy_color) {
-        return CHECKMATE_SCORE;
-    }
-    if board.is_stalemate(enemy_color) {
-        return 0; // Draw - avoid this!
-    }
-    if board.is_checkmate(for_color) {
-        return -CHECKMATE_SCORE;
+/// Score of a tablebase win (`dtm` > 0) or loss (`dtm` < 0) found `ply` plies from the root
+/// Quicker wins score higher and slower losses lower, so the search converts instead of
+/// shuffling between won positions; the score stays within TB_BOUND
+fn tb_score(dtm: i32, ply: i32) -> i32 {
+    let score = TB_WIN_SCORE - (ply + dtm.abs()).min(TB_WIN_SCORE - TB_BOUND);
+    if dtm > 0 {
+        score
+    } else {
+        -score
     }
-    if board.is_stalemate(for_color) {
-        return 0;
-    }
-
-    let mut score = 0;
+}
 
-    // 2. Material evaluation (MOST IMPORTANT!)
-    score += evaluate_material(board, for_color);
+/// Centipawns to report for a search score outside the mate range:
+/// tablebase scores keep their distance but are brought down to TB_WIN_CP
+pub fn score_to_cp(score: i32) -> i32 {
+    if score.abs() >= TB_BOUND {
+        score.signum() * (TB_WIN_CP - (TB_WIN_SCORE - score.abs()))
+    } else {
+        score
+    }
+}
 
-    // 3. Piece safety - penalize pieces under attack
-    score += evaluate_piece_safety(board, for_color);
+// =============================================================================
+// Move Ordering (for better Alpha-Beta pruning)
+// =============================================================================
 
-    // 4. Amazon position (center is better)
-    score += evaluate_amazon_position(board, for_color);
+/// Score a move for ordering purposes
+/// Higher score = should be searched first
+fn score_move(board: &Board, mv: &Move, tt_move: Option<Move>) -> i32 {
+    let mut score = 0;
 
-    // 5. Enemy King position (pushed to edge/corner is good)
-    if let Some(enemy_king_sq) = board.find_king(enemy_color) {
-        score += evaluate_enemy_king_position(enemy_king_sq);
+    // 0. The best move from a previous search of this position comes first
+    if let Some(tt_move) = tt_move {
+        if mv.same_as(&tt_move) {
+            return TT_MOVE_SCORE;
+        }
     }
 
-    // 6. Check bonus (smaller now since material is more important)
-    if board.is_in_check(enemy_color) {
-        score += CHECK_BONUS;
+    // 1. Captures are very important - use MVV-LVA
+    //    (Most Valuable Victim - Least Valuable Attacker)
+    if let Some(captured) = mv.captured {
+        // Value of captured piece minus a fraction of attacker value
+        let victim_value = piece_value(captured.piece_type);
+        
+        // Get attacker piece type
+        if let Some(attacker) = board.get_piece(mv.from) {
+            let attacker_value = piece_value(attacker.piece_type);
+            // MVV-LVA: prioritize capturing valuable pieces with less valuable pieces
+            score += 10000 + victim_value - attacker_value / 100;
+        } else {
+            score += 10000 + victim_value;
+        }
     }
 
-    // 7. King proximity (for endgame)
-    if let (Some(our_king_sq), Some(enemy_king_sq)) =
-        (board.find_king(for_color), board.find_king(enemy_color))
-    {
-        score += evaluate_king_proximity(our_king_sq, enemy_king_sq);
+    // 2. Promotions, best piece first
+    if let Some(promotion) = mv.promotion {
+        score += 5000 + piece_value(promotion);
     }
 
-    // 8. Amazon Tropism - Amazon closer to enemy king
-    score += evaluate_amazon_tropism(board, for_color);
+    score
+}
 
-    // 9. Mobility - more legal moves is better
-    score += evaluate_mobility(board, for_color);
+/// Order moves for better Alpha-Beta pruning efficiency
+/// The transposition table move is searched first, then captures (MVV-LVA ordering)
+fn order_moves(board: &Board, moves: Vec<Move>, tt_move: Option<Move>) -> Vec<Move> {
+    let mut scored_moves: Vec<(Move, i32)> = moves
+        .into_iter()
+        .map(|mv| {
+            let score = score_move(board, &mv, tt_move);
+            (mv, score)
+        })
+        .collect();
 
-    // 10. King Cut-off - cutting enemy king's escape routes
-    score += evaluate_king_cutoff(board, for_color);
+    // Sort in descending order (highest score first)
+    scored_moves.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
 
-    // 11. Rook Activity - penalize active enemy rook
-    score += evaluate_rook_activity(board, for_color);
+    scored_moves.into_iter().map(|(mv, _)| mv).collect()
+}
 
-    // 12. Mating Distance - how close to checkmate position
-    score += evaluate_mating_distance(board, for_color);
+// =============================================================================
+// Search Limits
+// =============================================================================
 
-    score
+/// Deepest iteration the iterative deepening loop will start
+/// when only the clock limits the search
+pub const MAX_SEARCH_DEPTH: i32 = 64;
+
+/// How often (in nodes) the clock is polled during search
+const TIME_CHECK_INTERVAL: u64 = 256;
+
+/// Maximum distance from the root (size of the PV table)
+const MAX_PLY: usize = 128;
+
+/// Limits for a single search, mirroring the UCI `go` parameters
+/// Times are in milliseconds
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// Maximum depth (None = until time runs out)
+    pub depth: Option<i32>,
+    /// Fixed time for this move
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    /// Moves until the next time control
+    pub movestogo: Option<u32>,
+    /// Search until told to stop
+    pub infinite: bool,
+    /// Start in ponder mode: ignore the clock until "ponderhit"
+    pub ponder: bool,
 }
 
-/// Evaluate material balance
-fn evaluate_material(board: &Board, for_color: Color) -> i32 {
-    let mut our_material = 0;
-    let mut enemy_material = 0;
-
-    for row in 0..8 {
-        for col in 0..8 {
-            if let Some(piece) = board.get_piece((row, col)) {
-                let value = match piece.piece_type {
-                    PieceType::Amazon => AMAZON_VALUE,
-                    PieceType::Rook => ROOK_VALUE,
-                    PieceType::King => 0, // King has no material value
-                };
-                if piece.color == for_color {
-                    our_material += value;
-                } else {
-                    enemy_material += value;
-                }
-            }
+impl SearchLimits {
+    /// Limits for a fixed-depth search without time control
+    pub fn depth(depth: i32) -> Self {
+        SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
         }
     }
 
-    our_material - enemy_material
+    /// Deepest iteration allowed by these limits
+    pub fn max_depth(&self) -> i32 {
+        self.depth.unwrap_or(MAX_SEARCH_DEPTH).clamp(1, MAX_SEARCH_DEPTH)
+    }
 }
 
-/// Quick material evaluation for repetition detection
-/// Returns material from side to move's perspective
-fn evaluate_material_only(board: &Board) -> i32 {
-    evaluate_material(board, board.side_to_move())
+// =============================================================================
+// Search Signals
+// =============================================================================
+
+/// Flags shared between a running search and the thread controlling it
+/// The search polls them, so "stop" and "ponderhit" take effect within a few nodes
+#[derive(Debug, Default)]
+pub struct SearchSignals {
+    stop: AtomicBool,
+    ponder: AtomicBool,
 }
 
-/// Evaluate piece safety - penalize pieces that are attacked
-fn evaluate_piece_safety(board: &Board, for_color: Color) -> i32 {
-    let mut penalty = 0;
-    let enemy_color = for_color.opposite();
-
-    for row in 0..8 {
-        for col in 0..8 {
-            if let Some(piece) = board.get_piece((row, col)) {
-                if piece.color == for_color && piece.piece_type != PieceType::King {
-                    let square = (row, col);
-                    // If our piece is attacked, apply penalty
-                    if board.is_square_attacked(square, enemy_color) {
-                        // Penalty based on piece value
-                        let piece_value = match piece.piece_type {
-                            PieceType::Amazon => AMAZON_VALUE / 10,
-                            PieceType::Rook => ROOK_VALUE / 10,
-                            PieceType::King => 0,
-                        };
-                        penalty -= piece_value + PIECE_SAFETY_PENALTY;
-                    }
-                }
-            }
+impl SearchSignals {
+    /// Signals for a new search; `ponder` starts it in ponder mode
+    pub fn new(ponder: bool) -> Self {
+        SearchSignals {
+            stop: AtomicBool::new(false),
+            ponder: AtomicBool::new(ponder),
         }
     }
 
-    penalty
-}
+    /// Ask the search to stop as soon as possible
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
 
-/// Evaluate Amazon position using PST
-fn evaluate_amazon_position(board: &Board, for_color: Color) -> i32 {
-    let mut score = 0;
+    /// Whether a stop was requested
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
 
-    for row in 0..8 {
-        for col in 0..8 {
-            if let Some(piece) = board.get_piece((row, col)) {
-                if piece.piece_type == PieceType::Amazon {
-                    let pst_value = AMAZON_PST[row as usize][col as usize] * AMAZON_CENTER_BONUS;
-                    if piece.color == for_color {
-                        score += pst_value;
-                    } else {
-                        score -= pst_value;
-                    }
-                }
-            }
-        }
+    /// The opponent played the expected move: switch from pondering to normal search
+    pub fn ponderhit(&self) {
+        self.ponder.store(false, Ordering::Relaxed);
     }
 
-    score
+    /// Whether the search is still pondering
+    pub fn is_pondering(&self) -> bool {
+        self.ponder.load(Ordering::Relaxed)
+    }
 }
 
-fn evaluate_enemy_king_position(square: Square) -> i32 {
-    let (row, col) = square;
-    ENEMY_KING_PST[row as usize][col as usize] * 50  // Reduced weight
-}
+// =============================================================================
+// Search Information
+// =============================================================================
 
-fn evaluate_king_proximity(our_king: Square, enemy_king: Square) -> i32 {
-    let row_diff = (our_king.0 as i32 - enemy_king.0 as i32).abs();
-    let col_diff = (our_king.1 as i32 - enemy_king.1 as i32).abs();
-    let distance = row_diff.max(col_diff);
-    (7 - distance) * KING_PROXIMITY_WEIGHT
+/// Result of one completed iteration, reported to the UCI layer
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    /// Nominal depth of the iteration
+    pub depth: i32,
+    /// Deepest ply reached (including quiescence search)
+    pub seldepth: i32,
+    /// Score from the side to move's perspective (see CHECKMATE_SCORE for mates)
+    pub score: i32,
+    /// Nodes searched since the search started
+    pub nodes: u64,
+    /// Time since the search started
+    pub time: Duration,
+    /// Permille of the transposition table in use
+    pub hashfull: usize,
+    /// Principal variation; the first move is the best move
+    pub pv: Vec<Move>,
 }
 
-/// Find Amazon position for a given color
-fn find_amazon(board: &Board, color: Color) -> Option<Square> {
-    for row in 0..8u8 {
-        for col in 0..8u8 {
-            if let Some(piece) = board.get_piece((row, col)) {
-                if piece.piece_type == PieceType::Amazon && piece.color == color {
-                    return Some((row, col));
-                }
-            }
-        }
+impl SearchInfo {
+    /// Best move of this iteration
+    pub fn best_move(&self) -> Move {
+        self.pv[0]
     }
-    None
-}
 
-/// Find Rook position for a given color
-fn find_rook(board: &Board, color: Color) -> Option<Square> {
-    for row in 0..8u8 {
-        for col in 0..8u8 {
-            if let Some(piece) = board.get_piece((row, col)) {
-                if piece.piece_type == PieceType::Rook && piece.color == color {
-                    return Some((row, col));
-                }
-            }
-        }
+    /// Nodes per second
+    pub fn nps(&self) -> u64 {
+        let millis = self.time.as_millis() as u64;
+        (self.nodes * 1000).checked_div(millis).unwrap_or(0)
     }
-    None
 }
 
-/// Evaluate Amazon Tropism - Amazon closer to enemy king is better
-fn evaluate_amazon_tropism(board: &Board, for_color: Color) -> i32 {
-    let enemy_color = for_color.opposite();
+// =============================================================================
+// Searcher State
+// =============================================================================
 
-    let amazon_sq = find_amazon(board, for_color);
-    let enemy_king_sq = board.find_king(enemy_color);
+/// State shared by all nodes of one search
+struct Searcher<'a, E: Evaluator + ?Sized> {
+    time: TimeManager,
+    evaluator: &'a E,
+    tt: &'a mut TranspositionTable,
+    signals: &'a SearchSignals,
+    /// Endgame tables probed below the root
+    tablebase: Option<TbProbe<'a>>,
+    /// Whether we still consider ourselves pondering (clock not running yet)
+    pondering: bool,
+    /// When the search started, for reporting
+    start: Instant,
+    nodes: u64,
+    /// Set once the time is up; every node returns immediately afterwards
+    stopped: bool,
+    /// Aborting is only allowed after the first iteration completed,
+    /// so there is always a move to play
+    can_abort: bool,
+    /// Deepest ply reached in the current iteration
+    seldepth: i32,
+    /// Triangular PV table: pv_table[ply] holds the best line found from that ply
+    pv_table: Vec<Vec<Move>>,
+}
 
-    if let (Some(amazon), Some(king)) = (amazon_sq, enemy_king_sq) {
-        // Chebyshev distance (max of row/col difference)
-        let row_diff = (amazon.0 as i32 - king.0 as i32).abs();
-        let col_diff = (amazon.1 as i32 - king.1 as i32).abs();
-        let distance = row_diff.max(col_diff);
+impl<'a, E: Evaluator + ?Sized> Searcher<'a, E> {
+    fn new(
+        time: TimeManager,
+        evaluator: &'a E,
+        tt: &'a mut TranspositionTable,
+        signals: &'a SearchSignals,
+        tablebase: Option<TbProbe<'a>>,
+    ) -> Self {
+        Searcher {
+            time,
+            evaluator,
+            tt,
+            signals,
+            tablebase,
+            pondering: signals.is_pondering(),
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+            can_abort: false,
+            seldepth: 0,
+            pv_table: vec![Vec::new(); MAX_PLY + 1],
+        }
+    }
 
-        // Closer = higher score (max distance is 7, so 7 - distance gives 0-7)
-        return (7 - distance) * TROPISM_WEIGHT;
+    /// Record `mv` followed by the child's line as the PV at this ply
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        let (head, tail) = self.pv_table.split_at_mut(ply + 1);
+        let line = &mut head[ply];
+        line.clear();
+        line.push(mv);
+        line.extend_from_slice(&tail[0]);
     }
 
-    0
-}
+    /// Count a node and check whether the search has to be aborted
+    fn should_stop(&mut self) -> bool {
+        self.nodes += 1;
+        if self.stopped || !self.can_abort {
+            return self.stopped;
+        }
 
-/// Evaluate Mobility - more legal moves is better
-fn evaluate_mobility(board: &mut Board, for_color: Color) -> i32 {
-    let current_side = board.side_to_move();
+        if self.signals.is_stopped() {
+            self.stopped = true;
+        } else if self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) {
+            self.poll_ponderhit();
+            if !self.pondering && self.time.hard_limit_reached() {
+                self.stopped = true;
+            }
+        }
+        self.stopped
+    }
 
-    // If it's our turn, count our moves
-    if current_side == for_color {
-        let our_moves = board.generate_legal_moves().len() as i32;
-        return our_moves * MOBILITY_WEIGHT;
+    /// Start the clock once the controlling thread sends "ponderhit"
+    fn poll_ponderhit(&mut self) {
+        if self.pondering && !self.signals.is_pondering() {
+            self.pondering = false;
+            self.time.restart();
+        }
     }
 
-    // Otherwise, we need to temporarily switch sides to count
-    // But this is expensive, so we'll just use 0 for now
-    0
-}
+    // =========================================================================
+    // Quiescence Search (to avoid horizon effect)
+    // =========================================================================
+
+    /// Quiescence search - continue searching captures at depth 0
+    /// This prevents the "horizon effect" where the engine stops searching
+    /// right before a major tactical change (like a piece being captured)
+    fn quiescence(&mut self, board: &mut Board, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        // Quiescence lines are not part of the PV
+        self.pv_table[ply as usize].clear();
+        self.seldepth = self.seldepth.max(ply);
 
-/// Evaluate King Cut-off - our king cutting off enemy king's escape routes
-fn evaluate_king_cutoff(board: &Board, for_color: Color) -> i32 {
-    let enemy_color = for_color.opposite();
+        if self.should_stop() {
+            return 0;
+        }
 
-    let our_king_sq = board.find_king(for_color);
-    let enemy_king_sq = board.find_king(enemy_color);
+        // Fifty-move rule: no capture can save the position from the draw
+        if ply > 0 && board.is_fifty_move_draw() {
+            return 0;
+        }
 
-    if let (Some(our_king), Some(enemy_king)) = (our_king_sq, enemy_king_sq) {
-        let mut bonus = 0;
+        // "Stand pat" - evaluate the current position
+        let mut stand_pat = self.evaluator.evaluate(board);
 
-        // Check if our king cuts off the enemy king on the same file
-        if our_king.1 == enemy_king.1 {
-            // Same file - check if we're between enemy king and center/other side
-            let our_dist_to_edge = our_king.0.min(7 - our_king.0);
-            let enemy_dist_to_edge = enemy_king.0.min(7 - enemy_king.0);
-            if our_dist_to_edge > enemy_dist_to_edge {
-                bonus += KING_CUTOFF_BONUS;
-            }
+        // The evaluator reports mate without knowing the ply; make it root-relative
+        if stand_pat >= CHECKMATE_SCORE {
+            stand_pat = CHECKMATE_SCORE - ply;
+        } else if stand_pat <= -CHECKMATE_SCORE {
+            stand_pat = -CHECKMATE_SCORE + ply;
         }
 
-        // Check if our king cuts off the enemy king on the same rank
-        if our_king.0 == enemy_king.0 {
-            // Same rank - check if we're between enemy king and center/other side
-            let our_dist_to_edge = our_king.1.min(7 - our_king.1);
-            let enemy_dist_to_edge = enemy_king.1.min(7 - enemy_king.1);
-            if our_dist_to_edge > enemy_dist_to_edge {
-                bonus += KING_CUTOFF_BONUS;
-            }
+        // If standing pat is good enough, we can prune
+        if stand_pat >= beta {
+            return beta;
         }
 
-        // Bonus if kings are close (opposition can be useful)
-        let row_diff = (our_king.0 as i32 - enemy_king.0 as i32).abs();
-        let col_diff = (our_king.1 as i32 - enemy_king.1 as i32).abs();
-        if row_diff <= 2 && col_diff <= 2 {
-            bonus += KING_CUTOFF_BONUS / 2;
+        // Update alpha if stand pat is better
+        if stand_pat > alpha {
+            alpha = stand_pat;
         }
 
-        return bonus;
-    }
+        // Too deep to continue: trust the static evaluation
+        if ply as usize >= MAX_PLY {
+            return stand_pat;
+        }
 
-    0
-}
+        // Generate only capture moves
+        let all_moves = board.generate_legal_moves();
+        let captures: Vec<Move> = all_moves
+            .into_iter()
+            .filter(|mv| mv.captured.is_some())
+            .collect();
 
-/// Evaluate Rook Activity - penalize enemy rook that has many moves
-fn evaluate_rook_activity(board: &Board, for_color: Color) -> i32 {
-    let enemy_color = for_color.opposite();
+        // If no captures, return the stand pat score
+        if captures.is_empty() {
+            return stand_pat;
+        }
 
-    let enemy_rook_sq = find_rook(board, enemy_color);
+        // Order captures (MVV-LVA)
+        let ordered_captures = order_moves(board, captures, None);
 
-    if let Some(rook) = enemy_rook_sq {
-        // Count how many squares the rook can move to (simplified)
-        let mut rook_mobility = 0;
+        for mv in ordered_captures {
+            board.apply_move(mv);
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move(mv);
 
-        // Check horizontal moves
-        for col in 0..8u8 {
-            if col != rook.1 {
-                let sq = (rook.0, col);
-                if board.get_piece(sq).is_none() {
-                    rook_mobility += 1;
-                } else {
-                    break; // Blocked
-                }
+            if self.stopped {
+                return 0;
             }
-        }
-
-        // Check vertical moves
-        for row in 0..8u8 {
-            if row != rook.0 {
-                let sq = (row, rook.1);
-                if board.get_piece(sq).is_none() {
-                    rook_mobility += 1;
-                } else {
-                    break; // Blocked
-                }
+            if score >= beta {
+                return beta; // Beta cutoff
+            }
+            if score > alpha {
+                alpha = score;
             }
         }
 
-        // Less mobility for enemy rook = better for us
-        // Max rook mobility is 14 (7 + 7)
-        return (14 - rook_mobility) * (ROOK_TRAPPED_BONUS / 7);
+        alpha
     }
 
-    0
-}
-
-/// Evaluate Mating Distance - how close are we to a mating position
-fn evaluate_mating_distance(board: &Board, for_color: Color) -> i32 {
-    let enemy_color = for_color.opposite();
-
-    let our_king_sq = board.find_king(for_color);
-    let enemy_king_sq = board.find_king(enemy_color);
-    let amazon_sq = find_amazon(board, for_color);
-
-    if let (Some(our_king), Some(enemy_king), Some(amazon)) =
-        (our_king_sq, enemy_king_sq, amazon_sq)
-    {
-        // Distance of enemy king to nearest corner
-        let corner_dist = [
-            enemy_king.0.max(enemy_king.1),                    // Distance to (0,0)
-            enemy_king.0.max(7 - enemy_king.1),                // Distance to (0,7)
-            (7 - enemy_king.0).max(enemy_king.1),              // Distance to (7,0)
-            (7 - enemy_king.0).max(7 - enemy_king.1),          // Distance to (7,7)
-        ]
-        .into_iter()
-        .min()
-        .unwrap_or(7) as i32;
+    // =========================================================================
+    // Search Algorithm: Negamax with Alpha-Beta Pruning
+    // =========================================================================
 
-        // Distance from our pieces to enemy king
-        let amazon_dist = (amazon.0 as i32 - enemy_king.0 as i32)
-            .abs()
-            .max((amazon.1 as i32 - enemy_king.1 as i32).abs());
-        let our_king_dist = (our_king.0 as i32 - enemy_king.0 as i32)
-            .abs()
-            .max((our_king.1 as i32 - enemy_king.1 as i32).abs());
+    /// Negamax search with Alpha-Beta pruning
+    /// Returns the score of the position from the side to move's perspective
+    /// `ply` is the distance from the root, used to score mates
+    fn negamax(&mut self, board: &mut Board, depth: i32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        self.pv_table[ply as usize].clear();
+        self.seldepth = self.seldepth.max(ply);
 
-        // Score: enemy king close to corner + our pieces close to enemy king
-        let corner_score = (7 - corner_dist) * 2;
-        let approach_score = 14 - amazon_dist - our_king_dist;
+        // Check for repetition - if position repeated twice, it's essentially a draw
+        // Return 0 (draw score) - this is fair: if we're winning we'll find another way,
+        // if we're losing, a draw is actually good
+        if board.repetition_count() >= 2 {
+            return 0; // Threefold repetition = draw
+        }
 
-        return (corner_score + approach_score) * MATING_NET_WEIGHT / 10;
-    }
+        // Fifty-move rule: 100 plies without a capture is a draw (a mate is still found below)
+        if ply > 0 && board.is_fifty_move_draw() {
+            return 0;
+        }
 
-    0
-}
+        // Tablebase hit: the result is known, no search needed
+        // A mated position has no distance to probe, and a mate the fifty-move rule may
+        // come first is not trusted: the search below scores both
+        if let Some(probe) = self.tablebase.filter(|probe| ply > 0 && depth >= probe.probe_depth) {
+            match probe.tablebase.probe_wdl(board) {
+                Some(Wdl::Draw) => return 0,
+                Some(_) => {
+                    if let Some(dtm) = probe.tablebase.probe_dtm(board) {
+                        if board.halfmove_clock() as i32 + dtm.abs() <= FIFTY_MOVE_PLIES {
+                            return tb_score(dtm, ply);
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
 
-// =============================================================================
-// Move Ordering (for better Alpha-Beta pruning)
-// =============================================================================
+        // Base case: reached maximum depth - use quiescence search
+        if depth == 0 || ply as usize >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta);
+        }
 
-/// Get the material value of a piece type
-fn piece_value(piece_type: PieceType) -> i32 {
-    match piece_type {
-        PieceType::Amazon => AMAZON_VALUE,
-        PieceType::Rook => ROOK_VALUE,
-        PieceType::King => 10000, // King is invaluable
-    }
-}
+        if self.should_stop() {
+            return 0;
+        }
 
-/// Score a move for ordering purposes
-/// Higher score = should be searched first
-fn score_move(board: &Board, mv: &Move) -> i32 {
-    let mut score = 0;
+        // Transposition table lookup: cut off if a deep enough result is stored
+        // The key ignores the halfmove clock, so near a fifty-move draw a stored score
+        // may come from a search where the draw was out of reach: only use its move
+        let key = board.position_hash();
+        let mut tt_move = None;
+        if let Some(entry) = self.tt.probe(key) {
+            tt_move = entry.best_move;
+            if entry.depth >= depth && board.halfmove_clock() as i32 + depth < FIFTY_MOVE_PLIES {
+                let score = score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
 
-    // 1. Captures are very important - use MVV-LVA
-    //    (Most Valuable Victim - Least Valuable Attacker)
-    if let Some(captured) = mv.captured {
-        // Value of captured piece minus a fraction of attacker value
-        let victim_value = piece_value(captured.piece_type);
-        
-        // Get attacker piece type
-        if let Some(attacker) = board.get_piece(mv.from) {
-            let attacker_value = piece_value(attacker.piece_type);
-            // MVV-LVA: prioritize capturing valuable pieces with less valuable pieces
-            score += 10000 + victim_value - attacker_value / 100;
-        } else {
-            score += 10000 + victim_value;
+        let moves = board.generate_legal_moves();
+
+        // No legal moves = checkmate or stalemate
+        if moves.is_empty() {
+            if board.is_in_check(board.side_to_move()) {
+                // Checkmate - return negative score (we lose)
+                // Mated closer to the root scores lower, so faster mates are preferred
+                return -CHECKMATE_SCORE + ply;
+            } else {
+                // Stalemate - draw
+                return 0;
+            }
         }
-    }
 
-    score
-}
+        // Order moves for better pruning (TT move, then captures)
+        let ordered_moves = order_moves(board, moves, tt_move);
 
-/// Order moves for better Alpha-Beta pruning efficiency
-/// Captures are searched first (MVV-LVA ordering)
-fn order_moves(board: &Board, moves: Vec<Move>) -> Vec<Move> {
-    let mut scored_moves: Vec<(Move, i32)> = moves
-        .into_iter()
-        .map(|mv| {
-            let score = score_move(board, &mv);
-            (mv, score)
-        })
-        .collect();
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
 
-    // Sort in descending order (highest score first)
-    scored_moves.sort_by(|a, b| b.1.cmp(&a.1));
+        for mv in ordered_moves {
+            board.apply_move(mv);
+            let score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha);
+            board.unmake_move(mv);
 
-    scored_moves.into_iter().map(|(mv, _)| mv).collect()
-}
+            if self.stopped {
+                return 0;
+            }
 
-// =============================================================================
-// Quiescence Search (to avoid horizon effect)
-// =============================================================================
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply as usize, mv);
+            }
 
-/// Quiescence search - continue searching captures at depth 0
-/// This prevents the "horizon effect" where the engine stops searching
-/// right before a major tactical change (like a piece being captured)
-fn quiescence(board: &mut Board, mut alpha: i32, beta: i32) -> i32 {
-    // "Stand pat" - evaluate the current position
-    let stand_pat = evaluate(board);
+            if alpha >= beta {
+                break; // Beta cutoff (pruning)
+            }
+        }
 
-    // If standing pat is good enough, we can prune
-    if stand_pat >= beta {
-        return beta;
-    }
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.tt.store(key, depth, bound, score_to_tt(best_score, ply), best_move);
 
-    // Update alpha if stand pat is better
-    if stand_pat > alpha {
-        alpha = stand_pat;
+        best_score
     }
 
-    // Generate only capture moves
-    let all_moves = board.generate_legal_moves();
-    let captures: Vec<Move> = all_moves
-        .into_iter()
-        .filter(|mv| mv.captured.is_some())
-        .collect();
+    /// Search all root moves to the given depth
+    /// Returns None if the iteration was aborted before it completed
+    fn search_root(&mut self, board: &mut Board, root_moves: &[Move], depth: i32) -> Option<(Move, i32)> {
+        self.seldepth = 0;
+        self.pv_table[0].clear();
 
-    // If no captures, return the stand pat score
-    if captures.is_empty() {
-        return stand_pat;
-    }
+        let mut best_move = None;
+        let mut best_score = -INFINITY;
+        let mut alpha = -INFINITY;
+        let beta = INFINITY;
 
-    // Order captures (MVV-LVA)
-    let ordered_captures = order_moves(board, captures);
+        for &mv in root_moves {
+            board.apply_move(mv);
+            let score = -self.negamax(board, depth - 1, 1, -beta, -alpha);
+            board.unmake_move(mv);
 
-    for mv in ordered_captures {
-        board.make_move(mv.from, mv.to);
-        let score = -quiescence(board, -beta, -alpha);
-        board.unmake_move(mv);
+            if self.stopped {
+                return None;
+            }
 
-        if score >= beta {
-            return beta; // Beta cutoff
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+                self.update_pv(0, mv);
+            }
+            alpha = alpha.max(score);
         }
-        if score > alpha {
-            alpha = score;
+
+        if best_move.is_some() {
+            self.tt.store(board.position_hash(), depth, Bound::Exact, best_score, best_move);
         }
-    }
 
-    alpha
+        best_move.map(|mv| (mv, best_score))
+    }
 }
 
 // =============================================================================
-// Search Algorithm: Negamax with Alpha-Beta Pruning
+// Iterative Deepening Driver
 // =============================================================================
 
-/// Negamax search with Alpha-Beta pruning
-/// Returns the score of the position from the side to move's perspective
-pub fn negamax(board: &mut Board, depth: i32, mut alpha: i32, beta: i32) -> i32 {
-    // Check for repetition - if position repeated twice, it's essentially a draw
-    // Return 0 (draw score) - this is fair: if we're winning we'll find another way,
-    // if we're losing, a draw is actually good
-    if board.repetition_count() >= 2 {
-        return 0; // Threefold repetition = draw
-    }
+/// Find the best move for the current position with the default hand-crafted evaluation
+/// Returns the best move and score of the last completed iteration (see `search`)
+pub fn find_best_move(
+    board: &mut Board,
+    limits: &SearchLimits,
+    tt: &mut TranspositionTable,
+) -> Option<(Move, i32)> {
+    find_best_move_with(board, limits, tt, &HandCrafted::default())
+}
 
-    // Base case: reached maximum depth - use quiescence search
-    if depth == 0 {
-        return quiescence(board, alpha, beta);
-    }
+/// `find_best_move` with the given evaluator
+pub fn find_best_move_with<E: Evaluator + ?Sized>(
+    board: &mut Board,
+    limits: &SearchLimits,
+    tt: &mut TranspositionTable,
+    evaluator: &E,
+) -> Option<(Move, i32)> {
+    let signals = SearchSignals::default();
+    search(board, limits, tt, evaluator, None, &signals, &mut |_| {}).map(|info| (info.best_move(), info.score))
+}
 
-    let moves = board.generate_legal_moves();
+/// Search the current position with iterative deepening until the depth or time limit is hit,
+/// or until `signals` asks it to stop
+/// `on_iteration` is called after every completed iteration (for UCI "info" output),
+/// and the last completed iteration is returned
+/// The transposition table is kept between calls, so pass the same table for a whole game
+/// With a tablebase, only the root moves keeping the best tablebase result are searched
+pub fn search<E: Evaluator + ?Sized>(
+    board: &mut Board,
+    limits: &SearchLimits,
+    tt: &mut TranspositionTable,
+    evaluator: &E,
+    tablebase: Option<TbProbe>,
+    signals: &SearchSignals,
+    on_iteration: &mut dyn FnMut(&SearchInfo),
+) -> Option<SearchInfo> {
+    let mut moves = board.generate_legal_moves();
 
-    // No legal moves = checkmate or stalemate
     if moves.is_empty() {
-        if board.is_in_check(board.side_to_move()) {
-            // Checkmate - return negative score (we lose)
-            // Add depth to prefer faster checkmates
-            return -CHECKMATE_SCORE + (100 - depth);
-        } else {
-            // Stalemate - draw
-            return 0;
-        }
+        return None;
     }
 
-    // Order moves for better pruning (captures first)
-    let ordered_moves = order_moves(board, moves);
-
-    let mut best_score = -INFINITY;
-
-    for mv in ordered_moves {
-        board.make_move(mv.from, mv.to);
-        let score = -negamax(board, depth - 1, -beta, -alpha);
-        board.unmake_move(mv);
-
-        best_score = best_score.max(score);
-        alpha = alpha.max(score);
-
-        if alpha >= beta {
-            break; // Beta cutoff (pruning)
-        }
+    if let Some(best) = tablebase.and_then(|probe| filter_root_moves(probe.tablebase, board, &moves)) {
+        moves = best;
     }
+    evaluator.prepare(board);
 
-    best_score
-}
-
-/// Find the best move for the current position
-/// Returns the best move and its score
-pub fn find_best_move(board: &mut Board, depth: i32) -> Option<(Move, i32)> {
-    let moves = board.generate_legal_moves();
+    // Order moves for better pruning, starting with the stored best move
+    let tt_move = tt.probe(board.position_hash()).and_then(|entry| entry.best_move);
+    let mut root_moves = order_moves(board, moves, tt_move);
 
-    if moves.is_empty() {
-        return None;
-    }
+    let mut searcher = Searcher::new(TimeManager::new(limits, board.side_to_move()), evaluator, tt, signals, tablebase);
+    let mut best: Option<SearchInfo> = None;
 
-    // Order moves for better pruning
-    let ordered_moves = order_moves(board, moves);
-
-    let mut best_move = None;
-    let mut best_score = -INFINITY;
-    let mut alpha = -INFINITY;
-    let beta = INFINITY;
+    for depth in 1..=limits.max_depth() {
+        searcher.poll_ponderhit();
+        if depth > 1
+            && (signals.is_stopped() || (!searcher.pondering && !searcher.time.should_start_iteration()))
+        {
+            break;
+        }
 
-    for mv in ordered_moves {
-        board.make_move(mv.from, mv.to);
-        let score = -negamax(board, depth - 1, -beta, -alpha);
-        board.unmake_move(mv);
+        match searcher.search_root(board, &root_moves, depth) {
+            Some((best_move, score)) => {
+                // Search the best move first in the next iteration
+                if let Some(index) = root_moves.iter().position(|mv| mv.same_as(&best_move)) {
+                    let mv = root_moves.remove(index);
+                    root_moves.insert(0, mv);
+                }
+                let info = SearchInfo {
+                    depth,
+                    seldepth: searcher.seldepth,
+                    score,
+                    nodes: searcher.nodes,
+                    time: searcher.start.elapsed(),
+                    hashfull: searcher.tt.hashfull(),
+                    pv: searcher.pv_table[0].clone(),
+                };
+                on_iteration(&info);
+                best = Some(info);
+                searcher.can_abort = true;
+            }
+            None => break, // Time is up, keep the last completed iteration
+        }
 
-        if score > best_score {
-            best_score = score;
-            best_move = Some(mv);
+        // Only one legal move: no need to spend the clock on it
+        if root_moves.len() == 1 && searcher.time.is_timed() && !searcher.pondering {
+            break;
         }
-        alpha = alpha.max(score);
     }
 
-    best_move.map(|mv| (mv, best_score))
+    evaluator.finish(board);
+    best
 }
 
 // =============================================================================
@@ -620,7 +688,7 @@ mod tests {
             "Should not be checkmate yet"
         );
 
-        let result = find_best_move(&mut board, 4);
+        let result = find_best_move(&mut board, &SearchLimits::depth(4), &mut TranspositionTable::new(1));
         assert!(result.is_some(), "Should find a move");
 
         let (best_move, score) = result.unwrap();
@@ -648,13 +716,13 @@ mod tests {
         board.set_piece((3, 5), Some(Piece::new(PieceType::Amazon, Color::White)));
         board.set_side_to_move(Color::White);
 
-        let result = find_best_move(&mut board, 3);
+        let result = find_best_move(&mut board, &SearchLimits::depth(3), &mut TranspositionTable::new(1));
         assert!(result.is_some(), "Should find a move");
 
         let (best_move, score) = result.unwrap();
 
         // Apply the move
-        board.make_move(best_move.from, best_move.to);
+        board.apply_move(best_move);
 
         // Should NOT be stalemate
         assert!(
@@ -669,7 +737,7 @@ mod tests {
     fn test_search_returns_move() {
         let mut board = Board::setup_amazon_vs_rook();
 
-        let result = find_best_move(&mut board, 3);
+        let result = find_best_move(&mut board, &SearchLimits::depth(3), &mut TranspositionTable::new(1));
         assert!(result.is_some(), "Should find a move in starting position");
 
         let (mv, _score) = result.unwrap();
@@ -689,35 +757,264 @@ mod tests {
         board.set_piece((2, 1), Some(Piece::new(PieceType::Amazon, Color::White)));
         board.set_side_to_move(Color::Black);
 
-        let result = find_best_move(&mut board, 3);
+        let result = find_best_move(&mut board, &SearchLimits::depth(3), &mut TranspositionTable::new(1));
         assert!(result.is_none(), "Should return None when no legal moves");
     }
 
     #[test]
-    fn test_evaluation_prefers_corner() {
-        let mut board_corner = Board::new();
-        let mut board_center = Board::new();
-
-        // Black king in corner
-        board_corner.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::Black)));
-        board_corner.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
-        board_corner.set_piece((7, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
-        board_corner.set_side_to_move(Color::White);
-
-        // Black king in center
-        board_center.set_piece((4, 4), Some(Piece::new(PieceType::King, Color::Black)));
-        board_center.set_piece((7, 4), Some(Piece::new(PieceType::King, Color::White)));
-        board_center.set_piece((7, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
-        board_center.set_side_to_move(Color::White);
+    fn test_movetime_limits_search() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let limits = SearchLimits {
+            movetime: Some(200),
+            ..SearchLimits::default()
+        };
 
-        let score_corner = evaluate(&mut board_corner);
-        let score_center = evaluate(&mut board_center);
+        let start = std::time::Instant::now();
+        let result = find_best_move(&mut board, &limits, &mut TranspositionTable::new(1));
+        let elapsed = start.elapsed();
 
+        assert!(result.is_some(), "Should return the last completed iteration's move");
         assert!(
-            score_corner > score_center,
-            "Corner ({}) should be better than center ({})",
-            score_corner,
-            score_center
+            elapsed < std::time::Duration::from_millis(2000),
+            "Search should stop near the movetime budget, took {:?}",
+            elapsed
         );
     }
+
+    #[test]
+    fn test_prefers_faster_mate() {
+        // Mate in 1 is available (Ab6#); the score must say mate at ply 1
+        let mut board = Board::new();
+        board.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::Black)));
+        board.set_piece((1, 2), Some(Piece::new(PieceType::King, Color::White)));
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
+        board.set_side_to_move(Color::White);
+
+        let mut tt = TranspositionTable::new(1);
+        let (_, score) =
+            find_best_move(&mut board, &SearchLimits::depth(4), &mut tt).expect("Should find a move");
+        assert_eq!(score, CHECKMATE_SCORE - 1, "Should report mate in one ply");
+    }
+
+    #[test]
+    fn test_fifty_move_rule_scores_draw() {
+        // K+R vs K is winning, but one more quiet move ends the game as a draw
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        let (_, score) = find_best_move(&mut board, &SearchLimits::depth(3), &mut TranspositionTable::new(1))
+            .expect("Should find a move");
+        assert_eq!(score, 0);
+
+        // With a fresh clock the same position is clearly winning
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 80").unwrap();
+        let (_, score) = find_best_move(&mut board, &SearchLimits::depth(3), &mut TranspositionTable::new(1))
+            .expect("Should find a move");
+        assert!(score > 0);
+    }
+
+    #[test]
+    fn test_fifty_move_rule_in_quiescence() {
+        // Black's rook hangs, but the clock has run out before it can be taken
+        let mut board = Board::from_fen("4k3/8/8/3r4/8/8/8/3AK3 w - - 100 80").unwrap();
+        let limits = SearchLimits::depth(1);
+        let mut tt = TranspositionTable::new(1);
+        let signals = SearchSignals::default();
+        let evaluator = HandCrafted::default();
+        let mut searcher =
+            Searcher::new(TimeManager::new(&limits, board.side_to_move()), &evaluator, &mut tt, &signals, None);
+        assert_eq!(searcher.quiescence(&mut board, 1, -INFINITY, INFINITY), 0);
+
+        // With one ply left on the clock the capture resets it
+        board.set_halfmove_clock(99);
+        assert!(searcher.quiescence(&mut board, 1, -INFINITY, INFINITY) > 0);
+    }
+
+    #[test]
+    fn test_fifty_move_rule_not_hidden_by_tt() {
+        // The table is full of wins from a search with a fresh clock
+        let mut tt = TranspositionTable::new(1);
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 80").unwrap();
+        find_best_move(&mut board, &SearchLimits::depth(5), &mut tt).unwrap();
+
+        // Three plies from the fifty-move draw, those entries must not be trusted
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 97 80").unwrap();
+        let (_, score) = find_best_move(&mut board, &SearchLimits::depth(3), &mut tt).unwrap();
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn test_tt_reused_between_searches() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut tt = TranspositionTable::new(1);
+
+        let first = find_best_move(&mut board, &SearchLimits::depth(3), &mut tt).unwrap();
+        assert!(tt.probe(board.position_hash()).is_some(), "Root should be stored");
+
+        // Searching again with a warm table gives the same answer
+        let second = find_best_move(&mut board, &SearchLimits::depth(3), &mut tt).unwrap();
+        assert_eq!((first.0.from, first.0.to), (second.0.from, second.0.to));
+        assert_eq!(first.1, second.1);
+    }
+
+    #[test]
+    fn test_search_reports_each_iteration() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut tt = TranspositionTable::new(1);
+        let mut depths = Vec::new();
+
+        let info = search(&mut board, &SearchLimits::depth(3), &mut tt, &HandCrafted::default(), None, &SearchSignals::default(), &mut |info| {
+            depths.push(info.depth);
+        })
+        .expect("Should find a move");
+
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert_eq!(info.depth, 3);
+        assert!(info.nodes > 0);
+        assert!(info.seldepth >= 3);
+    }
+
+    #[test]
+    fn test_pv_is_legal_line() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut tt = TranspositionTable::new(1);
+        let info = search(&mut board, &SearchLimits::depth(3), &mut tt, &HandCrafted::default(), None, &SearchSignals::default(), &mut |_| {}).unwrap();
+
+        assert!(!info.pv.is_empty());
+        // Replay the PV: every move must be legal in turn
+        let mut made = Vec::new();
+        for mv in &info.pv {
+            let legal = board.generate_legal_moves();
+            assert!(
+                legal.iter().any(|m| m.from == mv.from && m.to == mv.to),
+                "PV move {:?} is not legal",
+                mv
+            );
+            made.push(board.apply_move(*mv));
+        }
+        for mv in made.into_iter().rev() {
+            board.unmake_move(mv);
+        }
+    }
+
+    #[test]
+    fn test_pv_ends_in_mate() {
+        // Mate in 1: the PV is just the mating move
+        let mut board = Board::new();
+        board.set_piece((0, 0), Some(Piece::new(PieceType::King, Color::Black)));
+        board.set_piece((1, 2), Some(Piece::new(PieceType::King, Color::White)));
+        board.set_piece((4, 3), Some(Piece::new(PieceType::Amazon, Color::White)));
+        board.set_side_to_move(Color::White);
+
+        let mut tt = TranspositionTable::new(1);
+        let info = search(&mut board, &SearchLimits::depth(3), &mut tt, &HandCrafted::default(), None, &SearchSignals::default(), &mut |_| {}).unwrap();
+        let mv = info.best_move();
+        board.apply_move(mv);
+        assert!(board.is_checkmate(Color::Black));
+    }
+
+    #[test]
+    fn test_stop_signal_ends_infinite_search() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut tt = TranspositionTable::new(1);
+        let limits = SearchLimits {
+            infinite: true,
+            ..SearchLimits::default()
+        };
+        let signals = SearchSignals::default();
+
+        // Ask for a stop as soon as the first iteration is reported
+        let info = search(&mut board, &limits, &mut tt, &HandCrafted::default(), None, &signals, &mut |_| signals.stop())
+            .expect("Should return the completed iteration");
+        assert_eq!(info.depth, 1);
+    }
+
+    #[test]
+    fn test_pondering_ignores_clock_until_ponderhit() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut tt = TranspositionTable::new(1);
+        let limits = SearchLimits {
+            movetime: Some(10),
+            ponder: true,
+            depth: Some(3),
+            ..SearchLimits::default()
+        };
+        let signals = SearchSignals::new(true);
+
+        // With a 10ms budget a timed search could stop early; pondering must reach depth 3
+        let info = search(&mut board, &limits, &mut tt, &HandCrafted::default(), None, &signals, &mut |_| {}).unwrap();
+        assert_eq!(info.depth, 3);
+        assert!(signals.is_pondering());
+
+        signals.ponderhit();
+        assert!(!signals.is_pondering());
+    }
+
+    #[test]
+    fn test_max_depth_defaults_to_search_limit() {
+        assert_eq!(SearchLimits::default().max_depth(), MAX_SEARCH_DEPTH);
+        assert_eq!(SearchLimits::depth(5).max_depth(), 5);
+    }
+
+    /// Evaluator giving every position the same score
+    struct ConstantEvaluator(i32);
+
+    impl Evaluator for ConstantEvaluator {
+        fn evaluate(&self, _board: &mut Board) -> i32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_search_uses_given_evaluator() {
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut tt = TranspositionTable::new(1);
+        let evaluator = ConstantEvaluator(7);
+        let signals = SearchSignals::default();
+        let info = search(&mut board, &SearchLimits::depth(1), &mut tt, &evaluator, None, &signals, &mut |_| {})
+            .unwrap();
+        // Every reply is scored 7 for Black
+        assert_eq!(info.score, -7);
+    }
+
+    /// Backend that calls every position a draw and counts its probes
+    struct DrawTablebase {
+        probes: std::sync::atomic::AtomicU64,
+    }
+
+    impl crate::tablebase::Tablebase for DrawTablebase {
+        fn max_pieces(&self) -> usize {
+            32
+        }
+
+        fn probe_wdl(&self, _board: &Board) -> Option<Wdl> {
+            self.probes.fetch_add(1, Ordering::Relaxed);
+            Some(Wdl::Draw)
+        }
+
+        fn probe_dtm(&self, _board: &Board) -> Option<i32> {
+            Some(0)
+        }
+    }
+
+    #[test]
+    fn test_tablebase_probe_depth() {
+        let stub = DrawTablebase { probes: Default::default() };
+        let mut board = Board::setup_amazon_vs_rook();
+        let mut search_with = |probe_depth| {
+            let probe = TbProbe { tablebase: &stub, probe_depth };
+            let mut tt = TranspositionTable::new(1);
+            let signals = SearchSignals::default();
+            search(&mut board, &SearchLimits::depth(3), &mut tt, &HandCrafted::default(), Some(probe), &signals, &mut |_| {}).unwrap()
+        };
+
+        // Every child of the root is a tablebase draw
+        let info = search_with(1);
+        assert_eq!(info.score, 0);
+        assert!(stub.probes.load(Ordering::Relaxed) > 0);
+
+        // Deeper than the search: only the root move filter probes
+        stub.probes.store(0, Ordering::Relaxed);
+        let info = search_with(10);
+        assert_ne!(info.score, 0);
+        assert_eq!(stub.probes.load(Ordering::Relaxed), 1);
+    }
 }
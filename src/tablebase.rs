@@ -0,0 +1,1166 @@
+// Endgame tablebases
+// Exact distance-to-mate (DTM) tables for pawnless endings of up to four pieces,
+// built by retrograde analysis: start from the mates, then walk moves backwards
+// one ply at a time. Positions never reached that way are draws.
+//
+// The search does not depend on these tables directly: it probes any backend
+// implementing the `Tablebase` trait, with Syzygy-style WDL and DTZ queries.
+//
+// On disk a table is a short header followed by one byte per position.
+// The white king is mirrored into the a1-d1-d4 triangle, so only 10 of its
+// 64 squares are stored.
+
+use crate::bitboard::{bits, piece_attacks, piece_quiet_moves, square_index, Bitboard};
+use crate::board::{Board, Color, Move, Piece, PieceType};
+use std::collections::HashMap;
+use std::fs;
+use std::env;
+use std::path::Path;
+
+/// Largest number of pieces (kings included) a table can hold
+pub const MAX_PIECES: usize = 4;
+
+/// File extension of tables on disk
+pub const FILE_EXTENSION: &str = "atb";
+
+/// Extensions of Syzygy WDL and DTZ files, which cannot be read
+const SYZYGY_EXTENSIONS: [&str; 2] = ["rtbw", "rtbz"];
+
+/// First bytes of every table file
+const MAGIC: &[u8; 4] = b"ATB1";
+
+/// Entry byte: draw (or not yet resolved during generation)
+const DRAW: u8 = 0;
+/// Entry byte: the side not to move is in check, or two pieces share a square
+const INVALID: u8 = 255;
+/// Longest mate an entry can hold (stored as plies + 1)
+const MAX_PLIES: usize = 253;
+
+/// Squares the white king is mirrored onto: rank <= file <= d
+const KING_SLOTS: usize = 10;
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Why a table could not be built or loaded
+#[derive(Debug)]
+pub enum TablebaseError {
+    /// Material string is not like "KAvKR"
+    BadMaterial(String),
+    /// More pieces than MAX_PIECES
+    TooManyPieces(usize),
+    /// Pieces without symmetric moves (pawns) cannot be unmoved
+    UnsupportedPiece(PieceType),
+    /// A mate is longer than an entry can hold
+    TooDeep(String),
+    /// A file is not a table written by this engine
+    BadFile(String),
+    /// A Syzygy table: only tables written by this engine are supported
+    Syzygy(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for TablebaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TablebaseError::BadMaterial(s) => write!(f, "invalid material '{}'", s),
+            TablebaseError::TooManyPieces(n) => write!(f, "{} pieces (at most {})", n, MAX_PIECES),
+            TablebaseError::UnsupportedPiece(pt) => write!(f, "{} is not supported", pt.info().name),
+            TablebaseError::TooDeep(s) => write!(f, "{} has mates longer than {} plies", s, MAX_PLIES),
+            TablebaseError::BadFile(s) => write!(f, "not a tablebase file: {}", s),
+            TablebaseError::Syzygy(s) => {
+                write!(f, "Syzygy tables are not supported, only .{} tables: {}", FILE_EXTENSION, s)
+            }
+            TablebaseError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for TablebaseError {}
+
+impl From<std::io::Error> for TablebaseError {
+    fn from(e: std::io::Error) -> Self {
+        TablebaseError::Io(e)
+    }
+}
+
+// =============================================================================
+// Material
+// =============================================================================
+
+/// The pieces of an ending, e.g. "KAvKR"
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Material {
+    /// counts[color][piece type], kings included
+    counts: [[u8; PieceType::COUNT]; 2],
+}
+
+impl Material {
+    /// Parse a material string: White's pieces, "v", Black's pieces, each with one king
+    pub fn parse(s: &str) -> Result<Material, TablebaseError> {
+        let bad = || TablebaseError::BadMaterial(s.to_string());
+        let (white, black) = s.split_once('v').ok_or_else(bad)?;
+
+        let mut counts = [[0u8; PieceType::COUNT]; 2];
+        for (color, side) in [(Color::White, white), (Color::Black, black)] {
+            for letter in side.chars() {
+                let pt = PieceType::from_letter(letter).ok_or_else(bad)?;
+                counts[color.index()][pt.index()] += 1;
+            }
+            if counts[color.index()][PieceType::King.index()] != 1 {
+                return Err(bad());
+            }
+        }
+
+        let material = Material { counts };
+        material.check_supported()?;
+        Ok(material)
+    }
+
+    /// Material of a list of pieces
+    fn of_pieces(pieces: &[Piece]) -> Material {
+        let mut counts = [[0u8; PieceType::COUNT]; 2];
+        for piece in pieces {
+            counts[piece.color.index()][piece.piece_type.index()] += 1;
+        }
+        Material { counts }
+    }
+
+    /// Whether tables can be built for this material
+    fn check_supported(&self) -> Result<(), TablebaseError> {
+        if self.counts[0][PieceType::Pawn.index()] + self.counts[1][PieceType::Pawn.index()] > 0 {
+            return Err(TablebaseError::UnsupportedPiece(PieceType::Pawn));
+        }
+        let count = self.pieces().len();
+        if count > MAX_PIECES {
+            return Err(TablebaseError::TooManyPieces(count));
+        }
+        Ok(())
+    }
+
+    /// Name like "KAvKR": kings first, then by piece type index
+    pub fn name(&self) -> String {
+        let side = |color: Color| -> String {
+            self.pieces()
+                .iter()
+                .filter(|p| p.color == color)
+                .map(|p| p.piece_type.info().letter)
+                .collect()
+        };
+        format!("{}v{}", side(Color::White), side(Color::Black))
+    }
+
+    /// Pieces in table order: White's then Black's, each sorted by piece type index
+    pub fn pieces(&self) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        for color in [Color::White, Color::Black] {
+            for pt in PieceType::ALL {
+                for _ in 0..self.counts[color.index()][pt.index()] {
+                    pieces.push(Piece::new(pt, color));
+                }
+            }
+        }
+        pieces
+    }
+
+    /// The same material with the colors exchanged
+    pub fn swapped(&self) -> Material {
+        Material {
+            counts: [self.counts[1], self.counts[0]],
+        }
+    }
+
+    /// Only the two kings are left
+    fn is_bare_kings(&self) -> bool {
+        self.pieces().len() == 2
+    }
+}
+
+// =============================================================================
+// Probe Interface
+// =============================================================================
+
+/// Game-theoretic result for the side to move (ignoring the fifty-move rule)
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win,
+}
+
+/// A tablebase backend the search can probe
+pub trait Tablebase: Send + Sync {
+    /// Most pieces (kings included) in any position the backend covers
+    fn max_pieces(&self) -> usize;
+
+    /// Win, draw or loss for the side to move; None if the position is not covered
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    /// Plies to mate (DTM) along the best line: positive if the side to move mates,
+    /// negative if it is mated, 0 for a draw; the fifty-move rule is not considered
+    /// Positions without legal moves are not probed
+    fn probe_dtm(&self, board: &Board) -> Option<i32>;
+}
+
+/// Default for the UCI "TbProbeDepth" option: probe every node above the leaves
+pub const DEFAULT_TB_PROBE_DEPTH: i32 = 1;
+
+/// A tablebase and how deep in the tree to probe it (UCI "TbProbeDepth")
+#[derive(Clone, Copy)]
+pub struct TbProbe<'a> {
+    pub tablebase: &'a dyn Tablebase,
+    /// Nodes with less depth left than this are not probed
+    pub probe_depth: i32,
+}
+
+/// The root moves that keep the best result: the fastest win, a draw, or the slowest loss
+/// None if the position (or one after a move) is not covered
+pub fn filter_root_moves(tablebase: &dyn Tablebase, board: &mut Board, moves: &[Move]) -> Option<Vec<Move>> {
+    tablebase.probe_wdl(board)?;
+
+    // Rank each move from the mover's point of view: (result, shorter wins / longer losses first)
+    let mut ranked = Vec::with_capacity(moves.len());
+    for &mv in moves {
+        board.apply_move(mv);
+        let side = board.side_to_move();
+        let rank = if board.is_checkmate(side) {
+            Some((2, 0))
+        } else if board.is_stalemate(side) {
+            Some((1, 0))
+        } else {
+            tablebase.probe_dtm(board).map(|dtm| match dtm.signum() {
+                -1 => (2, dtm),
+                0 => (1, 0),
+                _ => (0, dtm),
+            })
+        };
+        board.unmake_move(mv);
+        ranked.push((mv, rank?));
+    }
+
+    let best = ranked.iter().map(|&(_, rank)| rank).max()?;
+    Some(ranked.into_iter().filter(|&(_, rank)| rank == best).map(|(mv, _)| mv).collect())
+}
+
+// =============================================================================
+// Probe Results
+// =============================================================================
+
+/// Exact result of a position for the side to move
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TbValue {
+    /// Mates in this many plies
+    Win(u32),
+    /// Is mated in this many plies
+    Loss(u32),
+    Draw,
+}
+
+impl TbValue {
+    /// Decode an entry byte (INVALID must be handled by the caller)
+    fn from_byte(byte: u8) -> TbValue {
+        match byte {
+            DRAW => TbValue::Draw,
+            _ if byte.is_multiple_of(2) => TbValue::Win(byte as u32 - 1),
+            _ => TbValue::Loss(byte as u32 - 1),
+        }
+    }
+}
+
+// =============================================================================
+// Square Symmetry
+// =============================================================================
+
+/// Slot of each square of the a1-d1-d4 triangle, by bit index
+fn king_slot(index: usize) -> Option<usize> {
+    let (rank, file) = (7 - index / 8, index % 8);
+    if file > 3 || rank > file {
+        return None;
+    }
+    // Slots are numbered file by file: a1, b1, b2, c1, c2, c3, d1..d4
+    Some(file * (file + 1) / 2 + rank)
+}
+
+/// Bit index of the square in a king slot
+fn slot_square(slot: usize) -> usize {
+    (0..64).find(|&index| king_slot(index) == Some(slot)).expect("Invalid king slot")
+}
+
+/// Mirror along the a1-h8 diagonal
+fn transpose(index: usize) -> usize {
+    let (row, col) = (index / 8, index % 8);
+    (7 - col) * 8 + (7 - row)
+}
+
+/// Map all squares so the first one (the white king) lands in the triangle
+fn canonicalize(squares: &mut [usize]) {
+    let king = squares[0];
+    let mut flip = 0;
+    if king % 8 > 3 {
+        flip ^= 7; // Mirror files
+    }
+    if king / 8 < 4 {
+        flip ^= 56; // Mirror ranks
+    }
+    let king = king ^ flip;
+    let diagonal = 7 - king / 8 > king % 8;
+
+    for square in squares.iter_mut() {
+        *square ^= flip;
+        if diagonal {
+            *square = transpose(*square);
+        }
+    }
+}
+
+// =============================================================================
+// Tables
+// =============================================================================
+
+/// DTM table of one material
+pub struct Table {
+    material: Material,
+    /// Pieces in the order their squares are indexed
+    pieces: Vec<Piece>,
+    /// One entry per (side to move, king slot, other squares)
+    data: Vec<u8>,
+}
+
+impl Table {
+    fn new(material: Material, data: Vec<u8>) -> Table {
+        Table {
+            material,
+            pieces: material.pieces(),
+            data,
+        }
+    }
+
+    /// Number of entries for a piece count
+    fn size(piece_count: usize) -> usize {
+        2 * KING_SLOTS * 64usize.pow(piece_count as u32 - 1)
+    }
+
+    /// Material this table covers
+    pub fn material(&self) -> Material {
+        self.material
+    }
+
+    /// Entry index of a position given in table piece order
+    fn index(&self, squares: &[usize], side_to_move: Color) -> usize {
+        let mut squares = squares.to_vec();
+        canonicalize(&mut squares);
+        let slot = king_slot(squares[0]).expect("King not canonical");
+        squares[1..]
+            .iter()
+            .fold(side_to_move.index() * KING_SLOTS + slot, |index, &sq| index * 64 + sq)
+    }
+
+    /// Look up a position given in table piece order
+    /// None if the side not to move is in check
+    fn probe_squares(&self, squares: &[usize], side_to_move: Color) -> Option<TbValue> {
+        match self.data[self.index(squares, side_to_move)] {
+            INVALID => None,
+            byte => Some(TbValue::from_byte(byte)),
+        }
+    }
+
+    /// Longest win in the table, in plies
+    pub fn longest_mate(&self) -> Option<u32> {
+        self.data
+            .iter()
+            .filter(|&&byte| byte != INVALID)
+            .filter_map(|&byte| match TbValue::from_byte(byte) {
+                TbValue::Win(plies) => Some(plies),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// Write the table to a file
+    pub fn write(&self, path: &Path) -> Result<(), TablebaseError> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + self.pieces.len() + self.data.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.pieces.len() as u8);
+        bytes.extend(self.pieces.iter().map(|p| p.to_char() as u8));
+        bytes.extend_from_slice(&self.data);
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Read a table written by `write`
+    pub fn read(path: &Path) -> Result<Table, TablebaseError> {
+        let bytes = fs::read(path)?;
+        let bad = || TablebaseError::BadFile(path.display().to_string());
+
+        if bytes.len() < MAGIC.len() + 1 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(bad());
+        }
+        let count = bytes[MAGIC.len()] as usize;
+        let header = MAGIC.len() + 1 + count;
+        if !(2..=MAX_PIECES).contains(&count) || bytes.len() != header + Table::size(count) {
+            return Err(bad());
+        }
+
+        let mut pieces = Vec::with_capacity(count);
+        for &letter in &bytes[MAGIC.len() + 1..header] {
+            let letter = letter as char;
+            let pt = PieceType::from_letter(letter).ok_or_else(bad)?;
+            let color = if letter.is_ascii_uppercase() { Color::White } else { Color::Black };
+            pieces.push(Piece::new(pt, color));
+        }
+
+        let material = Material::of_pieces(&pieces);
+        if material.pieces() != pieces {
+            return Err(bad());
+        }
+        Ok(Table::new(material, bytes[header..].to_vec()))
+    }
+}
+
+/// A table found for a piece list, and how to reorder the list for it
+struct TableRef<'a> {
+    table: &'a Table,
+    /// order[k] = index into the piece list of the table's k-th piece
+    order: Vec<usize>,
+    /// The table has the colors exchanged (ranks are mirrored too)
+    swapped: bool,
+}
+
+impl TableRef<'_> {
+    fn probe(&self, squares: &[usize], side_to_move: Color) -> Option<TbValue> {
+        let mirror = if self.swapped { 56 } else { 0 };
+        let mut ordered = [0usize; MAX_PIECES];
+        for (k, &i) in self.order.iter().enumerate() {
+            ordered[k] = squares[i] ^ mirror;
+        }
+        let side_to_move = if self.swapped { side_to_move.opposite() } else { side_to_move };
+        self.table.probe_squares(&ordered[..self.order.len()], side_to_move)
+    }
+}
+
+/// Positions of a piece list in table order
+fn table_order(pieces: &[Piece]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..pieces.len()).collect();
+    order.sort_by_key(|&i| (pieces[i].color.index(), pieces[i].piece_type.index()));
+    order
+}
+
+// =============================================================================
+// FairyTablebase
+// =============================================================================
+
+/// A set of tables, looked up by material (either color can be the stronger side)
+#[derive(Default)]
+pub struct FairyTablebase {
+    tables: HashMap<Material, Table>,
+}
+
+impl FairyTablebase {
+    /// An empty tablebase
+    pub fn new() -> Self {
+        FairyTablebase::default()
+    }
+
+    /// Number of tables
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Whether there are no tables
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Add a table, replacing one of the same material
+    pub fn insert(&mut self, table: Table) {
+        self.tables.insert(table.material, table);
+    }
+
+    /// Table of a material, if present with these exact colors
+    pub fn get(&self, material: &Material) -> Option<&Table> {
+        self.tables.get(material)
+    }
+
+    /// Whether a material (or its color-swapped form) is covered
+    pub fn contains(&self, material: &Material) -> bool {
+        material.is_bare_kings()
+            || self.tables.contains_key(material)
+            || self.tables.contains_key(&material.swapped())
+    }
+
+    /// Load every table file in a directory
+    /// Syzygy files are rejected rather than skipped, so a Syzygy directory is not
+    /// mistaken for an empty set of tables
+    pub fn load_dir(dir: &Path) -> Result<FairyTablebase, TablebaseError> {
+        let mut tablebase = FairyTablebase::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            if extension == Some(FILE_EXTENSION) {
+                tablebase.insert(Table::read(&path)?);
+            } else if extension.is_some_and(|ext| SYZYGY_EXTENSIONS.contains(&ext)) {
+                return Err(TablebaseError::Syzygy(path.display().to_string()));
+            }
+        }
+        Ok(tablebase)
+    }
+
+    /// Load the tables of every directory in a path list ("dir1:dir2", ";" on Windows)
+    pub fn load_paths(paths: &str) -> Result<FairyTablebase, TablebaseError> {
+        let mut tablebase = FairyTablebase::new();
+        for dir in env::split_paths(paths) {
+            tablebase.tables.extend(FairyTablebase::load_dir(&dir)?.tables);
+        }
+        Ok(tablebase)
+    }
+
+    /// Find the table for a piece list
+    fn find(&self, pieces: &[Piece]) -> Option<TableRef<'_>> {
+        let material = Material::of_pieces(pieces);
+        if let Some(table) = self.tables.get(&material) {
+            return Some(TableRef { table, order: table_order(pieces), swapped: false });
+        }
+        let swapped: Vec<Piece> = pieces
+            .iter()
+            .map(|p| Piece::new(p.piece_type, p.color.opposite()))
+            .collect();
+        self.tables.get(&material.swapped()).map(|table| TableRef {
+            table,
+            order: table_order(&swapped),
+            swapped: true,
+        })
+    }
+
+    /// Exact value of a position for the side to move
+    /// None if it has castling rights, too many pieces or no table
+    pub fn probe(&self, board: &Board) -> Option<TbValue> {
+        if !board.castling_rights().is_empty() {
+            return None;
+        }
+
+        let mut pieces = Vec::with_capacity(MAX_PIECES);
+        let mut squares = Vec::with_capacity(MAX_PIECES);
+        for row in 0..8 {
+            for col in 0..8 {
+                if let Some(piece) = board.get_piece((row, col)) {
+                    if pieces.len() == MAX_PIECES {
+                        return None;
+                    }
+                    pieces.push(piece);
+                    squares.push(square_index((row, col)));
+                }
+            }
+        }
+
+        if Material::of_pieces(&pieces).is_bare_kings() {
+            return Some(TbValue::Draw);
+        }
+        self.find(&pieces)?.probe(&squares, board.side_to_move())
+    }
+
+    /// Build the table of a material, and first every missing table it captures into
+    /// Returns the materials built, in order
+    pub fn generate(&mut self, material: &Material) -> Result<Vec<Material>, TablebaseError> {
+        material.check_supported()?;
+        let mut built = Vec::new();
+        if self.contains(material) {
+            return Ok(built);
+        }
+
+        let pieces = material.pieces();
+        for (i, piece) in pieces.iter().enumerate() {
+            if piece.piece_type != PieceType::King {
+                let mut rest = pieces.clone();
+                rest.remove(i);
+                built.extend(self.generate(&Material::of_pieces(&rest))?);
+            }
+        }
+
+        let table = Generator::new(self, *material).run()?;
+        self.insert(table);
+        built.push(*material);
+        Ok(built)
+    }
+}
+
+/// The tables store distance to mate and ignore the fifty-move rule: the search
+/// checks the halfmove clock against the distance
+impl Tablebase for FairyTablebase {
+    fn max_pieces(&self) -> usize {
+        self.tables.keys().map(|m| m.pieces().len()).max().unwrap_or(0)
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        Some(match self.probe(board)? {
+            TbValue::Win(_) => Wdl::Win,
+            TbValue::Loss(_) => Wdl::Loss,
+            TbValue::Draw => Wdl::Draw,
+        })
+    }
+
+    fn probe_dtm(&self, board: &Board) -> Option<i32> {
+        match self.probe(board)? {
+            TbValue::Win(plies) => Some(plies as i32),
+            TbValue::Loss(0) => None, // Already mated
+            TbValue::Loss(plies) => Some(-(plies as i32)),
+            TbValue::Draw => Some(0),
+        }
+    }
+}
+
+// =============================================================================
+// Retrograde Generator
+// =============================================================================
+
+/// Unmoved-into marker: a move leads to a draw, so the position can never be lost
+const BLOCKED: u8 = u8::MAX;
+
+/// Working state for one material
+/// Positions are indexed without symmetry: side to move, then one square per piece
+struct Generator<'a> {
+    material: Material,
+    pieces: Vec<Piece>,
+    /// Tables of the captures, by captured piece (None: only the kings remain)
+    captures: Vec<Option<TableRef<'a>>>,
+    /// Entry bytes; DRAW means unresolved until the end
+    values: Vec<u8>,
+    /// Quiet moves whose result is still unknown (BLOCKED if one draws)
+    remaining: Vec<u8>,
+    /// Longest loss forced through a capture, in plies
+    floor: Vec<u8>,
+    /// Resolved positions whose predecessors are still to be visited, by plies
+    expand: Vec<Vec<u32>>,
+    /// Wins by capture into a lost sub-table position, by plies
+    candidates: Vec<Vec<u32>>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(tablebase: &'a FairyTablebase, material: Material) -> Self {
+        let pieces = material.pieces();
+        let captures = (0..pieces.len())
+            .map(|i| {
+                let mut rest = pieces.clone();
+                rest.remove(i);
+                if pieces[i].piece_type == PieceType::King || Material::of_pieces(&rest).is_bare_kings() {
+                    None
+                } else {
+                    Some(tablebase.find(&rest).expect("Sub-table generated first"))
+                }
+            })
+            .collect();
+        let size = 2 * 64usize.pow(pieces.len() as u32);
+
+        Generator {
+            material,
+            pieces,
+            captures,
+            values: vec![DRAW; size],
+            remaining: vec![0; size],
+            floor: vec![0; size],
+            expand: Vec::new(),
+            candidates: Vec::new(),
+        }
+    }
+
+    /// Squares and side to move of a position index
+    fn decode(&self, mut index: usize, squares: &mut [usize]) -> Color {
+        for square in squares.iter_mut().rev() {
+            *square = index % 64;
+            index /= 64;
+        }
+        if index == 0 { Color::White } else { Color::Black }
+    }
+
+    fn encode(squares: &[usize], side_to_move: Color) -> usize {
+        squares.iter().fold(side_to_move.index(), |index, &sq| index * 64 + sq)
+    }
+
+    /// Whether a square is attacked by a color; `skip` is a captured piece
+    fn attacked(&self, squares: &[usize], occupancy: Bitboard, target: usize, by: Color, skip: Option<usize>) -> bool {
+        self.pieces.iter().enumerate().any(|(i, piece)| {
+            piece.color == by
+                && Some(i) != skip
+                && piece_attacks(piece.piece_type, by, squares[i], occupancy) & (1u64 << target) != 0
+        })
+    }
+
+    fn king(&self, color: Color) -> usize {
+        self.pieces
+            .iter()
+            .position(|p| p.color == color && p.piece_type == PieceType::King)
+            .expect("Material has both kings")
+    }
+
+    fn push(buckets: &mut Vec<Vec<u32>>, plies: usize, index: usize) -> Result<(), ()> {
+        if plies > MAX_PLIES {
+            return Err(());
+        }
+        if buckets.len() <= plies {
+            buckets.resize(plies + 1, Vec::new());
+        }
+        buckets[plies].push(index as u32);
+        Ok(())
+    }
+
+    /// Mark invalid positions, mates and stalemates, resolve captures and count quiet moves
+    fn initialize(&mut self) -> Result<(), ()> {
+        let n = self.pieces.len();
+        let mut squares = [0usize; MAX_PIECES];
+        let mut child = [0usize; MAX_PIECES];
+
+        for index in 0..self.values.len() {
+            let us = self.decode(index, &mut squares[..n]);
+            let squares = &squares[..n];
+            let them = us.opposite();
+
+            let mut occupancy: Bitboard = 0;
+            for &sq in squares {
+                occupancy |= 1u64 << sq;
+            }
+            if occupancy.count_ones() as usize != n
+                || self.attacked(squares, occupancy, squares[self.king(them)], us, None)
+            {
+                self.values[index] = INVALID;
+                continue;
+            }
+
+            let our_king = self.king(us);
+            let their_occupancy = self
+                .pieces
+                .iter()
+                .zip(squares)
+                .filter(|(p, _)| p.color == them)
+                .fold(0u64, |bb, (_, &sq)| bb | 1u64 << sq);
+
+            let mut legal = 0;
+            let mut quiet = 0u8;
+            let mut blocked = false;
+            let mut floor = 0;
+
+            for i in 0..n {
+                let piece = self.pieces[i];
+                if piece.color != us {
+                    continue;
+                }
+                let from = squares[i];
+                let targets = (piece_attacks(piece.piece_type, us, from, occupancy) & their_occupancy)
+                    | (piece_quiet_moves(piece.piece_type, us, from, occupancy) & !occupancy);
+
+                for to in bits(targets) {
+                    let captured = (0..n).find(|&j| j != i && squares[j] == to);
+                    let after = (occupancy & !(1u64 << from)) | 1u64 << to;
+                    child[..n].copy_from_slice(squares);
+                    child[i] = to;
+                    let king_square = child[our_king];
+                    if self.attacked(&child[..n], after, king_square, them, captured) {
+                        continue;
+                    }
+                    legal += 1;
+
+                    let Some(j) = captured else {
+                        quiet += 1;
+                        continue;
+                    };
+                    let mut rest = [0usize; MAX_PIECES];
+                    let mut k = 0;
+                    for (m, &sq) in child[..n].iter().enumerate() {
+                        if m != j {
+                            rest[k] = sq;
+                            k += 1;
+                        }
+                    }
+                    let value = match &self.captures[j] {
+                        Some(table) => table.probe(&rest[..k], them).expect("Legal capture"),
+                        None => TbValue::Draw,
+                    };
+                    match value {
+                        TbValue::Loss(plies) => {
+                            blocked = true; // At least a win: never lost
+                            Self::push(&mut self.candidates, plies as usize + 1, index)?;
+                        }
+                        TbValue::Win(plies) => floor = floor.max(plies as usize + 1),
+                        TbValue::Draw => blocked = true,
+                    }
+                }
+            }
+
+            if legal == 0 {
+                if self.attacked(squares, occupancy, squares[our_king], them, None) {
+                    self.values[index] = 1; // Mated: loss in 0 plies
+                    Self::push(&mut self.expand, 0, index)?;
+                }
+                continue; // Stalemate stays a draw
+            }
+
+            self.floor[index] = floor as u8;
+            if blocked {
+                self.remaining[index] = BLOCKED;
+            } else if quiet == 0 {
+                // Every move captures into a lost position
+                self.values[index] = floor as u8 + 1;
+                Self::push(&mut self.expand, floor, index)?;
+            } else {
+                self.remaining[index] = quiet;
+            }
+        }
+        Ok(())
+    }
+
+    /// Visit the positions one quiet move before a resolved position
+    fn expand(&mut self, index: usize, plies: usize) -> Result<(), ()> {
+        let n = self.pieces.len();
+        let mut squares = [0usize; MAX_PIECES];
+        let moved = self.decode(index, &mut squares[..n]);
+        let mover = moved.opposite();
+        let occupancy = squares[..n].iter().fold(0u64, |bb, &sq| bb | 1u64 << sq);
+
+        for i in 0..n {
+            let piece = self.pieces[i];
+            if piece.color != mover {
+                continue;
+            }
+            // All supported pieces move symmetrically, so unmoves are moves
+            let to = squares[i];
+            for from in bits(piece_quiet_moves(piece.piece_type, mover, to, occupancy) & !occupancy) {
+                squares[i] = from;
+                let parent = Self::encode(&squares[..n], mover);
+                squares[i] = to;
+
+                if self.values[parent] != DRAW {
+                    continue; // Resolved or invalid
+                }
+                if plies.is_multiple_of(2) {
+                    // Moves into a lost position: won
+                    self.values[parent] = (plies + 2) as u8;
+                    Self::push(&mut self.expand, plies + 1, parent)?;
+                } else if self.remaining[parent] != BLOCKED {
+                    self.remaining[parent] -= 1;
+                    if self.remaining[parent] == 0 {
+                        // Every move loses: as late as possible
+                        let loss = (plies + 1).max(self.floor[parent] as usize);
+                        self.values[parent] = (loss + 1) as u8;
+                        Self::push(&mut self.expand, loss, parent)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve plies 0, 1, 2, ... until nothing changes
+    fn solve(&mut self) -> Result<(), ()> {
+        let mut plies = 0;
+        while plies < self.expand.len() || plies < self.candidates.len() {
+            if let Some(candidates) = self.candidates.get_mut(plies) {
+                for index in std::mem::take(candidates) {
+                    let index = index as usize;
+                    if self.values[index] == DRAW {
+                        self.values[index] = (plies + 1) as u8;
+                        Self::push(&mut self.expand, plies, index)?;
+                    }
+                }
+            }
+            if let Some(resolved) = self.expand.get_mut(plies) {
+                for index in std::mem::take(resolved) {
+                    self.expand(index as usize, plies)?;
+                }
+            }
+            plies += 1;
+        }
+        Ok(())
+    }
+
+    /// Generate the table and pack it with the king symmetry
+    fn run(mut self) -> Result<Table, TablebaseError> {
+        let name = self.material.name();
+        let too_deep = |_| TablebaseError::TooDeep(name.clone());
+        self.initialize().map_err(too_deep)?;
+        self.solve().map_err(too_deep)?;
+
+        let n = self.pieces.len();
+        let others = 64usize.pow(n as u32 - 1);
+        let mut data = vec![DRAW; Table::size(n)];
+        let mut squares = [0usize; MAX_PIECES];
+        for (entry, byte) in data.iter_mut().enumerate() {
+            let mut rest = entry % others;
+            for square in squares[1..n].iter_mut().rev() {
+                *square = rest % 64;
+                rest /= 64;
+            }
+            let head = entry / others;
+            squares[0] = slot_square(head % KING_SLOTS);
+            let side_to_move = if head / KING_SLOTS == 0 { Color::White } else { Color::Black };
+            *byte = self.values[Self::encode(&squares[..n], side_to_move)];
+        }
+        Ok(Table::new(self.material, data))
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::OnceLock;
+
+    /// KRvK and KAvK, built once for all tests
+    fn tablebase() -> &'static FairyTablebase {
+        static TABLEBASE: OnceLock<FairyTablebase> = OnceLock::new();
+        TABLEBASE.get_or_init(|| {
+            let mut tablebase = FairyTablebase::new();
+            for name in ["KRvK", "KAvK"] {
+                tablebase.generate(&Material::parse(name).unwrap()).unwrap();
+            }
+            tablebase
+        })
+    }
+
+    fn probe(fen: &str) -> Option<TbValue> {
+        tablebase().probe(&Board::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn test_material_names() {
+        let material = Material::parse("KAvKR").unwrap();
+        assert_eq!(material.name(), "KAvKR");
+        assert_eq!(material.swapped().name(), "KRvKA");
+        assert_eq!(Material::parse("KRAvK").unwrap().name(), "KARvK");
+        assert_eq!(material.pieces().len(), 4);
+    }
+
+    #[test]
+    fn test_bad_material() {
+        assert!(matches!(Material::parse("KAKR"), Err(TablebaseError::BadMaterial(_))));
+        assert!(matches!(Material::parse("AvKR"), Err(TablebaseError::BadMaterial(_))));
+        assert!(matches!(Material::parse("KZvK"), Err(TablebaseError::BadMaterial(_))));
+        assert!(matches!(Material::parse("KPvK"), Err(TablebaseError::UnsupportedPiece(PieceType::Pawn))));
+        assert!(matches!(Material::parse("KAAvKR"), Err(TablebaseError::TooManyPieces(5))));
+    }
+
+    #[test]
+    fn test_king_slots_cover_the_triangle() {
+        let slots: Vec<usize> = (0..64).filter_map(king_slot).collect();
+        assert_eq!(slots.len(), KING_SLOTS);
+        for slot in 0..KING_SLOTS {
+            assert_eq!(king_slot(slot_square(slot)), Some(slot));
+        }
+
+        // A king on g7 maps to b2
+        let mut squares = [square_index((1, 6))];
+        canonicalize(&mut squares);
+        assert_eq!(squares[0], square_index((6, 1)));
+    }
+
+    #[test]
+    fn test_longest_rook_mate() {
+        // KRK is mate in at most 16 moves
+        let table = tablebase().get(&Material::parse("KRvK").unwrap()).unwrap();
+        assert_eq!(table.longest_mate(), Some(31));
+    }
+
+    #[test]
+    fn test_probe_results() {
+        // Mate in one: Ra8#
+        assert_eq!(probe("6k1/8/6K1/8/8/8/8/R7 w - - 0 1"), Some(TbValue::Win(1)));
+        // Already mated
+        assert_eq!(probe("R5k1/8/6K1/8/8/8/8/8 b - - 0 1"), Some(TbValue::Loss(0)));
+        // Black takes the undefended rook
+        assert_eq!(probe("6k1/6R1/8/8/8/8/8/K7 b - - 0 1"), Some(TbValue::Draw));
+        // Stalemate
+        assert_eq!(probe("7k/6R1/6K1/8/8/8/8/8 b - - 0 1"), Some(TbValue::Draw));
+        // Bare kings
+        assert_eq!(probe("8/8/4k3/8/8/4K3/8/8 w - - 0 1"), Some(TbValue::Draw));
+    }
+
+    #[test]
+    fn test_probe_swapped_colors() {
+        let white = probe("6k1/8/6K1/8/8/8/8/R7 w - - 0 1");
+        let black = probe("r7/8/8/8/8/6k1/8/6K1 b - - 0 1");
+        assert_eq!(white, black);
+    }
+
+    #[test]
+    fn test_probe_unknown_material() {
+        assert_eq!(probe("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1"), None);
+        assert_eq!(probe("r3k3/8/8/8/8/8/8/3AK3 w - - 0 1"), None);
+    }
+
+    #[test]
+    fn test_amazon_mates_faster_than_rook() {
+        let amazon = tablebase().get(&Material::parse("KAvK").unwrap()).unwrap();
+        let longest = amazon.longest_mate().unwrap();
+        assert!(longest % 2 == 1 && longest < 31, "longest mate {}", longest);
+    }
+
+    #[test]
+    fn test_filter_root_moves_keeps_fastest_mates() {
+        let mut board = Board::from_fen("6k1/8/6K1/8/8/8/8/R7 w - - 0 1").unwrap();
+        let moves = board.generate_legal_moves();
+        let best = filter_root_moves(tablebase(), &mut board, &moves).unwrap();
+        assert_eq!(best.len(), 1);
+        board.apply_move(best[0]);
+        assert!(board.is_checkmate(Color::Black));
+    }
+
+    #[test]
+    fn test_probe_wdl_and_dtm() {
+        let tb: &dyn Tablebase = tablebase();
+        let board = Board::from_fen("6k1/8/6K1/8/8/8/8/R7 w - - 0 1").unwrap();
+        assert_eq!(tb.probe_wdl(&board), Some(Wdl::Win));
+        assert_eq!(tb.probe_dtm(&board), Some(1));
+
+        let board = Board::from_fen("6k1/8/5K2/8/8/8/8/R7 b - - 0 1").unwrap();
+        assert_eq!(tb.probe_wdl(&board), Some(Wdl::Loss));
+        assert!(tb.probe_dtm(&board).unwrap() < 0);
+
+        let board = Board::from_fen("6k1/6R1/8/8/8/8/8/K7 b - - 0 1").unwrap();
+        assert_eq!(tb.probe_wdl(&board), Some(Wdl::Draw));
+        assert_eq!(tb.probe_dtm(&board), Some(0));
+
+        assert_eq!(tb.max_pieces(), 3);
+        assert_eq!(FairyTablebase::new().max_pieces(), 0);
+    }
+
+    #[test]
+    fn test_search_plays_tablebase_mate() {
+        use crate::evaluation::HandCrafted;
+        use crate::search::{search, SearchLimits, SearchSignals, TB_WIN_SCORE};
+        use crate::transposition::TranspositionTable;
+
+        // A long rook mate, far beyond the search depth
+        let mut board = Board::from_fen("8/8/8/8/3k4/8/8/KR6 w - - 0 1").unwrap();
+        let Some(TbValue::Win(plies)) = tablebase().probe(&board) else {
+            panic!("KRK with White to move is won");
+        };
+        let probe = TbProbe { tablebase: tablebase(), probe_depth: 1 };
+        let mut tt = TranspositionTable::new(1);
+        let info = search(
+            &mut board,
+            &SearchLimits::depth(2),
+            &mut tt,
+            &HandCrafted::default(),
+            Some(probe),
+            &SearchSignals::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(info.score, TB_WIN_SCORE - plies as i32);
+
+        // The move played shortens the mate
+        board.apply_move(info.best_move());
+        assert_eq!(tablebase().probe(&board), Some(TbValue::Loss(plies - 1)));
+    }
+
+    #[test]
+    fn test_search_distrusts_mates_past_fifty_moves() {
+        use crate::evaluation::HandCrafted;
+        use crate::search::{search, SearchLimits, SearchSignals, TB_BOUND};
+        use crate::transposition::TranspositionTable;
+
+        // The mate is too far away for the halfmove clock: no tablebase score
+        let mut board = Board::from_fen("8/8/8/8/3k4/8/8/KR6 w - - 90 1").unwrap();
+        let Some(TbValue::Win(plies)) = tablebase().probe(&board) else {
+            panic!("KRK with White to move is won");
+        };
+        assert!(90 + plies > 100);
+        let probe = TbProbe { tablebase: tablebase(), probe_depth: 1 };
+        let info = search(
+            &mut board,
+            &SearchLimits::depth(2),
+            &mut TranspositionTable::new(1),
+            &HandCrafted::default(),
+            Some(probe),
+            &SearchSignals::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert!(info.score.abs() < TB_BOUND, "score {}", info.score);
+    }
+
+    #[test]
+    fn test_search_converts_tablebase_win() {
+        use crate::evaluation::HandCrafted;
+        use crate::search::{search, SearchLimits, SearchSignals};
+        use crate::transposition::TranspositionTable;
+
+        // Both sides play from the tables: the mate comes exactly when the table says
+        let mut board = Board::from_fen("8/8/8/8/3k4/8/8/KR6 w - - 0 1").unwrap();
+        let Some(TbValue::Win(plies)) = tablebase().probe(&board) else {
+            panic!("KRK with White to move is won");
+        };
+        let probe = TbProbe { tablebase: tablebase(), probe_depth: 1 };
+        let mut tt = TranspositionTable::new(1);
+        let mut played = 0;
+        while let Some(info) = search(
+            &mut board,
+            &SearchLimits::depth(2),
+            &mut tt,
+            &HandCrafted::default(),
+            Some(probe),
+            &SearchSignals::default(),
+            &mut |_| {},
+        ) {
+            board.apply_move(info.best_move());
+            played += 1;
+            assert!(played <= plies, "no mate after {} plies", played);
+        }
+        assert!(board.is_checkmate(Color::Black));
+        assert_eq!(played, plies);
+    }
+
+    #[test]
+    fn test_load_paths() {
+        let dirs: Vec<_> = ["a", "b"]
+            .iter()
+            .map(|name| std::env::temp_dir().join(format!("tablebase_paths_{}_{}", std::process::id(), name)))
+            .collect();
+        for (dir, name) in dirs.iter().zip(["KRvK", "KAvK"]) {
+            fs::create_dir_all(dir).unwrap();
+            let table = tablebase().get(&Material::parse(name).unwrap()).unwrap();
+            table.write(&dir.join(format!("{}.{}", name, FILE_EXTENSION))).unwrap();
+        }
+
+        let paths = env::join_paths(&dirs).unwrap();
+        let loaded = FairyTablebase::load_paths(paths.to_str().unwrap());
+        for dir in &dirs {
+            fs::remove_dir_all(dir).unwrap();
+        }
+        assert_eq!(loaded.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_load_dir_rejects_syzygy_files() {
+        let dir = std::env::temp_dir().join(format!("tablebase_syzygy_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("KRvK.rtbw"), b"").unwrap();
+        let loaded = FairyTablebase::load_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(loaded, Err(TablebaseError::Syzygy(_))));
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let material = Material::parse("KRvK").unwrap();
+        let table = tablebase().get(&material).unwrap();
+        let path = std::env::temp_dir().join(format!("tablebase_test_{}.{}", std::process::id(), FILE_EXTENSION));
+        table.write(&path).unwrap();
+        let read = Table::read(&path);
+        fs::remove_file(&path).unwrap();
+
+        let read = read.unwrap();
+        assert_eq!(read.material(), material);
+        assert!(read.data == table.data);
+    }
+
+    #[test]
+    fn test_read_rejects_other_files() {
+        let path = std::env::temp_dir().join(format!("tablebase_bad_{}.{}", std::process::id(), FILE_EXTENSION));
+        fs::write(&path, b"not a table").unwrap();
+        let result = Table::read(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(TablebaseError::BadFile(_))));
+    }
+}
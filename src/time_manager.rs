@@ -0,0 +1,232 @@
+// Time management for iterative deepening
+// Turns the UCI clock fields into per-move time budgets
+
+use crate::board::Color;
+use crate::search::SearchLimits;
+use std::time::{Duration, Instant};
+
+/// Safety margin subtracted from every budget (GUI and pipe latency)
+const MOVE_OVERHEAD_MS: u64 = 30;
+
+/// Assumed number of moves until the next time control when
+/// the GUI does not send `movestogo` (sudden death / increment games)
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// Never plan to think for less than this
+const MIN_THINK_MS: u64 = 5;
+
+/// Tracks elapsed time and decides when the search has to stop
+///
+/// - `optimum`: target time for this move. A new iteration is only
+///   started while less than half of it is used, because the next
+///   iteration usually costs more than all previous ones together.
+/// - `maximum`: hard limit. The running iteration is aborted once it is hit.
+pub struct TimeManager {
+    start: Instant,
+    optimum: Option<Duration>,
+    maximum: Option<Duration>,
+}
+
+impl TimeManager {
+    /// Create a time manager for the given limits and side to move
+    pub fn new(limits: &SearchLimits, side: Color) -> Self {
+        let start = Instant::now();
+
+        // Searching until "stop": the clock fields (if any) do not apply
+        if limits.infinite {
+            return TimeManager::unlimited();
+        }
+
+        // Fixed time per move
+        if let Some(movetime) = limits.movetime {
+            let budget = Duration::from_millis(
+                movetime.saturating_sub(MOVE_OVERHEAD_MS).max(MIN_THINK_MS),
+            );
+            return TimeManager {
+                start,
+                optimum: Some(budget),
+                maximum: Some(budget),
+            };
+        }
+
+        // Clock based allocation
+        let (time_left, increment) = match side {
+            Color::White => (limits.wtime, limits.winc.unwrap_or(0)),
+            Color::Black => (limits.btime, limits.binc.unwrap_or(0)),
+        };
+
+        let time_left = match time_left {
+            Some(t) => t,
+            None => return TimeManager::unlimited(),
+        };
+
+        let usable = time_left.saturating_sub(MOVE_OVERHEAD_MS).max(MIN_THINK_MS);
+        let moves_to_go = limits
+            .movestogo
+            .map(|m| m.max(1) as u64)
+            .unwrap_or(DEFAULT_MOVES_TO_GO);
+
+        // Spread the remaining time over the moves to go, and spend most of the increment
+        let target = usable / moves_to_go + increment * 3 / 4;
+
+        // Never risk more than half of the clock, except on the last move before the time control
+        let maximum = if moves_to_go == 1 {
+            usable
+        } else {
+            (target * 3).min(usable / 2)
+        }
+        .max(MIN_THINK_MS);
+        let optimum = target.min(maximum).max(MIN_THINK_MS);
+
+        TimeManager {
+            start,
+            optimum: Some(Duration::from_millis(optimum)),
+            maximum: Some(Duration::from_millis(maximum)),
+        }
+    }
+
+    /// A time manager that never stops the search (depth-limited search)
+    pub fn unlimited() -> Self {
+        TimeManager {
+            start: Instant::now(),
+            optimum: None,
+            maximum: None,
+        }
+    }
+
+    /// Time spent since the search started (or since the last restart)
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Start counting from now, keeping the budgets
+    /// Used on "ponderhit": our clock only starts running when the opponent has moved
+    pub fn restart(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// Whether the search is limited by time at all
+    pub fn is_timed(&self) -> bool {
+        self.maximum.is_some()
+    }
+
+    /// Whether there is enough time left to start another iteration
+    pub fn should_start_iteration(&self) -> bool {
+        match self.optimum {
+            Some(optimum) => self.elapsed() < optimum / 2,
+            None => true,
+        }
+    }
+
+    /// Whether the hard limit is reached and the current iteration must be aborted
+    pub fn hard_limit_reached(&self) -> bool {
+        match self.maximum {
+            Some(maximum) => self.elapsed() >= maximum,
+            None => false,
+        }
+    }
+
+    /// Planned time for this move (None = unlimited)
+    pub fn optimum(&self) -> Option<Duration> {
+        self.optimum
+    }
+
+    /// Hard time limit for this move (None = unlimited)
+    pub fn maximum(&self) -> Option<Duration> {
+        self.maximum
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_clock_is_unlimited() {
+        let limits = SearchLimits::depth(4);
+        let tm = TimeManager::new(&limits, Color::White);
+        assert!(!tm.is_timed());
+        assert!(tm.should_start_iteration());
+        assert!(!tm.hard_limit_reached());
+    }
+
+    #[test]
+    fn test_infinite_ignores_clock() {
+        let limits = SearchLimits {
+            wtime: Some(1_000),
+            infinite: true,
+            ..SearchLimits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        assert!(!tm.is_timed());
+    }
+
+    #[test]
+    fn test_movetime_budget() {
+        let limits = SearchLimits {
+            movetime: Some(1000),
+            ..SearchLimits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        let budget = Duration::from_millis(1000 - MOVE_OVERHEAD_MS);
+        assert_eq!(tm.optimum(), Some(budget));
+        assert_eq!(tm.maximum(), Some(budget));
+    }
+
+    #[test]
+    fn test_uses_clock_of_side_to_move() {
+        let limits = SearchLimits {
+            wtime: Some(60_000),
+            btime: Some(1_000),
+            ..SearchLimits::default()
+        };
+        let white = TimeManager::new(&limits, Color::White);
+        let black = TimeManager::new(&limits, Color::Black);
+        assert!(white.optimum().unwrap() > black.optimum().unwrap());
+    }
+
+    #[test]
+    fn test_clock_budget_within_remaining_time() {
+        // 10+0.1 game with only 200ms left: must never plan past the clock
+        let limits = SearchLimits {
+            wtime: Some(200),
+            winc: Some(100),
+            ..SearchLimits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        let maximum = tm.maximum().unwrap();
+        assert!(maximum < Duration::from_millis(200), "maximum: {:?}", maximum);
+        assert!(tm.optimum().unwrap() <= maximum);
+    }
+
+    #[test]
+    fn test_increment_is_used() {
+        let base = SearchLimits {
+            btime: Some(10_000),
+            ..SearchLimits::default()
+        };
+        let with_inc = SearchLimits {
+            btime: Some(10_000),
+            binc: Some(100),
+            ..SearchLimits::default()
+        };
+        let a = TimeManager::new(&base, Color::Black);
+        let b = TimeManager::new(&with_inc, Color::Black);
+        assert!(b.optimum().unwrap() > a.optimum().unwrap());
+    }
+
+    #[test]
+    fn test_last_move_before_time_control_may_use_clock() {
+        let limits = SearchLimits {
+            wtime: Some(5_000),
+            movestogo: Some(1),
+            ..SearchLimits::default()
+        };
+        let tm = TimeManager::new(&limits, Color::White);
+        assert!(tm.maximum().unwrap() > Duration::from_millis(2_500));
+    }
+}
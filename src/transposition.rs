@@ -0,0 +1,237 @@
+// Transposition table
+// Caches search results by Zobrist key so positions reached through different
+// move orders (very common with lone kings shuffling around) are searched once
+
+use crate::board::Move;
+use crate::search::TB_BOUND;
+
+/// Default table size in megabytes (UCI "Hash" option)
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// Smallest and largest table sizes accepted through UCI
+pub const MIN_HASH_MB: usize = 1;
+pub const MAX_HASH_MB: usize = 1024;
+
+/// How the stored score relates to the true score of the position
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Bound {
+    /// Score is exact (a PV node)
+    Exact,
+    /// Score is a lower bound (the search failed high / beta cutoff)
+    Lower,
+    /// Score is an upper bound (no move raised alpha)
+    Upper,
+}
+
+/// A single table entry
+#[derive(Copy, Clone, Debug)]
+pub struct TtEntry {
+    /// Full Zobrist key, to detect index collisions
+    pub key: u64,
+    /// Remaining depth the position was searched to
+    pub depth: i32,
+    pub bound: Bound,
+    /// Score, with mate scores stored relative to this node (see `score_to_tt`)
+    pub score: i32,
+    /// Best (or refutation) move found, tried first when the position comes up again
+    pub best_move: Option<Move>,
+}
+
+/// Fixed-size hash table indexed by the low bits of the Zobrist key
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+    /// entries.len() - 1 (length is always a power of two)
+    mask: usize,
+}
+
+impl TranspositionTable {
+    /// Create a table using at most `size_mb` megabytes
+    pub fn new(size_mb: usize) -> Self {
+        let size_mb = size_mb.clamp(MIN_HASH_MB, MAX_HASH_MB);
+        let bytes = size_mb * 1024 * 1024;
+        let max_entries = bytes / std::mem::size_of::<Option<TtEntry>>();
+
+        // Round down to a power of two so the index is a simple mask
+        let count = if max_entries.is_power_of_two() {
+            max_entries
+        } else {
+            max_entries.next_power_of_two() / 2
+        };
+
+        TranspositionTable {
+            entries: vec![None; count],
+            mask: count - 1,
+        }
+    }
+
+    /// Reallocate the table with a new size (clears all entries)
+    pub fn resize(&mut self, size_mb: usize) {
+        *self = TranspositionTable::new(size_mb);
+    }
+
+    /// Remove all entries (e.g. on "ucinewgame" or "Clear Hash")
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+    }
+
+    /// Number of entries the table can hold
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Look up a position; returns None if it is not stored
+    pub fn probe(&self, key: u64) -> Option<TtEntry> {
+        match self.entries[key as usize & self.mask] {
+            Some(entry) if entry.key == key => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Store a search result
+    /// A different position in the slot is always replaced; the same position
+    /// is only overwritten by a search that is at least as deep
+    pub fn store(&mut self, key: u64, depth: i32, bound: Bound, score: i32, best_move: Option<Move>) {
+        let slot = &mut self.entries[key as usize & self.mask];
+
+        if let Some(existing) = slot {
+            if existing.key == key && existing.depth > depth {
+                return;
+            }
+        }
+
+        *slot = Some(TtEntry {
+            key,
+            depth,
+            bound,
+            score,
+            best_move,
+        });
+    }
+
+    /// Permille of the table in use (UCI "hashfull"), sampled from the first 1000 slots
+    pub fn hashfull(&self) -> usize {
+        let sample = self.entries.len().min(1000);
+        let used = self.entries[..sample].iter().filter(|e| e.is_some()).count();
+        used * 1000 / sample
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+// =============================================================================
+// Mate Score Adjustment
+// =============================================================================
+
+/// Convert a search score to the form stored in the table
+///
+/// Mate scores in search are relative to the root (`CHECKMATE_SCORE - ply`),
+/// but the same position can be reached at a different ply later. The table
+/// stores them relative to the node itself, so "mate in 3 from here" stays
+/// "mate in 3 from here" wherever the position is found again.
+/// Tablebase scores count their distance from the root the same way.
+pub fn score_to_tt(score: i32, ply: i32) -> i32 {
+    if score >= TB_BOUND {
+        score + ply
+    } else if score <= -TB_BOUND {
+        score - ply
+    } else {
+        score
+    }
+}
+
+/// Convert a stored score back to a root-relative search score
+pub fn score_from_tt(score: i32, ply: i32) -> i32 {
+    if score >= TB_BOUND {
+        score - ply
+    } else if score <= -TB_BOUND {
+        score + ply
+    } else {
+        score
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{CHECKMATE_SCORE, TB_WIN_SCORE};
+
+    #[test]
+    fn test_size_is_power_of_two() {
+        let tt = TranspositionTable::new(1);
+        assert!(tt.capacity().is_power_of_two());
+        assert!(tt.capacity() * std::mem::size_of::<Option<TtEntry>>() <= 1024 * 1024);
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let mut tt = TranspositionTable::new(1);
+        let mv = Move::new((7, 3), (3, 7));
+        tt.store(0xDEAD_BEEF, 5, Bound::Exact, 120, Some(mv));
+
+        let entry = tt.probe(0xDEAD_BEEF).expect("Entry should be stored");
+        assert_eq!(entry.depth, 5);
+        assert_eq!(entry.bound, Bound::Exact);
+        assert_eq!(entry.score, 120);
+        assert_eq!(entry.best_move.map(|m| (m.from, m.to)), Some(((7, 3), (3, 7))));
+    }
+
+    #[test]
+    fn test_probe_rejects_colliding_key() {
+        let mut tt = TranspositionTable::new(1);
+        let key = 0x1234;
+        // Same slot (identical low bits), different key
+        let other = key + (tt.capacity() as u64);
+        tt.store(key, 3, Bound::Lower, 50, None);
+        assert!(tt.probe(other).is_none());
+    }
+
+    #[test]
+    fn test_shallower_search_does_not_overwrite() {
+        let mut tt = TranspositionTable::new(1);
+        tt.store(42, 6, Bound::Exact, 10, None);
+        tt.store(42, 2, Bound::Upper, -30, None);
+        assert_eq!(tt.probe(42).unwrap().depth, 6);
+
+        tt.store(42, 7, Bound::Lower, 99, None);
+        assert_eq!(tt.probe(42).unwrap().score, 99);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut tt = TranspositionTable::new(1);
+        tt.store(42, 1, Bound::Exact, 0, None);
+        tt.clear();
+        assert!(tt.probe(42).is_none());
+        assert_eq!(tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_mate_score_round_trip() {
+        // Mate found 7 plies from the root, stored at ply 3,
+        // then probed from a node at ply 5
+        let score = CHECKMATE_SCORE - 7;
+        let stored = score_to_tt(score, 3);
+        assert_eq!(stored, CHECKMATE_SCORE - 4, "Stored as mate in 4 plies from the node");
+        assert_eq!(score_from_tt(stored, 5), CHECKMATE_SCORE - 9);
+
+        let mated = -CHECKMATE_SCORE + 6;
+        assert_eq!(score_from_tt(score_to_tt(mated, 2), 2), mated);
+
+        // Tablebase wins keep their distance from the node too
+        let tb_win = TB_WIN_SCORE - 12;
+        assert_eq!(score_from_tt(score_to_tt(tb_win, 4), 6), TB_WIN_SCORE - 14);
+        assert_eq!(score_from_tt(score_to_tt(-tb_win, 4), 6), -TB_WIN_SCORE + 14);
+
+        // Normal scores are untouched
+        assert_eq!(score_to_tt(250, 10), 250);
+        assert_eq!(score_from_tt(-250, 10), -250);
+    }
+}
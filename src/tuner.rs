@@ -0,0 +1,476 @@
+// Texel tuning
+// Fits the evaluation weights to game results: each training position is
+// labelled with the result of the game it came from, the evaluation is mapped
+// to an expected score with a sigmoid, and a local search over the weights
+// minimises the mean squared difference between the two.
+
+use crate::board::{Board, Color, Move};
+use crate::eval_params::{EvalParams, Param};
+use crate::evaluation::{Evaluator, HandCrafted};
+use crate::pgn::PgnGame;
+use crate::search::MATE_BOUND;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+/// Capture depth when resolving positions to a quiet leaf
+const MAX_QUIESCENCE_PLY: usize = 16;
+
+/// Bounds of the search for the sigmoid scaling constant
+const K_RANGE: (f64, f64) = (0.0, 10.0);
+
+/// Golden-section steps when fitting K
+const K_ITERATIONS: usize = 40;
+
+/// A training position and the result of its game, from White's point of view
+/// (1.0 = White won, 0.5 = draw, 0.0 = Black won)
+#[derive(Clone)]
+pub struct TuningPosition {
+    pub board: Board,
+    pub result: f64,
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Reasons a training set cannot be read
+#[derive(Debug)]
+pub enum TuneError {
+    Io(std::io::Error),
+    /// Malformed line (line number, description)
+    BadLine(usize, String),
+}
+
+impl std::fmt::Display for TuneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TuneError::Io(e) => write!(f, "{}", e),
+            TuneError::BadLine(line, message) => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for TuneError {}
+
+impl From<std::io::Error> for TuneError {
+    fn from(e: std::io::Error) -> Self {
+        TuneError::Io(e)
+    }
+}
+
+// =============================================================================
+// Training Data
+// =============================================================================
+
+/// Parse a game result: "1-0", "0-1", "1/2-1/2", or a number such as "[0.5]"
+/// A number needs brackets or quotes unless `bare_numbers` is set: on its own it
+/// may be a FEN's halfmove or fullmove counter
+fn parse_result(token: &str, bare_numbers: bool) -> Option<f64> {
+    let token = token.trim_end_matches(';');
+    let inner = token.trim_matches(|c| matches!(c, '"' | '[' | ']' | '(' | ')'));
+    let delimited = inner.len() < token.len();
+    match inner {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" | "½-½" => Some(0.5),
+        _ if delimited || bare_numbers => inner.parse::<f64>().ok().filter(|r| (0.0..=1.0).contains(r)),
+        _ => None,
+    }
+}
+
+/// Parse one EPD line labelled with its game result, either as a `c9 "1-0";`
+/// opcode or as a trailing result such as `[0.5]`; blank and `#` lines give None
+pub fn parse_epd_line(line: &str) -> Result<Option<TuningPosition>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 5 {
+        return Err("expected a position followed by a result".to_string());
+    }
+    let board = Board::from_fen(&fields[..4].join(" ")).map_err(|e| e.to_string())?;
+
+    // Prefer the c9 opcode, otherwise take the last field that reads as a result
+    let opcode = fields.iter().position(|f| *f == "c9").and_then(|i| fields.get(i + 1));
+    let result = match opcode {
+        Some(token) => parse_result(token, true),
+        None => fields[4..].iter().rev().find_map(|f| parse_result(f, false)),
+    };
+    let result = result.ok_or_else(|| "missing game result".to_string())?;
+    Ok(Some(TuningPosition { board, result }))
+}
+
+/// Read every labelled position of an EPD file
+pub fn load_epd(path: &Path) -> Result<Vec<TuningPosition>, TuneError> {
+    let text = fs::read_to_string(path)?;
+    let mut positions = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if let Some(position) = parse_epd_line(line).map_err(|e| TuneError::BadLine(i + 1, e))? {
+            positions.push(position);
+        }
+    }
+    Ok(positions)
+}
+
+/// Every position of the finished games, labelled with the game's result;
+/// unfinished games ("*") are skipped
+pub fn positions_from_games(games: &[PgnGame]) -> Vec<TuningPosition> {
+    let mut positions = Vec::new();
+    for game in games {
+        let result = match parse_result(&game.result, false) {
+            Some(result) => result,
+            None => continue,
+        };
+        let mut board = game.start.clone();
+        positions.push(TuningPosition { board: board.clone(), result });
+        for mv in &game.moves {
+            board.apply_move(*mv);
+            positions.push(TuningPosition { board: board.clone(), result });
+        }
+    }
+    positions
+}
+
+// =============================================================================
+// Quiet Positions
+// =============================================================================
+
+/// Capture-only search that records its principal variation
+fn quiescence(
+    board: &mut Board,
+    evaluator: &dyn Evaluator,
+    ply: usize,
+    mut alpha: i32,
+    beta: i32,
+    pv: &mut Vec<Move>,
+) -> i32 {
+    pv.clear();
+    let stand_pat = evaluator.evaluate(board);
+    if stand_pat >= beta || ply >= MAX_QUIESCENCE_PLY {
+        return stand_pat;
+    }
+    alpha = alpha.max(stand_pat);
+
+    let captures: Vec<Move> = board
+        .generate_legal_moves()
+        .into_iter()
+        .filter(|mv| mv.captured.is_some())
+        .collect();
+
+    let mut child_pv = Vec::new();
+    for mv in captures {
+        board.apply_move(mv);
+        let score = -quiescence(board, evaluator, ply + 1, -beta, -alpha, &mut child_pv);
+        board.unmake_move(mv);
+
+        if score > alpha {
+            alpha = score;
+            pv.clear();
+            pv.push(mv);
+            pv.extend_from_slice(&child_pv);
+            if score >= beta {
+                break;
+            }
+        }
+    }
+    alpha
+}
+
+/// Play out the capture sequence quiescence would choose, so the static
+/// evaluation is not asked about positions with hanging pieces
+pub fn resolve_quiet(board: &mut Board, evaluator: &dyn Evaluator) {
+    let mut pv = Vec::new();
+    quiescence(board, evaluator, 0, -i32::MAX, i32::MAX, &mut pv);
+    for mv in pv {
+        board.apply_move(mv);
+    }
+}
+
+/// Resolve every position to its quiet leaf and drop those that end in mate,
+/// whose score says nothing about the weights
+pub fn prepare_positions(positions: &mut Vec<TuningPosition>, params: &EvalParams) {
+    let evaluator = HandCrafted::new(params.clone());
+    for position in positions.iter_mut() {
+        resolve_quiet(&mut position.board, &evaluator);
+    }
+    positions.retain_mut(|position| evaluator.evaluate(&mut position.board).abs() < MATE_BOUND);
+}
+
+// =============================================================================
+// Loss
+// =============================================================================
+
+/// Expected score for White given a centipawn evaluation
+pub fn sigmoid(score: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+/// Evaluation from White's point of view, as the search would see it
+fn white_score(evaluator: &HandCrafted, board: &mut Board) -> f64 {
+    let score = evaluator.evaluate(board) as f64;
+    match board.side_to_move() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// Mean squared error between the predicted and actual results
+/// The positions are split across threads; each owns its slice of boards
+pub fn evaluation_error(positions: &mut [TuningPosition], params: &EvalParams, k: f64) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+    let count = positions.len();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = count.div_ceil(threads);
+
+    let total: f64 = thread::scope(|scope| {
+        let handles: Vec<_> = positions
+            .chunks_mut(chunk)
+            .map(|slice| {
+                scope.spawn(move || {
+                    let evaluator = HandCrafted::new(params.clone());
+                    slice
+                        .iter_mut()
+                        .map(|position| {
+                            let predicted = sigmoid(white_score(&evaluator, &mut position.board), k);
+                            (position.result - predicted).powi(2)
+                        })
+                        .sum::<f64>()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().expect("Tuning thread panicked")).sum()
+    });
+    total / count as f64
+}
+
+/// Scaling constant that best fits the current evaluation to the results
+pub fn fit_k(positions: &mut [TuningPosition], params: &EvalParams) -> f64 {
+    // Golden-section search; the error is unimodal in K
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = K_RANGE;
+    let mut a = high - ratio * (high - low);
+    let mut b = low + ratio * (high - low);
+    let mut error_a = evaluation_error(positions, params, a);
+    let mut error_b = evaluation_error(positions, params, b);
+
+    for _ in 0..K_ITERATIONS {
+        if error_a < error_b {
+            high = b;
+            b = a;
+            error_b = error_a;
+            a = high - ratio * (high - low);
+            error_a = evaluation_error(positions, params, a);
+        } else {
+            low = a;
+            a = b;
+            error_a = error_b;
+            b = low + ratio * (high - low);
+            error_b = evaluation_error(positions, params, b);
+        }
+    }
+    (low + high) / 2.0
+}
+
+// =============================================================================
+// Local Search
+// =============================================================================
+
+/// Every tunable integer with a printable name; table entries become
+/// "name[row][col]"
+fn weights_mut(params: &mut EvalParams) -> Vec<(String, &mut i32)> {
+    let mut weights = Vec::new();
+    for (name, param) in params.params_mut() {
+        match param {
+            Param::Weight(weight) => weights.push((name.to_string(), weight)),
+            Param::Table(table) => {
+                for (row, values) in table.iter_mut().enumerate() {
+                    for (col, value) in values.iter_mut().enumerate() {
+                        weights.push((format!("{}[{}][{}]", name, row, col), value));
+                    }
+                }
+            }
+        }
+    }
+    weights
+}
+
+/// Number of tunable integers in a parameter set
+fn weight_count() -> usize {
+    weights_mut(&mut EvalParams::default()).len()
+}
+
+fn set_weight(params: &mut EvalParams, index: usize, value: i32) {
+    *weights_mut(params).swap_remove(index).1 = value;
+}
+
+fn get_weight(params: &mut EvalParams, index: usize) -> i32 {
+    *weights_mut(params).swap_remove(index).1
+}
+
+/// Texel's local search: nudge each weight by one step in either direction,
+/// keep any change that lowers the error, and repeat until a full pass finds
+/// nothing or `max_passes` is reached
+/// `on_pass` receives the pass number and the error after it
+pub fn tune(
+    positions: &mut [TuningPosition],
+    mut params: EvalParams,
+    k: f64,
+    max_passes: usize,
+    on_pass: &mut dyn FnMut(usize, f64),
+) -> EvalParams {
+    let mut best_error = evaluation_error(positions, &params, k);
+
+    for pass in 1..=max_passes {
+        let mut improved = false;
+        for index in 0..weight_count() {
+            let original = get_weight(&mut params, index);
+            for step in [1, -1] {
+                set_weight(&mut params, index, original + step);
+                let error = evaluation_error(positions, &params, k);
+                if error < best_error {
+                    best_error = error;
+                    improved = true;
+                    break;
+                }
+                set_weight(&mut params, index, original);
+            }
+        }
+
+        on_pass(pass, best_error);
+        if !improved {
+            break;
+        }
+    }
+    params
+}
+
+/// Names of the weights that differ between two parameter sets
+pub fn changed_weights(before: &EvalParams, after: &EvalParams) -> Vec<String> {
+    let mut before = before.clone();
+    let mut after = after.clone();
+    weights_mut(&mut before)
+        .into_iter()
+        .zip(weights_mut(&mut after))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((name, _), _)| name)
+        .collect()
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_c9_opcode() {
+        let position = parse_epd_line(r#"8/8/8/3k4/8/8/8/A3K3 w - - c9 "1-0";"#).unwrap().unwrap();
+        assert_eq!(position.result, 1.0);
+        let position = parse_epd_line(r#"8/8/8/3k4/8/8/8/A3K3 b - - c9 "1/2-1/2";"#).unwrap().unwrap();
+        assert_eq!(position.result, 0.5);
+        assert_eq!(position.board.side_to_move(), Color::Black);
+        let position = parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - c9 0.5;").unwrap().unwrap();
+        assert_eq!(position.result, 0.5);
+    }
+
+    #[test]
+    fn test_parse_trailing_result() {
+        let position = parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - [0.0]").unwrap().unwrap();
+        assert_eq!(position.result, 0.0);
+        let position = parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - 0 12 0-1").unwrap().unwrap();
+        assert_eq!(position.result, 0.0);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_lines() {
+        assert!(parse_epd_line("").unwrap().is_none());
+        assert!(parse_epd_line("# comment").unwrap().is_none());
+        assert!(parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - -").is_err());
+        assert!(parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - c9 \"win\";").is_err());
+        assert!(parse_epd_line("8/8/8/8/8/8/8/8 w - - 1-0").is_err());
+    }
+
+    #[test]
+    fn test_parse_refuses_clock_fields_as_results() {
+        assert!(parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - 0 1").is_err());
+        assert!(parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - 0 12").is_err());
+        let position = parse_epd_line("8/8/8/3k4/8/8/8/A3K3 w - - 0 1 \"0.5\"").unwrap().unwrap();
+        assert_eq!(position.result, 0.5);
+    }
+
+    #[test]
+    fn test_positions_from_games() {
+        let text = "[FEN \"k7/8/1K6/8/8/8/8/3A4 w - - 0 1\"]\n\n1. Ad8# 1-0\n\n\
+                    [FEN \"k7/8/1K6/8/8/8/8/3A4 w - - 0 1\"]\n\n1. Kc6 *\n";
+        let games = crate::pgn::parse_pgn(text).unwrap();
+        let mut positions = positions_from_games(&games);
+        assert_eq!(positions.len(), 2);
+        assert!(positions.iter().all(|position| position.result == 1.0));
+        assert!(positions[1].board.is_checkmate(Color::Black));
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        assert_eq!(sigmoid(0.0, 1.0), 0.5);
+        assert!(sigmoid(400.0, 1.0) > 0.9);
+        assert!(sigmoid(-400.0, 1.0) < 0.1);
+    }
+
+    #[test]
+    fn test_resolve_quiet_plays_capture() {
+        // The rook hangs to the amazon
+        let mut board = Board::from_fen("4k3/8/8/8/3r4/8/8/3AK3 w - -").unwrap();
+        resolve_quiet(&mut board, &HandCrafted::default());
+        assert_eq!(board.to_fen().split(' ').next(), Some("4k3/8/8/8/3A4/8/8/4K3"));
+    }
+
+    #[test]
+    fn test_weights_cover_tables() {
+        assert_eq!(weight_count(), 18 + 3 * 64);
+        let mut params = EvalParams::default();
+        set_weight(&mut params, 18 + 9, 77);
+        assert_eq!(params.enemy_king_pst[1][1], 77);
+        assert_eq!(changed_weights(&EvalParams::default(), &params), vec!["enemy_king_pst[1][1]"]);
+    }
+
+    #[test]
+    fn test_tune_lowers_error() {
+        let lines = [
+            r#"8/8/8/3k4/8/8/8/A3K3 w - - c9 "1-0";"#,
+            r#"k7/8/1K6/8/8/8/8/3A4 b - - c9 "1-0";"#,
+            r#"8/8/8/3k4/8/8/8/R3K3 w - - c9 "1/2-1/2";"#,
+        ];
+        let mut positions: Vec<TuningPosition> =
+            lines.iter().map(|line| parse_epd_line(line).unwrap().unwrap()).collect();
+        let params = EvalParams::default();
+        prepare_positions(&mut positions, &params);
+        assert_eq!(positions.len(), 3);
+
+        let k = 1.0;
+        let before = evaluation_error(&mut positions, &params, k);
+        let tuned = tune(&mut positions, params.clone(), k, 1, &mut |_, _| {});
+        let after = evaluation_error(&mut positions, &tuned, k);
+        assert!(after < before, "{} !< {}", after, before);
+        assert!(!changed_weights(&params, &tuned).is_empty());
+    }
+
+    #[test]
+    fn test_fit_k_beats_bounds() {
+        let mut positions = vec![
+            parse_epd_line(r#"8/8/8/3k4/8/8/8/A3K3 w - - c9 "1-0";"#).unwrap().unwrap(),
+            parse_epd_line(r#"8/8/8/3k4/8/8/8/R3K3 w - - c9 "1/2-1/2";"#).unwrap().unwrap(),
+        ];
+        let params = EvalParams::default();
+        let k = fit_k(&mut positions, &params);
+        assert!(k > K_RANGE.0 && k < K_RANGE.1);
+        let fitted = evaluation_error(&mut positions, &params, k);
+        assert!(fitted <= evaluation_error(&mut positions, &params, K_RANGE.1));
+    }
+}
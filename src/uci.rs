@@ -1,19 +1,32 @@
 // UCI (Universal Chess Interface) Protocol Implementation
 // Allows communication with chess GUIs and other engines
 
-use crate::board::{Board, Square};
-use crate::search::find_best_move;
+use crate::board::{Board, FenError, Move, PieceType, Square};
+use crate::eval_params::EvalParams;
+use crate::evaluation::{Evaluator, HandCrafted};
+use crate::nnue::{is_network, Network, NnueEvaluator};
+use crate::perft::divide;
+use crate::search::{
+    score_to_cp, search, SearchInfo, SearchLimits, SearchSignals, CHECKMATE_SCORE, MATE_BOUND, MAX_SEARCH_DEPTH,
+};
+use crate::tablebase::{FairyTablebase, Tablebase, TablebaseError, TbProbe, DEFAULT_TB_PROBE_DEPTH};
+use crate::transposition::{TranspositionTable, DEFAULT_HASH_MB, MAX_HASH_MB, MIN_HASH_MB};
+use crate::variant::{default_variant, find_variant, Variant, VARIANTS};
 use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 /// Convert a square to UCI notation (e.g., (7, 4) -> "e1")
-fn square_to_uci(square: Square) -> String {
+pub fn square_to_uci(square: Square) -> String {
     let col = (b'a' + square.1) as char;
     let row = (b'8' - square.0) as char;
     format!("{}{}", col, row)
 }
 
 /// Parse UCI notation to square (e.g., "e1" -> (7, 4))
-fn parse_square(s: &str) -> Option<Square> {
+pub fn parse_square(s: &str) -> Option<Square> {
     if s.len() < 2 {
         return None;
     }
@@ -26,26 +39,178 @@ fn parse_square(s: &str) -> Option<Square> {
     Some((row, col))
 }
 
-/// Parse a UCI move string (e.g., "e2e4") to (from, to) squares
-fn parse_uci_move(s: &str) -> Option<(Square, Square)> {
-    if s.len() < 4 {
+/// Parse a UCI move string (e.g., "e2e4", "e7e8q") to a move
+/// A fifth character names the promotion piece
+pub fn parse_uci_move(s: &str) -> Option<Move> {
+    if s.len() < 4 || s.len() > 5 || !s.is_ascii() {
         return None;
     }
     let from = parse_square(&s[0..2])?;
     let to = parse_square(&s[2..4])?;
-    Some((from, to))
+    match s[4..].chars().next() {
+        Some(c) => {
+            let piece_type = PieceType::from_letter(c.to_ascii_uppercase())?;
+            Some(Move::with_promotion(from, to, piece_type))
+        }
+        None => Some(Move::new(from, to)),
+    }
+}
+
+/// Convert a move to UCI notation, with a lowercase promotion suffix
+pub fn move_to_uci(mv: &Move) -> String {
+    let mut s = format!("{}{}", square_to_uci(mv.from), square_to_uci(mv.to));
+    if let Some(piece_type) = mv.promotion {
+        s.push(piece_type.info().letter.to_ascii_lowercase());
+    }
+    s
+}
+
+/// Convert a search score to UCI notation: "cp <centipawns>" or "mate <moves>"
+/// Mate scores count moves (not plies); negative means we are getting mated
+/// Tablebase wins and losses are reported in centipawns, see score_to_cp
+pub(crate) fn score_to_uci(score: i32) -> String {
+    if score >= MATE_BOUND {
+        let plies = CHECKMATE_SCORE - score;
+        format!("mate {}", (plies + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        let plies = CHECKMATE_SCORE + score;
+        format!("mate -{}", (plies + 1) / 2)
+    } else {
+        format!("cp {}", score_to_cp(score))
+    }
+}
+
+/// Format a principal variation as space-separated UCI moves
+fn pv_to_uci(pv: &[Move]) -> String {
+    pv.iter()
+        .map(move_to_uci)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Build the "info" line for a completed iteration
+fn format_info(info: &SearchInfo) -> String {
+    format!(
+        "info depth {} seldepth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+        info.depth,
+        info.seldepth,
+        score_to_uci(info.score),
+        info.nodes,
+        info.nps(),
+        info.hashfull,
+        info.time.as_millis(),
+        pv_to_uci(&info.pv)
+    )
+}
+
+/// A search running on a worker thread
+/// The worker prints "info" lines while searching and "bestmove" when done
+struct SearchThread {
+    signals: Arc<SearchSignals>,
+    handle: JoinHandle<()>,
+}
+
+impl SearchThread {
+    /// Start searching a copy of the board
+    fn start(
+        board: &Board,
+        limits: SearchLimits,
+        tt: Arc<Mutex<TranspositionTable>>,
+        evaluator: Arc<dyn Evaluator>,
+        tablebase: Option<Arc<dyn Tablebase>>,
+        tb_probe_depth: i32,
+    ) -> Self {
+        let signals = Arc::new(SearchSignals::new(limits.ponder));
+        let worker_signals = Arc::clone(&signals);
+        let mut board = board.clone();
+
+        let handle = thread::spawn(move || {
+            let result = {
+                let mut tt = tt.lock().unwrap();
+                let probe = tablebase.as_deref().map(|tablebase| TbProbe {
+                    tablebase,
+                    probe_depth: tb_probe_depth,
+                });
+                search(&mut board, &limits, &mut tt, evaluator.as_ref(), probe, &worker_signals, &mut |info| {
+                    println!("{}", format_info(info));
+                    io::stdout().flush().unwrap();
+                })
+            };
+
+            // In infinite and ponder mode "bestmove" may only be sent after "stop" / "ponderhit"
+            while (limits.infinite || worker_signals.is_pondering()) && !worker_signals.is_stopped() {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            println!("{}", format_bestmove(result.as_ref()));
+            io::stdout().flush().unwrap();
+        });
+
+        SearchThread { signals, handle }
+    }
+
+    /// Stop the search and wait until its "bestmove" has been sent
+    fn stop(self) {
+        self.signals.stop();
+        self.handle.join().unwrap();
+    }
 }
 
-/// Convert a move to UCI notation
-fn move_to_uci(from: Square, to: Square) -> String {
-    format!("{}{}", square_to_uci(from), square_to_uci(to))
+/// Stop the running search (if any) before handling a command that changes engine state
+fn stop_search(search_thread: &mut Option<SearchThread>) {
+    if let Some(running) = search_thread.take() {
+        running.stop();
+    }
+}
+
+/// Format perft divide output: one "move: nodes" line per root move, then the total
+pub fn format_divide(split: &[(Move, u64)]) -> String {
+    let mut lines: Vec<String> = split
+        .iter()
+        .map(|(mv, nodes)| format!("{}: {}", move_to_uci(mv), nodes))
+        .collect();
+    lines.sort();
+
+    let total: u64 = split.iter().map(|(_, nodes)| nodes).sum();
+    lines.push(String::new());
+    lines.push(format!("Nodes searched: {}", total));
+    lines.join("\n")
+}
+
+/// Build the "bestmove" line, with the expected reply as ponder move when known
+fn format_bestmove(result: Option<&SearchInfo>) -> String {
+    match result {
+        Some(info) => {
+            let best_move = info.best_move();
+            let mut line = format!("bestmove {}", move_to_uci(&best_move));
+            if let Some(reply) = info.pv.get(1) {
+                line.push_str(&format!(" ponder {}", move_to_uci(reply)));
+            }
+            line
+        }
+        None => "bestmove 0000".to_string(), // No legal move
+    }
 }
 
 /// Main UCI loop - reads commands from stdin and responds
-pub fn uci_loop() {
+/// Searches run on a worker thread, so "stop", "ponderhit", "isready"
+/// and "quit" are answered while the engine is thinking
+/// `params` are the evaluation weights used until "EvalFile" is set
+/// "EvalFile" takes either a weights file or a network; "Use NNUE" picks the network
+pub fn uci_loop(params: EvalParams) {
     let stdin = io::stdin();
-    let mut board = Board::setup_amazon_vs_rook();
+    let mut variant = default_variant();
+    let mut board = variant.start_position();
     let mut default_depth = 4;
+    let mut chess960 = false;
+    let mut evaluator: Arc<dyn Evaluator> = Arc::new(HandCrafted::new(params.clone()));
+    let mut hand_crafted = params.clone();
+    let mut network: Option<Arc<Network>> = None;
+    let mut use_nnue = false;
+    let mut tablebase: Option<Arc<dyn Tablebase>> = None;
+    let mut tb_probe_depth = DEFAULT_TB_PROBE_DEPTH;
+    let tt = Arc::new(Mutex::new(TranspositionTable::new(DEFAULT_HASH_MB)));
+    let mut search_thread: Option<SearchThread> = None;
 
     for line in stdin.lock().lines() {
         let line = match line {
@@ -67,8 +232,23 @@ pub fn uci_loop() {
             "uci" => {
                 println!("id name Amazon-vs-Rook Chess Engine");
                 println!("id author Gingnose");
-                println!("option name UCI_Variant type combo default amazon var amazon");
+                println!("{}", format_variant_option());
                 println!("option name Depth type spin default 4 min 1 max 10");
+                println!(
+                    "option name Hash type spin default {} min {} max {}",
+                    DEFAULT_HASH_MB, MIN_HASH_MB, MAX_HASH_MB
+                );
+                println!("option name Clear Hash type button");
+                println!("option name Ponder type check default false");
+                println!("option name UCI_Chess960 type check default false");
+                println!("option name EvalFile type string default <empty>");
+                println!("option name Use NNUE type check default false");
+                println!("option name SyzygyPath type string default <empty>");
+                println!("option name TablebasePath type string default <empty>");
+                println!(
+                    "option name TbProbeDepth type spin default {} min 1 max {}",
+                    DEFAULT_TB_PROBE_DEPTH, MAX_SEARCH_DEPTH
+                );
                 println!("uciok");
                 io::stdout().flush().unwrap();
             }
@@ -79,32 +259,124 @@ pub fn uci_loop() {
             }
 
             "ucinewgame" => {
-                board = Board::setup_amazon_vs_rook();
-                board.clear_history();
+                stop_search(&mut search_thread);
+                board = variant.start_position();
+                board.set_chess960(chess960);
+                tt.lock().unwrap().clear();
             }
 
             "position" => {
-                parse_position(&mut board, &parts[1..]);
+                stop_search(&mut search_thread);
+                if let Err(e) = parse_position(&mut board, &parts[1..], variant, chess960) {
+                    println!("info string invalid FEN: {}", e);
+                    io::stdout().flush().unwrap();
+                }
+            }
+
+            "go" if parts.get(1) == Some(&"perft") => {
+                // Non-standard extension (as in Stockfish): "go perft N"
+                stop_search(&mut search_thread);
+                if let Some(depth) = parts.get(2).and_then(|d| d.parse::<u32>().ok()) {
+                    println!("{}", format_divide(&divide(&mut board, depth)));
+                    io::stdout().flush().unwrap();
+                }
             }
 
             "go" => {
-                let depth = parse_go_command(&parts[1..], default_depth);
-                if let Some((best_move, _score)) = find_best_move(&mut board, depth) {
-                    let uci_move = move_to_uci(best_move.from, best_move.to);
-                    println!("bestmove {}", uci_move);
-                } else {
-                    println!("bestmove 0000"); // No legal move
+                stop_search(&mut search_thread);
+                let limits = parse_go_command(&parts[1..], default_depth);
+                search_thread = Some(SearchThread::start(
+                    &board,
+                    limits,
+                    Arc::clone(&tt),
+                    Arc::clone(&evaluator),
+                    tablebase.clone(),
+                    tb_probe_depth,
+                ));
+            }
+
+            "stop" => {
+                stop_search(&mut search_thread);
+            }
+
+            "ponderhit" => {
+                // The opponent played the expected move: keep searching, now on our clock
+                if let Some(running) = &search_thread {
+                    running.signals.ponderhit();
                 }
-                io::stdout().flush().unwrap();
             }
 
             "setoption" => {
+                stop_search(&mut search_thread);
                 // Parse: setoption name Depth value 6
-                if parts.len() >= 5 && parts[1] == "name" && parts[3] == "value" {
-                    if parts[2].to_lowercase() == "depth" {
-                        if let Ok(d) = parts[4].parse::<i32>() {
-                            default_depth = d.clamp(1, 10);
+                if let Some((name, value)) = parse_setoption(&parts[1..]) {
+                    match name.as_str() {
+                        "depth" => {
+                            if let Some(d) = value.and_then(|v| v.parse::<i32>().ok()) {
+                                default_depth = d.clamp(1, 10);
+                            }
                         }
+                        "hash" => {
+                            if let Some(mb) = value.and_then(|v| v.parse::<usize>().ok()) {
+                                tt.lock().unwrap().resize(mb);
+                            }
+                        }
+                        "clear hash" => {
+                            tt.lock().unwrap().clear();
+                        }
+                        "uci_variant" => match value.as_deref().and_then(find_variant) {
+                            Some(selected) => {
+                                variant = selected;
+                                board = variant.start_position();
+                                board.set_chess960(chess960);
+                            }
+                            None => {
+                                println!("info string unknown variant {}", value.unwrap_or_default());
+                                io::stdout().flush().unwrap();
+                            }
+                        },
+                        "uci_chess960" => {
+                            chess960 = value.is_some_and(|v| v.eq_ignore_ascii_case("true"));
+                            board.set_chess960(chess960);
+                        }
+                        "evalfile" | "use nnue" => {
+                            if name == "use nnue" {
+                                use_nnue = value.is_some_and(|v| v.eq_ignore_ascii_case("true"));
+                            } else {
+                                match load_eval_file(value.as_deref(), &params) {
+                                    Ok(EvalFile::Params(loaded)) => {
+                                        hand_crafted = *loaded;
+                                        network = None;
+                                    }
+                                    Ok(EvalFile::Network(loaded)) => network = Some(loaded),
+                                    Err(e) => println!("info string cannot load evaluation file: {}", e),
+                                }
+                            }
+                            if use_nnue && network.is_none() {
+                                println!("info string no network loaded, using the hand-crafted evaluation");
+                            }
+                            io::stdout().flush().unwrap();
+                            evaluator = select_evaluator(&hand_crafted, network.as_ref(), use_nnue);
+                        }
+                        "syzygypath" | "tablebasepath" => {
+                            tablebase = match load_tablebase(value.as_deref()) {
+                                Ok(loaded) => loaded,
+                                Err(e) => {
+                                    println!("info string cannot load tablebases: {}", e);
+                                    None
+                                }
+                            };
+                            if let Some(loaded) = &tablebase {
+                                println!("info string tablebases up to {} pieces", loaded.max_pieces());
+                            }
+                            io::stdout().flush().unwrap();
+                        }
+                        "tbprobedepth" => {
+                            if let Some(d) = value.and_then(|v| v.parse::<i32>().ok()) {
+                                tb_probe_depth = d.clamp(1, MAX_SEARCH_DEPTH);
+                            }
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -123,12 +395,61 @@ pub fn uci_loop() {
             }
         }
     }
+
+    // "quit" or end of input: finish the running search cleanly
+    stop_search(&mut search_thread);
+}
+
+/// What an EvalFile contained
+enum EvalFile {
+    Params(Box<EvalParams>),
+    Network(Arc<Network>),
+}
+
+/// Load the weights or network named by EvalFile, told apart by the network header
+/// An empty value (or "<empty>") goes back to the weights the engine started with
+fn load_eval_file(path: Option<&str>, startup: &EvalParams) -> Result<EvalFile, Box<dyn std::error::Error>> {
+    let path = match path.map(str::trim) {
+        None | Some("") | Some("<empty>") => return Ok(EvalFile::Params(Box::new(startup.clone()))),
+        Some(path) => Path::new(path),
+    };
+    let mut header = [0; 4];
+    let is_network_file = std::fs::File::open(path)
+        .and_then(|mut file| io::Read::read_exact(&mut file, &mut header))
+        .is_ok_and(|_| is_network(&header));
+    if is_network_file {
+        Ok(EvalFile::Network(Arc::new(Network::read(path)?)))
+    } else {
+        Ok(EvalFile::Params(Box::new(EvalParams::load(path)?)))
+    }
+}
+
+/// The network when "Use NNUE" is on and one is loaded, the hand-crafted evaluation otherwise
+fn select_evaluator(params: &EvalParams, network: Option<&Arc<Network>>, use_nnue: bool) -> Arc<dyn Evaluator> {
+    match network.filter(|_| use_nnue) {
+        Some(network) => Arc::new(NnueEvaluator::new(Arc::clone(network))),
+        None => Arc::new(HandCrafted::new(params.clone())),
+    }
+}
+
+/// Load the tables named by SyzygyPath / TablebasePath
+/// An empty value (or "<empty>") unloads them
+/// Both options take this engine's tables only; Syzygy files are an error
+fn load_tablebase(path: Option<&str>) -> Result<Option<Arc<dyn Tablebase>>, TablebaseError> {
+    match path.map(str::trim) {
+        None | Some("") | Some("<empty>") => Ok(None),
+        Some(paths) => Ok(Some(Arc::new(FairyTablebase::load_paths(paths)?))),
+    }
 }
 
 /// Parse the "position" command
-fn parse_position(board: &mut Board, args: &[&str]) {
+/// An invalid FEN leaves the current position untouched and is returned as an error
+/// "startpos" and FENs follow the selected variant (start position, pieces, rules)
+/// With `chess960` castling moves are read (and later printed) as king takes rook;
+/// Chess960 FENs switch to that encoding by themselves
+fn parse_position(board: &mut Board, args: &[&str], variant: &Variant, chess960: bool) -> Result<(), FenError> {
     if args.is_empty() {
-        return;
+        return Ok(());
     }
 
     // Find where "moves" keyword is (if present)
@@ -137,82 +458,130 @@ fn parse_position(board: &mut Board, args: &[&str]) {
     // Parse position type
     match args[0] {
         "startpos" => {
-            *board = Board::setup_amazon_vs_rook();
-            board.clear_history();
+            *board = variant.start_position();
         }
         "fen" => {
             // Collect FEN parts (everything between "fen" and "moves" or end)
             let fen_end = moves_idx.unwrap_or(args.len());
-            if fen_end > 1 {
-                let fen_parts = &args[1..fen_end];
-                let fen_string = fen_parts.join(" ");
-                if let Some(parsed_board) = Board::from_fen(&fen_string) {
-                    *board = parsed_board;
-                    board.clear_history();
-                } else {
-                    // FEN parsing failed, use default position
-                    *board = Board::setup_amazon_vs_rook();
-                }
-            } else {
-                *board = Board::setup_amazon_vs_rook();
-            }
+            let fen_string = args[1..fen_end].join(" ");
+            *board = variant.from_fen(&fen_string)?;
         }
         _ => {
-            return;
+            return Ok(());
         }
     }
 
+    if chess960 {
+        board.set_chess960(true);
+    }
+
     // Apply moves if present
     if let Some(idx) = moves_idx {
         for move_str in &args[idx + 1..] {
-            if let Some((from, to)) = parse_uci_move(move_str) {
+            if let Some(parsed) = parse_uci_move(move_str) {
                 // Verify it's a legal move
                 let legal_moves = board.generate_legal_moves();
-                let is_legal = legal_moves.iter().any(|mv| mv.from == from && mv.to == to);
-                if is_legal {
-                    board.make_move(from, to);
+                if let Some(mv) = legal_moves.into_iter().find(|mv| mv.same_as(&parsed)) {
+                    board.apply_move(mv);
                 }
             }
         }
     }
+    Ok(())
 }
 
-/// Parse the "go" command and return the search depth
-fn parse_go_command(args: &[&str], default_depth: i32) -> i32 {
-    let mut depth = default_depth;
+/// The UCI_Variant combo option listing every registered variant
+fn format_variant_option() -> String {
+    let mut line = format!("option name UCI_Variant type combo default {}", default_variant().name);
+    for variant in &VARIANTS {
+        line.push_str(&format!(" var {}", variant.name));
+    }
+    line
+}
+
+/// Parse the "setoption" command into a lowercase option name and its value
+/// Option names may contain spaces ("Clear Hash"); buttons have no value
+fn parse_setoption(args: &[&str]) -> Option<(String, Option<String>)> {
+    if args.first() != Some(&"name") {
+        return None;
+    }
+    let value_idx = args.iter().position(|&x| x == "value");
+    let name_end = value_idx.unwrap_or(args.len());
+    if name_end <= 1 {
+        return None;
+    }
+    let name = args[1..name_end].join(" ").to_lowercase();
+    let value = value_idx.map(|idx| args[idx + 1..].join(" "));
+    Some((name, value))
+}
+
+/// Parse the "go" command into search limits
+/// Without any depth, clock, "infinite" or "ponder" the configured default depth is used
+fn parse_go_command(args: &[&str], default_depth: i32) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+
+    // Value of the parameter following the keyword at index i
+    fn value<T: std::str::FromStr>(args: &[&str], i: usize) -> Option<T> {
+        args.get(i + 1).and_then(|v| v.parse::<T>().ok())
+    }
 
     let mut i = 0;
     while i < args.len() {
         match args[i] {
             "depth" => {
-                if i + 1 < args.len() {
-                    if let Ok(d) = args[i + 1].parse::<i32>() {
-                        depth = d.clamp(1, 20);
-                    }
-                    i += 1;
-                }
+                limits.depth = value::<i32>(args, i).map(|d| d.clamp(1, 20));
+                i += 1;
             }
             "movetime" => {
-                // For simplicity, ignore movetime and use depth
-                if i + 1 < args.len() {
-                    i += 1;
-                }
+                limits.movetime = value(args, i);
+                i += 1;
+            }
+            "wtime" => {
+                // Clocks can go negative in some GUIs when flagging
+                limits.wtime = value::<i64>(args, i).map(|t| t.max(0) as u64);
+                i += 1;
+            }
+            "btime" => {
+                limits.btime = value::<i64>(args, i).map(|t| t.max(0) as u64);
+                i += 1;
+            }
+            "winc" => {
+                limits.winc = value(args, i);
+                i += 1;
+            }
+            "binc" => {
+                limits.binc = value(args, i);
+                i += 1;
+            }
+            "movestogo" => {
+                limits.movestogo = value(args, i);
+                i += 1;
             }
             "infinite" => {
-                // Use max depth for infinite
-                depth = 10;
+                limits.infinite = true;
+            }
+            "ponder" => {
+                limits.ponder = true;
             }
             _ => {}
         }
         i += 1;
     }
 
-    depth
+    // Nothing to stop the search by itself: fall back to the configured depth
+    let has_clock = limits.movetime.is_some() || limits.wtime.is_some() || limits.btime.is_some();
+    if limits.depth.is_none() && !limits.infinite && !limits.ponder && !has_clock {
+        limits.depth = Some(default_depth);
+    }
+
+    limits
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::MoveKind;
+    use crate::search::{TB_BOUND, TB_WIN_SCORE};
 
     #[test]
     fn test_square_to_uci() {
@@ -232,13 +601,306 @@ mod tests {
 
     #[test]
     fn test_parse_uci_move() {
-        assert_eq!(parse_uci_move("e2e4"), Some(((6, 4), (4, 4))));
-        assert_eq!(parse_uci_move("d1d6"), Some(((7, 3), (2, 3))));
+        let parses_to = |s: &str, expected: Move| {
+            parse_uci_move(s).is_some_and(|mv| mv.same_as(&expected))
+        };
+        assert!(parses_to("e2e4", Move::new((6, 4), (4, 4))));
+        assert!(parses_to("d1d6", Move::new((7, 3), (2, 3))));
+        assert!(parses_to(
+            "e7e8q",
+            Move::with_promotion((1, 4), (0, 4), PieceType::Queen)
+        ));
+        assert!(parses_to(
+            "b2b1a",
+            Move::with_promotion((6, 1), (7, 1), PieceType::Amazon)
+        ));
+        assert!(!parses_to("e7e8q", Move::new((1, 4), (0, 4))));
+        assert!(parse_uci_move("e7e8z").is_none());
+        assert!(parse_uci_move("e7e8qq").is_none());
+    }
+
+    #[test]
+    fn test_score_to_uci() {
+        assert_eq!(score_to_uci(35), "cp 35");
+        assert_eq!(score_to_uci(-120), "cp -120");
+        // Mate delivered at ply 1 = mate in 1, at ply 3 = mate in 2
+        assert_eq!(score_to_uci(CHECKMATE_SCORE - 1), "mate 1");
+        assert_eq!(score_to_uci(CHECKMATE_SCORE - 3), "mate 2");
+        // Getting mated at ply 2 = mated in 1, at ply 4 = mated in 2
+        assert_eq!(score_to_uci(-CHECKMATE_SCORE + 2), "mate -1");
+        assert_eq!(score_to_uci(-CHECKMATE_SCORE + 4), "mate -2");
+        // Tablebase wins are not mates: clamped centipawns, closer wins higher
+        assert_eq!(score_to_uci(TB_WIN_SCORE - 25), "cp 19975");
+        assert_eq!(score_to_uci(-TB_WIN_SCORE + 30), "cp -19970");
+        assert_eq!(score_to_uci(TB_BOUND), "cp 19000");
+    }
+
+    #[test]
+    fn test_format_info() {
+        let info = SearchInfo {
+            depth: 5,
+            seldepth: 7,
+            score: CHECKMATE_SCORE - 3,
+            nodes: 2000,
+            time: Duration::from_millis(500),
+            hashfull: 12,
+            pv: vec![Move::new((7, 3), (3, 7)), Move::new((0, 4), (0, 3))],
+        };
+        assert_eq!(
+            format_info(&info),
+            "info depth 5 seldepth 7 score mate 2 nodes 2000 nps 4000 hashfull 12 time 500 pv d1h5 e8d8"
+        );
+    }
+
+    #[test]
+    fn test_parse_position_fen_with_moves() {
+        let mut board = Board::new();
+        let args = ["fen", "r3k3/8/8/8/8/8/8/3AK3", "w", "-", "-", "0", "1", "moves", "e1f1"];
+        assert!(parse_position(&mut board, &args, default_variant(), false).is_ok());
+        assert_eq!(board.to_fen(), "r3k3/8/8/8/8/8/8/3A1K2 b - - 1 1");
+    }
+
+    #[test]
+    fn test_parse_position_castling_encodings() {
+        let fen = ["fen", "r3k2r/8/8/8/8/8/8/R3K2R", "w", "KQkq", "-", "0", "1", "moves"];
+
+        // Standard chess: the king's two-square move
+        let mut board = Board::new();
+        let args = [&fen[..], &["e1g1"]].concat();
+        assert!(parse_position(&mut board, &args, default_variant(), false).is_ok());
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+
+        // UCI_Chess960: king takes rook
+        let args = [&fen[..], &["e1a1"]].concat();
+        assert!(parse_position(&mut board, &args, default_variant(), true).is_ok());
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1");
+        let castles: Vec<String> = board
+            .generate_legal_moves()
+            .iter()
+            .filter(|mv| mv.kind == MoveKind::Castling)
+            .map(move_to_uci)
+            .collect();
+        // The rook on d1 now covers d8, so Black may only castle short
+        assert_eq!(castles, vec!["e8h8"]);
+    }
+
+    #[test]
+    fn test_parse_position_follows_variant() {
+        let chess = find_variant("chess").unwrap();
+        let mut board = Board::new();
+        assert!(parse_position(&mut board, &["startpos", "moves", "e2e4"], chess, false).is_ok());
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+
+        // Amazons are not part of orthodox chess
+        let args = ["fen", "4k3/8/8/8/8/8/8/3AK3", "w", "-", "-", "0", "1"];
+        assert_eq!(
+            parse_position(&mut board, &args, chess, false),
+            Err(FenError::UnknownPiece('A'))
+        );
+
+        // "amazon" is still the K+A vs K+R setup of the original engine
+        let amazon = find_variant("amazon").unwrap();
+        assert!(parse_position(&mut board, &["startpos"], amazon, false).is_ok());
+        assert_eq!(board.to_fen(), "r3k3/8/8/8/8/8/8/3AK3 w - - 0 1");
+
+        let two_amazons = find_variant("two-amazons-vs-rooks").unwrap();
+        assert!(parse_position(&mut board, &["startpos"], two_amazons, false).is_ok());
+        assert_eq!(board.to_fen(), "8/3r1r2/8/3rkr2/8/8/8/3AKA2 w - - 0 1");
+    }
+
+    #[test]
+    fn test_variant_option_lists_all_variants() {
+        let option = format_variant_option();
+        assert!(option.starts_with("option name UCI_Variant type combo default amazon var amazon "));
+        for variant in &VARIANTS {
+            assert!(option.contains(&format!(" var {}", variant.name)), "{}", variant.name);
+        }
+    }
+
+    #[test]
+    fn test_parse_position_bad_fen_keeps_position() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let before = board.to_fen();
+        let args = ["fen", "4k3/8/8/8/8/8/8/R7", "w", "moves", "a1a2"];
+        assert_eq!(
+            parse_position(&mut board, &args, default_variant(), false),
+            Err(FenError::MissingKing(crate::board::Color::White))
+        );
+        assert_eq!(board.to_fen(), before);
+    }
+
+    #[test]
+    fn test_parse_setoption() {
+        assert_eq!(
+            parse_setoption(&["name", "Depth", "value", "6"]),
+            Some(("depth".to_string(), Some("6".to_string())))
+        );
+        assert_eq!(
+            parse_setoption(&["name", "Clear", "Hash"]),
+            Some(("clear hash".to_string(), None))
+        );
+        assert_eq!(parse_setoption(&["value", "6"]), None);
+    }
+
+    #[test]
+    fn test_parse_go_depth() {
+        let limits = parse_go_command(&["depth", "6"], 4);
+        assert_eq!(limits.depth, Some(6));
+        assert_eq!(limits.movetime, None);
+    }
+
+    #[test]
+    fn test_parse_go_default_depth() {
+        let limits = parse_go_command(&[], 4);
+        assert_eq!(limits.depth, Some(4));
+    }
+
+    #[test]
+    fn test_parse_go_clock() {
+        let args = ["wtime", "10000", "btime", "9500", "winc", "100", "binc", "100", "movestogo", "20"];
+        let limits = parse_go_command(&args, 4);
+        assert_eq!(limits.wtime, Some(10000));
+        assert_eq!(limits.btime, Some(9500));
+        assert_eq!(limits.winc, Some(100));
+        assert_eq!(limits.binc, Some(100));
+        assert_eq!(limits.movestogo, Some(20));
+        // Clock given: depth is not capped by the default
+        assert_eq!(limits.depth, None);
+    }
+
+    #[test]
+    fn test_parse_go_movetime() {
+        let limits = parse_go_command(&["movetime", "500"], 4);
+        assert_eq!(limits.movetime, Some(500));
+        assert_eq!(limits.depth, None);
+    }
+
+    #[test]
+    fn test_parse_go_infinite_has_no_depth_cap() {
+        let limits = parse_go_command(&["infinite"], 4);
+        assert!(limits.infinite);
+        assert_eq!(limits.depth, None);
+    }
+
+    #[test]
+    fn test_parse_go_ponder() {
+        let limits = parse_go_command(&["ponder", "wtime", "1000", "btime", "1000"], 4);
+        assert!(limits.ponder);
+        assert_eq!(limits.wtime, Some(1000));
+    }
+
+    #[test]
+    fn test_format_divide() {
+        let split = vec![(Move::new((7, 4), (7, 5)), 12), (Move::new((7, 3), (2, 3)), 30)];
+        assert_eq!(format_divide(&split), "d1d6: 30\ne1f1: 12\n\nNodes searched: 42");
+    }
+
+    #[test]
+    fn test_format_bestmove_with_ponder() {
+        let info = SearchInfo {
+            depth: 2,
+            seldepth: 2,
+            score: 0,
+            nodes: 10,
+            time: Duration::from_millis(1),
+            hashfull: 0,
+            pv: vec![Move::new((7, 3), (3, 7)), Move::new((0, 4), (0, 3))],
+        };
+        assert_eq!(format_bestmove(Some(&info)), "bestmove d1h5 ponder e8d8");
+        assert_eq!(format_bestmove(None), "bestmove 0000");
+    }
+
+    #[test]
+    fn test_search_thread_stops_infinite_search() {
+        let board = Board::setup_amazon_vs_rook();
+        let tt = Arc::new(Mutex::new(TranspositionTable::new(1)));
+        let limits = parse_go_command(&["infinite"], 4);
+
+        let evaluator = Arc::new(HandCrafted::default());
+        let running = SearchThread::start(&board, limits, Arc::clone(&tt), evaluator, None, DEFAULT_TB_PROBE_DEPTH);
+        thread::sleep(Duration::from_millis(50));
+        // Must return promptly even though the search would otherwise never end
+        running.stop();
+        assert!(tt.lock().unwrap().probe(board.position_hash()).is_some());
+    }
+
+    /// Weights from an EvalFile that is expected to hold them
+    fn loaded_params(path: Option<&str>, startup: &EvalParams) -> EvalParams {
+        match load_eval_file(path, startup) {
+            Ok(EvalFile::Params(params)) => *params,
+            _ => panic!("expected evaluation parameters"),
+        }
+    }
+
+    #[test]
+    fn test_load_eval_params() {
+        let startup = EvalParams {
+            tropism_weight: 99,
+            ..EvalParams::default()
+        };
+        assert_eq!(loaded_params(Some("<empty>"), &startup), startup);
+        assert!(load_eval_file(Some("/nonexistent/params.toml"), &startup).is_err());
+
+        let path = std::env::temp_dir().join(format!("uci_eval_params_{}.toml", std::process::id()));
+        std::fs::write(&path, "rook_value = 450\n").unwrap();
+        let loaded = loaded_params(path.to_str(), &startup);
+        std::fs::remove_file(&path).unwrap();
+        // A file starts from the defaults, not from the startup weights
+        assert_eq!(loaded.rook_value, 450);
+    }
+
+    #[test]
+    fn test_load_network_eval_file() {
+        let path = std::env::temp_dir().join(format!("uci_eval_network_{}.nnue", std::process::id()));
+        Network::zeroed().write(&path).unwrap();
+        let loaded = load_eval_file(path.to_str(), &EvalParams::default());
+        std::fs::remove_file(&path).unwrap();
+        let network = match loaded {
+            Ok(EvalFile::Network(network)) => network,
+            _ => panic!("expected a network"),
+        };
+
+        // The zero network scores the start position 0; the hand-crafted one does not
+        let mut board = Board::setup_amazon_vs_rook();
+        let nnue = select_evaluator(&EvalParams::default(), Some(&network), true);
+        assert_eq!(nnue.evaluate(&mut board), 0);
+        let hand_crafted = select_evaluator(&EvalParams::default(), Some(&network), false);
+        assert_ne!(hand_crafted.evaluate(&mut board), 0);
+        let fallback = select_evaluator(&EvalParams::default(), None, true);
+        assert_ne!(fallback.evaluate(&mut board), 0);
+    }
+
+    #[test]
+    fn test_load_tablebase_path() {
+        assert!(load_tablebase(None).unwrap().is_none());
+        assert!(load_tablebase(Some("<empty>")).unwrap().is_none());
+        assert!(load_tablebase(Some("/nonexistent/tablebases")).is_err());
+
+        let dir = std::env::temp_dir().join(format!("uci_tablebase_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let loaded = load_tablebase(dir.to_str());
+        assert_eq!(loaded.unwrap().unwrap().max_pieces(), 0);
+
+        // A Syzygy directory is reported instead of loading as no tables
+        std::fs::write(dir.join("KAvKR.rtbz"), b"").unwrap();
+        let loaded = load_tablebase(dir.to_str());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(loaded, Err(TablebaseError::Syzygy(_))));
+    }
+
+    #[test]
+    fn test_parse_go_negative_clock() {
+        let limits = parse_go_command(&["wtime", "-20", "btime", "1000"], 4);
+        assert_eq!(limits.wtime, Some(0));
     }
 
     #[test]
     fn test_move_to_uci() {
-        assert_eq!(move_to_uci((7, 3), (2, 3)), "d1d6");
-        assert_eq!(move_to_uci((6, 4), (4, 4)), "e2e4");
+        assert_eq!(move_to_uci(&Move::new((7, 3), (2, 3))), "d1d6");
+        assert_eq!(move_to_uci(&Move::new((6, 4), (4, 4))), "e2e4");
+        assert_eq!(
+            move_to_uci(&Move::with_promotion((1, 0), (0, 0), PieceType::Knight)),
+            "a7a8n"
+        );
     }
 }
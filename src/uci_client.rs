@@ -0,0 +1,531 @@
+// UCI client
+// Drives an external UCI engine (Fairy-Stockfish, another build of this
+// engine, ...) as a child process: the "uci"/"isready" handshake, positions,
+// "go" with the usual limits, and the "info"/"bestmove" replies.
+//
+// The engine's output is read on a helper thread, so every wait has a
+// timeout; an engine that exits or stops answering is reported as an error
+// instead of hanging the caller.
+
+use crate::board::{Board, Color, Move};
+use crate::search::{SearchInfo, SearchLimits, CHECKMATE_SCORE};
+use crate::uci::{move_to_uci, parse_uci_move};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time allowed for the handshake and other quick replies
+pub const DEFAULT_REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed for a search without a clock (fixed depth)
+pub const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Extra time a timed search may take to answer before it counts as hung
+const SEARCH_GRACE: Duration = Duration::from_secs(5);
+
+/// How long to wait for "bestmove" after "stop", and for exit after "quit"
+const STOP_GRACE: Duration = Duration::from_millis(500);
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Ways talking to an engine can fail
+#[derive(Debug)]
+pub enum UciClientError {
+    /// The process could not be started
+    Spawn(io::Error),
+    Io(io::Error),
+    /// No reply in time (what was being waited for)
+    Timeout(String),
+    /// The process exited (exit code, if any)
+    Exited(Option<i32>),
+    /// A reply that does not follow the protocol
+    Protocol(String),
+}
+
+impl std::fmt::Display for UciClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UciClientError::Spawn(e) => write!(f, "cannot start engine: {}", e),
+            UciClientError::Io(e) => write!(f, "{}", e),
+            UciClientError::Timeout(waiting) => write!(f, "timed out waiting for {}", waiting),
+            UciClientError::Exited(Some(code)) => write!(f, "engine exited with code {}", code),
+            UciClientError::Exited(None) => write!(f, "engine exited"),
+            UciClientError::Protocol(line) => write!(f, "unexpected reply: {}", line),
+        }
+    }
+}
+
+impl std::error::Error for UciClientError {}
+
+impl From<io::Error> for UciClientError {
+    fn from(e: io::Error) -> Self {
+        UciClientError::Io(e)
+    }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+/// Convert "cp <n>" / "mate <moves>" to a search score (see CHECKMATE_SCORE)
+fn uci_to_score(kind: &str, value: i32) -> Option<i32> {
+    match kind {
+        "cp" => Some(value),
+        "mate" if value > 0 => Some(CHECKMATE_SCORE - (2 * value - 1)),
+        "mate" => Some(-CHECKMATE_SCORE + 2 * -value),
+        _ => None,
+    }
+}
+
+/// Parse an "info" line that carries a score; other info lines give None
+/// PV moves are parsed without checking them against a position
+pub fn parse_info(line: &str) -> Option<SearchInfo> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.first() != Some(&"info") {
+        return None;
+    }
+    let number = |i: usize| words.get(i + 1).and_then(|w| w.parse::<i64>().ok());
+
+    let mut info = SearchInfo {
+        depth: 0,
+        seldepth: 0,
+        score: 0,
+        nodes: 0,
+        time: Duration::ZERO,
+        hashfull: 0,
+        pv: Vec::new(),
+    };
+    let mut score = None;
+    let mut i = 1;
+    while i < words.len() {
+        match words[i] {
+            "depth" => info.depth = number(i)? as i32,
+            "seldepth" => info.seldepth = number(i)? as i32,
+            "nodes" => info.nodes = number(i)? as u64,
+            "hashfull" => info.hashfull = number(i)? as usize,
+            "time" => info.time = Duration::from_millis(number(i)? as u64),
+            "score" => {
+                score = uci_to_score(words.get(i + 1)?, words.get(i + 2)?.parse().ok()?);
+                i += 1;
+            }
+            "pv" => {
+                info.pv = words[i + 1..].iter().map_while(|w| parse_uci_move(w)).collect();
+                break;
+            }
+            // Free text runs to the end of the line
+            "string" => break,
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    info.score = score?;
+    Some(info)
+}
+
+/// Parse "bestmove <move> [ponder <move>]"
+/// The outer None means another line; "bestmove 0000" / "(none)" gives Some(None)
+pub fn parse_bestmove(line: &str) -> Option<Option<(Move, Option<Move>)>> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("bestmove") {
+        return None;
+    }
+    let best = words.next().and_then(parse_uci_move);
+    let ponder = match (words.next(), words.next()) {
+        (Some("ponder"), Some(mv)) => parse_uci_move(mv),
+        _ => None,
+    };
+    Some(best.map(|best| (best, ponder)))
+}
+
+/// The "go" command for the given limits
+pub fn go_command(limits: &SearchLimits) -> String {
+    let mut command = String::from("go");
+    let mut push = |name: &str, value: Option<u64>| {
+        if let Some(value) = value {
+            command.push_str(&format!(" {} {}", name, value));
+        }
+    };
+    push("depth", limits.depth.map(|d| d as u64));
+    push("movetime", limits.movetime);
+    push("wtime", limits.wtime);
+    push("btime", limits.btime);
+    push("winc", limits.winc);
+    push("binc", limits.binc);
+    push("movestogo", limits.movestogo.map(u64::from));
+    if limits.infinite {
+        command.push_str(" infinite");
+    }
+    command
+}
+
+/// The "position" command for a game from `start` with `moves` played
+pub fn position_command(start: &Board, moves: &[Move]) -> String {
+    let mut command = format!("position fen {}", start.to_fen());
+    if !moves.is_empty() {
+        command.push_str(" moves");
+        for mv in moves {
+            command.push(' ');
+            command.push_str(&move_to_uci(mv));
+        }
+    }
+    command
+}
+
+// =============================================================================
+// Engine Process
+// =============================================================================
+
+/// Reply to "go"
+#[derive(Clone, Debug)]
+pub struct GoResult {
+    /// None when the engine has no move ("bestmove 0000")
+    pub best_move: Option<Move>,
+    pub ponder: Option<Move>,
+    /// Last "info" line with a score
+    pub info: Option<SearchInfo>,
+}
+
+/// A running UCI engine
+pub struct UciEngine {
+    name: String,
+    author: String,
+    /// "option ..." lines from the handshake
+    options: Vec<String>,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    reply_timeout: Duration,
+    search_timeout: Duration,
+}
+
+impl UciEngine {
+    /// Start an engine from a command line (program and arguments separated by spaces)
+    pub fn start(command: &str) -> Result<Self, UciClientError> {
+        let mut words = command.split_whitespace();
+        let program = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        UciEngine::spawn(program, &args)
+    }
+
+    /// Start `program` with `args` and complete the "uci" handshake
+    pub fn spawn(program: &str, args: &[&str]) -> Result<Self, UciClientError> {
+        UciEngine::spawn_with_timeout(program, args, DEFAULT_REPLY_TIMEOUT)
+    }
+
+    /// `spawn` with a custom timeout for the handshake and other quick replies
+    pub fn spawn_with_timeout(program: &str, args: &[&str], reply_timeout: Duration) -> Result<Self, UciClientError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(UciClientError::Spawn)?;
+        let stdin = child.stdin.take().expect("Piped stdin");
+        let stdout = child.stdout.take().expect("Piped stdout");
+
+        // Forward output lines until the engine closes its end
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = UciEngine {
+            name: program.to_string(),
+            author: String::new(),
+            options: Vec::new(),
+            child,
+            stdin,
+            lines,
+            reply_timeout,
+            search_timeout: DEFAULT_SEARCH_TIMEOUT,
+        };
+        engine.handshake()?;
+        Ok(engine)
+    }
+
+    /// "uci" until "uciok", collecting the engine's id and options
+    fn handshake(&mut self) -> Result<(), UciClientError> {
+        self.send("uci")?;
+        let deadline = Instant::now() + self.reply_timeout;
+        loop {
+            let line = self.read_line(deadline, "uciok")?;
+            let line = line.trim();
+            if line == "uciok" {
+                return Ok(());
+            } else if let Some(name) = line.strip_prefix("id name ") {
+                self.name = name.to_string();
+            } else if let Some(author) = line.strip_prefix("id author ") {
+                self.author = author.to_string();
+            } else if line.starts_with("option ") {
+                self.options.push(line.to_string());
+            }
+        }
+    }
+
+    /// Name from "id name" (the program path if the engine sent none)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// The "option ..." lines the engine declared
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// Time allowed for a search without a clock
+    pub fn set_search_timeout(&mut self, timeout: Duration) {
+        self.search_timeout = timeout;
+    }
+
+    /// Send one command line
+    pub fn send(&mut self, command: &str) -> Result<(), UciClientError> {
+        writeln!(self.stdin, "{}", command).and_then(|_| self.stdin.flush()).map_err(|e| {
+            // A closed pipe means the engine is gone; report how it ended
+            match self.child.try_wait() {
+                Ok(Some(status)) => UciClientError::Exited(status.code()),
+                _ => UciClientError::Io(e),
+            }
+        })
+    }
+
+    /// Next output line, waiting until `deadline`
+    fn read_line(&mut self, deadline: Instant, waiting_for: &str) -> Result<String, UciClientError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(UciClientError::Timeout(waiting_for.to_string())),
+            Err(RecvTimeoutError::Disconnected) => {
+                let status = self.child.wait()?;
+                Err(UciClientError::Exited(status.code()))
+            }
+        }
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), UciClientError> {
+        self.send(&format!("setoption name {} value {}", name, value))
+    }
+
+    /// "isready" until "readyok"
+    pub fn is_ready(&mut self) -> Result<(), UciClientError> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.reply_timeout;
+        while self.read_line(deadline, "readyok")?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    /// "ucinewgame", then wait until the engine is ready
+    pub fn new_game(&mut self) -> Result<(), UciClientError> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    /// Set up a game from `start` with `moves` played
+    pub fn set_position(&mut self, start: &Board, moves: &[Move]) -> Result<(), UciClientError> {
+        self.send(&position_command(start, moves))
+    }
+
+    /// How long a search with these limits may take before it counts as hung
+    fn search_deadline(&self, limits: &SearchLimits, side_time: Option<u64>) -> Instant {
+        let budget = match (limits.movetime, side_time) {
+            (Some(movetime), _) => Duration::from_millis(movetime) + SEARCH_GRACE,
+            (None, Some(time)) => Duration::from_millis(time) + SEARCH_GRACE,
+            (None, None) => self.search_timeout,
+        };
+        Instant::now() + budget
+    }
+
+    /// Search the current position; `infinite` searches are not supported
+    /// A search that overruns is sent "stop"; one that still does not answer
+    /// is killed and reported as a timeout
+    pub fn go(&mut self, limits: &SearchLimits, side: Color) -> Result<GoResult, UciClientError> {
+        let side_time = match side {
+            Color::White => limits.wtime,
+            Color::Black => limits.btime,
+        };
+        let limits = SearchLimits {
+            infinite: false,
+            ponder: false,
+            ..limits.clone()
+        };
+        self.send(&go_command(&limits))?;
+
+        let mut deadline = self.search_deadline(&limits, side_time);
+        let mut stopped = false;
+        let mut last_info = None;
+        loop {
+            let line = match self.read_line(deadline, "bestmove") {
+                Ok(line) => line,
+                Err(UciClientError::Timeout(_)) if !stopped => {
+                    self.send("stop")?;
+                    stopped = true;
+                    deadline = Instant::now() + STOP_GRACE;
+                    continue;
+                }
+                Err(UciClientError::Timeout(waiting)) => {
+                    let _ = self.child.kill();
+                    return Err(UciClientError::Timeout(waiting));
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(info) = parse_info(&line) {
+                last_info = Some(info);
+            } else if let Some(best) = parse_bestmove(&line) {
+                return Ok(GoResult {
+                    best_move: best.map(|(mv, _)| mv),
+                    ponder: best.and_then(|(_, ponder)| ponder),
+                    info: last_info,
+                });
+            } else if line.starts_with("bestmove") {
+                return Err(UciClientError::Protocol(line));
+            }
+        }
+    }
+
+    /// "go perft N" (a Stockfish extension) in the current position: the total of
+    /// its "Nodes searched: <n>" line
+    pub fn perft(&mut self, depth: u32) -> Result<u64, UciClientError> {
+        self.send(&format!("go perft {}", depth))?;
+        let deadline = Instant::now() + self.search_timeout;
+        loop {
+            let line = self.read_line(deadline, "Nodes searched")?;
+            if let Some(nodes) = line.trim().strip_prefix("Nodes searched:") {
+                return nodes.trim().parse().map_err(|_| UciClientError::Protocol(line.clone()));
+            }
+        }
+    }
+
+    /// Ask the engine to quit, killing it if it does not
+    pub fn quit(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        if matches!(self.child.try_wait(), Ok(None)) {
+            let _ = self.send("quit");
+            let deadline = Instant::now() + STOP_GRACE;
+            while Instant::now() < deadline && matches!(self.child.try_wait(), Ok(None)) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uci::score_to_uci;
+
+    #[test]
+    fn test_parse_info() {
+        let info = parse_info("info depth 5 seldepth 7 multipv 1 score cp -31 nodes 2000 nps 4000 time 500 pv d1h5 e8d8")
+            .unwrap();
+        assert_eq!((info.depth, info.seldepth, info.score, info.nodes), (5, 7, -31, 2000));
+        assert_eq!(info.time, Duration::from_millis(500));
+        assert_eq!(info.pv.iter().map(move_to_uci).collect::<Vec<_>>(), vec!["d1h5", "e8d8"]);
+
+        assert_eq!(parse_info("info depth 9 score mate 2 pv d1h5").unwrap().score, CHECKMATE_SCORE - 3);
+        assert_eq!(parse_info("info depth 9 score mate -1 pv e8d8").unwrap().score, -CHECKMATE_SCORE + 2);
+        assert_eq!(parse_info("info depth 3 score cp 12 lowerbound").unwrap().score, 12);
+        assert!(parse_info("info depth 9 currmove d1h5 currmovenumber 1").is_none());
+        assert!(parse_info("info string score cp 5").is_none());
+        assert!(parse_info("bestmove d1h5").is_none());
+    }
+
+    #[test]
+    fn test_mate_scores_round_trip() {
+        for score in ["mate 3", "mate -4", "cp 250"] {
+            let parsed = parse_info(&format!("info score {}", score)).unwrap().score;
+            assert_eq!(score_to_uci(parsed), score);
+        }
+    }
+
+    #[test]
+    fn test_parse_bestmove() {
+        let (best, ponder) = parse_bestmove("bestmove d1h5 ponder e8d8").unwrap().unwrap();
+        assert_eq!(move_to_uci(&best), "d1h5");
+        assert_eq!(ponder.map(|mv| move_to_uci(&mv)).as_deref(), Some("e8d8"));
+        assert!(parse_bestmove("bestmove 0000").unwrap().is_none());
+        assert!(parse_bestmove("bestmove (none)").unwrap().is_none());
+        assert!(parse_bestmove("info depth 1").is_none());
+    }
+
+    #[test]
+    fn test_go_and_position_commands() {
+        assert_eq!(go_command(&SearchLimits::depth(6)), "go depth 6");
+        let limits = SearchLimits {
+            wtime: Some(1000),
+            btime: Some(900),
+            winc: Some(10),
+            movestogo: Some(20),
+            ..SearchLimits::default()
+        };
+        assert_eq!(go_command(&limits), "go wtime 1000 btime 900 winc 10 movestogo 20");
+
+        let board = Board::setup_amazon_vs_rook();
+        let mv = parse_uci_move("e1f2").unwrap();
+        assert_eq!(position_command(&board, &[mv]), format!("position fen {} moves e1f2", board.to_fen()));
+    }
+
+    #[test]
+    fn test_missing_program() {
+        let result = UciEngine::spawn("/nonexistent/engine", &[]);
+        assert!(matches!(result, Err(UciClientError::Spawn(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_crash_during_handshake() {
+        let result = UciEngine::spawn("sh", &["-c", "read line; exit 3"]);
+        assert!(matches!(result, Err(UciClientError::Exited(Some(3)))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_silent_engine_times_out() {
+        let result = UciEngine::spawn_with_timeout("sh", &["-c", "sleep 5"], Duration::from_millis(100));
+        assert!(matches!(result, Err(UciClientError::Timeout(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hung_search_is_stopped() {
+        // Answers the handshake, then ignores "go" but obeys "stop"
+        let script = "read l; echo 'id name stub'; echo uciok; \
+                      while read l; do case $l in stop) echo 'bestmove e1f2';; quit) exit 0;; esac; done";
+        let mut engine = UciEngine::spawn("sh", &["-c", script]).unwrap();
+        assert_eq!(engine.name(), "stub");
+        engine.set_search_timeout(Duration::from_millis(50));
+        let start = Instant::now();
+        let result = engine.go(&SearchLimits::depth(3), Color::White).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(result.best_move.map(|mv| move_to_uci(&mv)).as_deref(), Some("e1f2"));
+    }
+}
@@ -0,0 +1,257 @@
+// Variant registry
+// Each UCI_Variant value names a start position, the pieces allowed in it and
+// the rules that differ between variants (currently the promotion pieces).
+// "amazon" keeps its original meaning, the K+A vs K+R endgame this engine was
+// built for, so existing GUI and cutechess configurations still set up the
+// same position; the full board with Amazons is "amazon-chess".
+
+use crate::board::{
+    Board, FenError, PieceType, ACTRESS_PROMOTIONS, AMAZON_PROMOTIONS, STANDARD_PROMOTIONS,
+};
+
+/// A playable variant
+pub struct Variant {
+    /// UCI_Variant value
+    pub name: &'static str,
+    /// Other names accepted for UCI_Variant
+    pub aliases: &'static [&'static str],
+    /// Fairy-Stockfish/cutechess name, used for PGN Variant tags and for
+    /// UCI_Variant on external engines; positions are always given as FENs,
+    /// so the endgames share the name of the variant that has their pieces
+    pub external_name: &'static str,
+    /// Position used for "position startpos" and "ucinewgame"
+    pub start_fen: &'static str,
+    /// Piece types a FEN may contain in this variant
+    pub pieces: &'static [PieceType],
+    /// Pieces a pawn may promote to
+    pub promotion_types: &'static [PieceType],
+}
+
+/// Pieces of the lone-amazon endgames
+const AMAZON_ENDGAME_PIECES: &[PieceType] = &[PieceType::King, PieceType::Amazon, PieceType::Rook];
+
+/// All registered variants; the first one is the default
+pub const VARIANTS: [Variant; 5] = [
+    Variant {
+        name: "amazon",
+        external_name: "amazon",
+        aliases: &["amazon-vs-rook"],
+        start_fen: "r3k3/8/8/8/8/8/8/3AK3 w - - 0 1",
+        pieces: AMAZON_ENDGAME_PIECES,
+        promotion_types: AMAZON_PROMOTIONS,
+    },
+    // The benchmark position from startpos.epd
+    Variant {
+        name: "two-amazons-vs-rooks",
+        external_name: "amazon",
+        aliases: &[],
+        start_fen: "8/3r1r2/8/3rkr2/8/8/8/3AKA2 w - - 0 1",
+        pieces: AMAZON_ENDGAME_PIECES,
+        promotion_types: AMAZON_PROMOTIONS,
+    },
+    // Amazon chess: the Amazon replaces the Queen
+    Variant {
+        name: "amazon-chess",
+        external_name: "amazon",
+        aliases: &[],
+        start_fen: "rnbakbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBAKBNR w KQkq - 0 1",
+        pieces: &[
+            PieceType::King,
+            PieceType::Amazon,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Pawn,
+        ],
+        promotion_types: AMAZON_PROMOTIONS,
+    },
+    Variant {
+        name: "chess",
+        external_name: "chess",
+        aliases: &[],
+        start_fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        pieces: &[
+            PieceType::King,
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Pawn,
+        ],
+        promotion_types: STANDARD_PROMOTIONS,
+    },
+    // Actress chess: the Actress replaces the Queen
+    Variant {
+        name: "actress",
+        external_name: "actress",
+        aliases: &[],
+        start_fen: "rnbxkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBXKBNR w KQkq - 0 1",
+        pieces: &[
+            PieceType::King,
+            PieceType::Actress,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Pawn,
+        ],
+        promotion_types: ACTRESS_PROMOTIONS,
+    },
+];
+
+/// The variant used until UCI_Variant is set
+pub fn default_variant() -> &'static Variant {
+    &VARIANTS[0]
+}
+
+/// Look up a variant by its UCI name or an alias (case-insensitive)
+pub fn find_variant(name: &str) -> Option<&'static Variant> {
+    VARIANTS.iter().find(|v| {
+        v.name.eq_ignore_ascii_case(name) || v.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+    })
+}
+
+/// The variant a PGN Variant tag names: a registered name or alias, or else
+/// the first variant with that external name whose piece set fits the FEN
+pub fn find_external_variant(name: &str, fen: Option<&str>) -> Option<&'static Variant> {
+    let fits = |variant: &&Variant| fen.is_none_or(|fen| variant.from_fen(fen).is_ok());
+    find_variant(name).filter(fits).or_else(|| {
+        VARIANTS
+            .iter()
+            .filter(|v| v.external_name.eq_ignore_ascii_case(name))
+            .find(fits)
+    })
+}
+
+/// The first variant whose piece set covers every piece on the board, for
+/// labelling games that were set up from a FEN; falls back to the default
+pub fn variant_of(board: &Board) -> &'static Variant {
+    let covers = |variant: &Variant| {
+        (0..8).all(|row| {
+            (0..8).all(|col| match board.get_piece((row, col)) {
+                Some(piece) => variant.pieces.contains(&piece.piece_type),
+                None => true,
+            })
+        })
+    };
+    VARIANTS.iter().find(|variant| covers(variant)).unwrap_or(default_variant())
+}
+
+impl Variant {
+    /// The start position, with this variant's rules applied
+    pub fn start_position(&self) -> Board {
+        self.from_fen(self.start_fen).expect("Invalid built-in variant FEN")
+    }
+
+    /// Parse a FEN for this variant: pieces outside the variant's set are rejected
+    pub fn from_fen(&self, fen: &str) -> Result<Board, FenError> {
+        let mut board = Board::from_fen(fen)?;
+        for row in 0..8 {
+            for col in 0..8 {
+                if let Some(piece) = board.get_piece((row, col)) {
+                    if !self.pieces.contains(&piece.piece_type) {
+                        return Err(FenError::UnknownPiece(piece.to_char()));
+                    }
+                }
+            }
+        }
+        self.apply_rules(&mut board);
+        Ok(board)
+    }
+
+    /// Set the variant-specific rules on a board
+    pub fn apply_rules(&self, board: &mut Board) {
+        board.set_promotion_types(self.promotion_types);
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece};
+    use crate::perft::perft;
+
+    #[test]
+    fn test_start_positions_parse() {
+        for variant in &VARIANTS {
+            let board = variant.start_position();
+            assert_eq!(board.to_fen(), variant.start_fen, "{}", variant.name);
+            assert_eq!(board.promotion_types(), variant.promotion_types);
+        }
+    }
+
+    #[test]
+    fn test_default_variant_is_amazon_vs_rook() {
+        let board = default_variant().start_position();
+        assert_eq!(board.to_fen(), Board::setup_amazon_vs_rook().to_fen());
+        assert_eq!(default_variant().name, "amazon");
+    }
+
+    #[test]
+    fn test_amazon_keeps_its_original_position() {
+        // Configurations written for the original engine select "amazon"
+        let amazon = find_variant("amazon").unwrap();
+        assert_eq!(amazon.start_position().to_fen(), Board::setup_amazon_vs_rook().to_fen());
+        assert_eq!(find_variant("amazon-vs-rook").map(|v| v.name), Some("amazon"));
+        let full = find_variant("amazon-chess").unwrap();
+        assert_eq!(full.start_fen, "rnbakbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBAKBNR w KQkq - 0 1");
+    }
+
+    #[test]
+    fn test_find_variant() {
+        assert_eq!(find_variant("chess").map(|v| v.name), Some("chess"));
+        assert_eq!(find_variant("Two-Amazons-vs-Rooks").map(|v| v.name), Some("two-amazons-vs-rooks"));
+        assert!(find_variant("crazyhouse").is_none());
+    }
+
+    #[test]
+    fn test_variant_of_position() {
+        let name = |fen: &str| variant_of(&Board::from_fen(fen).unwrap()).name;
+        assert_eq!(name("r3k3/8/8/8/8/8/8/3AK3 w - - 0 1"), "amazon");
+        assert_eq!(name("rnbakbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBAKBNR w KQkq - 0 1"), "amazon-chess");
+        assert_eq!(name("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1"), "chess");
+        assert_eq!(name("4k3/8/8/8/8/8/8/3XK3 w - - 0 1"), "actress");
+    }
+
+    #[test]
+    fn test_external_names() {
+        let external = |fen: &str| variant_of(&Board::from_fen(fen).unwrap()).external_name;
+        assert_eq!(external("r3k3/8/8/8/8/8/8/3AK3 w - - 0 1"), "amazon");
+        assert_eq!(external("8/3r1r2/8/3rkr2/8/8/8/3AKA2 w - - 0 1"), "amazon");
+        assert_eq!(external("rnbakbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBAKBNR w KQkq - 0 1"), "amazon");
+        assert_eq!(external("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1"), "chess");
+
+        // Fairy-Stockfish's "amazon" covers the endgames and the full board
+        let full = "rnbakbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBAKBNR w KQkq - 0 1";
+        assert_eq!(find_external_variant("amazon", Some(full)).map(|v| v.name), Some("amazon-chess"));
+        let endgame = "8/8/4k3/4r3/8/8/8/3AK3 w - - 0 1";
+        assert_eq!(find_external_variant("amazon", Some(endgame)).map(|v| v.name), Some("amazon"));
+        assert_eq!(find_external_variant("amazon", None).map(|v| v.name), Some("amazon"));
+        assert!(find_external_variant("chess", Some(endgame)).is_none());
+    }
+
+    #[test]
+    fn test_piece_set_is_enforced() {
+        let chess = find_variant("chess").unwrap();
+        assert_eq!(
+            chess.from_fen("4k3/8/8/8/8/8/8/3AK3 w - - 0 1").err(),
+            Some(FenError::UnknownPiece('A'))
+        );
+        let amazon = find_variant("amazon").unwrap();
+        let board = amazon.from_fen("4k3/8/8/8/8/8/8/3AK3 w - - 0 1").unwrap();
+        assert_eq!(board.get_piece((7, 3)), Some(Piece::new(PieceType::Amazon, Color::White)));
+    }
+
+    #[test]
+    fn test_orthodox_start_positions() {
+        // 16 pawn and 4 knight moves, plus the Amazon's knight jumps to c3/e3
+        // and the Actress' camel leaps to c4/e4
+        for (name, moves) in [("chess", 20), ("amazon-chess", 22), ("actress", 24)] {
+            let mut board = find_variant(name).unwrap().start_position();
+            assert_eq!(perft(&mut board, 1), moves, "{}", name);
+        }
+    }
+}
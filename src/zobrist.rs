@@ -0,0 +1,148 @@
+// Zobrist hashing
+// Every (piece type, color, square) combination, the side to move, each
+// castling right and each en passant file gets a random 64-bit key. A position's hash is the XOR of the keys of everything
+// on the board, so moving a piece only needs two or three XORs.
+
+use crate::board::{CastleSide, Color, Piece, PieceType, Square};
+
+/// Random keys used to build position hashes
+pub struct ZobristKeys {
+    /// Indexed by [piece type][color][square index]
+    pieces: [[[u64; 64]; 2]; PieceType::COUNT],
+    /// XORed in when Black is to move
+    black_to_move: u64,
+    /// Indexed by [color][castle side], XORed in while the right is held
+    castling: [[u64; 2]; 2],
+    /// Indexed by the file of the en passant square
+    en_passant: [u64; 8],
+}
+
+/// The key table, generated at compile time from a fixed seed
+/// so hashes are reproducible between runs
+pub static ZOBRIST: ZobristKeys = ZobristKeys::generate(0x9E37_79B9_7F4A_7C15);
+
+/// SplitMix64 step - small, fast, and usable in const context
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+impl ZobristKeys {
+    const fn generate(seed: u64) -> Self {
+        let mut state = seed;
+        let mut pieces = [[[0u64; 64]; 2]; PieceType::COUNT];
+
+        let mut piece = 0;
+        while piece < PieceType::COUNT {
+            let mut color = 0;
+            while color < 2 {
+                let mut square = 0;
+                while square < 64 {
+                    let (next, key) = splitmix64(state);
+                    state = next;
+                    pieces[piece][color][square] = key;
+                    square += 1;
+                }
+                color += 1;
+            }
+            piece += 1;
+        }
+
+        let (next, black_to_move) = splitmix64(state);
+        state = next;
+
+        let mut castling = [[0u64; 2]; 2];
+        let mut i = 0;
+        while i < 4 {
+            let (next, key) = splitmix64(state);
+            state = next;
+            castling[i / 2][i % 2] = key;
+            i += 1;
+        }
+
+        let mut en_passant = [0u64; 8];
+        let mut file = 0;
+        while file < 8 {
+            let (next, key) = splitmix64(state);
+            state = next;
+            en_passant[file] = key;
+            file += 1;
+        }
+
+        ZobristKeys {
+            pieces,
+            black_to_move,
+            castling,
+            en_passant,
+        }
+    }
+
+    /// Key for a piece standing on a square
+    pub fn piece(&self, piece: Piece, square: Square) -> u64 {
+        let color = match piece.color {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+        let index = (square.0 as usize) * 8 + square.1 as usize;
+        self.pieces[piece.piece_type.index()][color][index]
+    }
+
+    /// Key XORed in when Black is to move
+    pub fn side_to_move(&self) -> u64 {
+        self.black_to_move
+    }
+
+    /// Key XORed in while a castling right is held
+    pub fn castling(&self, color: Color, side: CastleSide) -> u64 {
+        let color = match color {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+        self.castling[color][side.index()]
+    }
+
+    /// Key XORed in while an en passant capture is possible on the given file
+    pub fn en_passant(&self, file: u8) -> u64 {
+        self.en_passant[file as usize]
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut keys = Vec::new();
+        for piece_type in PieceType::ALL {
+            for color in [Color::White, Color::Black] {
+                for row in 0..8 {
+                    for col in 0..8 {
+                        keys.push(ZOBRIST.piece(Piece::new(piece_type, color), (row, col)));
+                    }
+                }
+            }
+        }
+        keys.push(ZOBRIST.side_to_move());
+        for color in [Color::White, Color::Black] {
+            for side in CastleSide::BOTH {
+                keys.push(ZOBRIST.castling(color, side));
+            }
+        }
+        for file in 0..8 {
+            keys.push(ZOBRIST.en_passant(file));
+        }
+
+        let count = keys.len();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), count, "All Zobrist keys should be unique");
+    }
+}
@@ -0,0 +1,85 @@
+// Perft regression suite
+// Runs every position in tests/perft_positions.epd through both board
+// representations and compares the node counts with the fixture values.
+// With FAIRY_STOCKFISH=<path to fairy-stockfish> the fixture itself is checked
+// against Fairy-Stockfish ("go perft"), using tests/fairy_variants.ini.
+
+use rust_chess_engine::bitboard::BitBoard;
+use rust_chess_engine::board::Board;
+use rust_chess_engine::perft::perft;
+use rust_chess_engine::uci_client::UciEngine;
+
+/// One fixture line: a FEN and the expected node count per depth
+struct PerftCase {
+    fen: String,
+    expected: Vec<(u32, u64)>,
+}
+
+fn load_cases() -> Vec<PerftCase> {
+    let text = include_str!("perft_positions.epd");
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.split(';');
+            let fen = fields.next().unwrap().trim().to_string();
+            let expected = fields
+                .map(|field| {
+                    let (depth, nodes) = field.trim().split_once(' ').expect("Expected 'D<n> <nodes>'");
+                    let depth = depth.trim_start_matches('D').parse().expect("Bad depth");
+                    let nodes = nodes.trim().parse().expect("Bad node count");
+                    (depth, nodes)
+                })
+                .collect();
+            PerftCase { fen, expected }
+        })
+        .collect()
+}
+
+#[test]
+fn test_fixture_is_not_empty() {
+    assert!(load_cases().len() >= 5);
+}
+
+#[test]
+fn test_mailbox_perft() {
+    for case in load_cases() {
+        let mut board = Board::from_fen(&case.fen).expect("Fixture FEN should parse");
+        for &(depth, nodes) in &case.expected {
+            assert_eq!(perft(&mut board, depth), nodes, "{} depth {}", case.fen, depth);
+        }
+    }
+}
+
+#[test]
+fn test_bitboard_perft() {
+    for case in load_cases() {
+        let mut board = BitBoard::from_fen(&case.fen).expect("Fixture FEN should parse");
+        for &(depth, nodes) in &case.expected {
+            assert_eq!(perft(&mut board, depth), nodes, "{} depth {}", case.fen, depth);
+        }
+    }
+}
+
+#[test]
+fn test_fixture_matches_fairy_stockfish() {
+    let Some(program) = std::env::var("FAIRY_STOCKFISH").ok() else {
+        eprintln!("FAIRY_STOCKFISH is not set: fixture not checked against Fairy-Stockfish");
+        return;
+    };
+    let mut engine = UciEngine::spawn(&program, &[]).expect("Fairy-Stockfish should start");
+    eprintln!("Checking the fixture against {}", engine.name());
+    engine.set_option("VariantPath", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fairy_variants.ini")).unwrap();
+    engine.set_option("UCI_Variant", "amazon-actress").unwrap();
+    for case in load_cases() {
+        // Shredder-FEN castling rights (file letters) need Chess960 mode
+        let castling = case.fen.split_whitespace().nth(2).unwrap_or("-");
+        let chess960 = castling.chars().any(|c| !"KQkq-".contains(c));
+        engine.set_option("UCI_Chess960", if chess960 { "true" } else { "false" }).unwrap();
+        engine.send(&format!("position fen {}", case.fen)).unwrap();
+        for &(depth, nodes) in &case.expected {
+            assert_eq!(engine.perft(depth).unwrap(), nodes, "{} depth {}", case.fen, depth);
+        }
+    }
+    engine.quit();
+}
@@ -0,0 +1,41 @@
+// PGN files in the repository
+// Every recorded game must replay legally from its setup, and games
+// reported as mates must end in checkmate.
+
+use rust_chess_engine::board::Color;
+use rust_chess_engine::pgn::load_pgn;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_repository_games_replay() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files: Vec<_> = fs::read_dir(root)
+        .unwrap()
+        .chain(fs::read_dir(root.join("gui")).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pgn"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    for path in files {
+        let games = load_pgn(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        assert!(!games.is_empty(), "{}", path.display());
+
+        for game in games {
+            let mut board = game.start.clone();
+            for mv in &game.moves {
+                board.apply_move(*mv);
+            }
+            // Games that cutechess reports as mates end in checkmate
+            let last_comment = game.comments.last().cloned().flatten();
+            let ending = game.result_comment.clone().or(last_comment).unwrap_or_default();
+            if ending.ends_with("mates") {
+                let loser = board.side_to_move();
+                assert!(board.is_checkmate(loser), "{}: {}", path.display(), ending);
+                assert_eq!(game.result, if loser == Color::White { "0-1" } else { "1-0" });
+            }
+        }
+    }
+}
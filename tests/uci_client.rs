@@ -0,0 +1,58 @@
+// UCI client against our own binary
+// Starts the engine in UCI mode as a child process and plays through the
+// handshake, a search and a short match.
+
+use rust_chess_engine::board::Board;
+use rust_chess_engine::engine_match::{play_game, EngineSpec, GameResult};
+use rust_chess_engine::search::{SearchLimits, MATE_BOUND};
+use rust_chess_engine::uci::move_to_uci;
+use rust_chess_engine::uci_client::UciEngine;
+
+const ENGINE: &str = env!("CARGO_BIN_EXE_rust-chess-engine");
+
+#[test]
+fn test_handshake_and_search() {
+    let mut engine = UciEngine::spawn(ENGINE, &["--uci"]).unwrap();
+    assert!(!engine.name().is_empty());
+    assert!(engine.options().iter().any(|option| option.starts_with("option name Hash")));
+
+    engine.set_option("Hash", "1").unwrap();
+    engine.new_game().unwrap();
+
+    // Mate in one with the amazon
+    let board = Board::from_fen("k7/8/1K6/8/8/8/8/3A4 w - - 0 1").unwrap();
+    engine.set_position(&board, &[]).unwrap();
+    let reply = engine.go(&SearchLimits::depth(2), board.side_to_move()).unwrap();
+
+    let best = reply.best_move.unwrap();
+    let mut after = board.clone();
+    let legal = after.generate_legal_moves().into_iter().find(|mv| mv.same_as(&best));
+    after.apply_move(legal.expect("Engine played an illegal move"));
+    assert!(after.is_checkmate(after.side_to_move()), "{} is not mate", move_to_uci(&best));
+
+    let info = reply.info.unwrap();
+    assert!(info.score >= MATE_BOUND);
+    assert!(info.pv[0].same_as(&best));
+    engine.quit();
+}
+
+#[test]
+fn test_match_against_external_engine() {
+    let mut internal = EngineSpec::parse("name=internal,depth=2").unwrap().build("a").unwrap();
+    let spec = format!("cmd={} --uci,depth=2", ENGINE);
+    let mut external = EngineSpec::parse(&spec).unwrap().build("b").unwrap();
+
+    let start = Board::from_fen("k7/8/1K6/8/8/8/8/3A4 w - - 0 1").unwrap();
+    let record = play_game(external.as_mut(), internal.as_mut(), &start, 10);
+    assert_eq!(record.result, GameResult::WhiteWins);
+    assert_eq!(record.moves.len(), 1);
+}
+
+#[test]
+fn test_perft_command() {
+    let mut engine = UciEngine::spawn(ENGINE, &["--uci"]).unwrap();
+    engine.send("position fen r3k3/8/8/8/8/8/8/3AK3 w - - 0 1").unwrap();
+    assert_eq!(engine.perft(1).unwrap(), 25);
+    assert_eq!(engine.perft(2).unwrap(), 268);
+    engine.quit();
+}